@@ -1,10 +1,11 @@
-use veWILD::msg::{ ExecuteMsg, InstantiateMsg, QueryMsg };
+use veWILD::msg::{ ExecuteMsg, InstantiateMsg, QueryMsg, MigrateMsg };
 use cosmwasm_schema::write_api;
 
 fn main() {
     write_api! {
             instantiate: InstantiateMsg,
             execute: ExecuteMsg,
-            query: QueryMsg
+            query: QueryMsg,
+            migrate: MigrateMsg
         }
 }
@@ -0,0 +1,48 @@
+use cosmwasm_std::{ Addr, BankMsg, CosmosMsg, QuerierWrapper, StdResult, Uint128, coins };
+use cw20::Denom;
+
+use crate::cw20_client::CW20Client;
+
+/// Moves the locked asset, whether it is a CW20 token or a native (bank / tokenfactory) denom
+pub struct AssetClient<'a> {
+    querier: &'a QuerierWrapper<'a>,
+    asset: Denom,
+}
+
+impl<'a> AssetClient<'a> {
+    pub fn new(querier: &'a QuerierWrapper<'a>, asset: Denom) -> Self {
+        Self {
+            querier,
+            asset,
+        }
+    }
+
+    // Query balance
+    pub fn balance(&self, address: Addr) -> StdResult<Uint128> {
+        match &self.asset {
+            Denom::Cw20(contract_addr) =>
+                CW20Client::new(self.querier, contract_addr.clone()).balance(address),
+            Denom::Native(denom) => Ok(self.querier.query_balance(address, denom)?.amount),
+        }
+    }
+
+    // Transfer tokens
+    pub fn make_transfer_msg(&self, recipient: Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+        match &self.asset {
+            Denom::Cw20(contract_addr) => {
+                let msg = CW20Client::new(self.querier, contract_addr.clone()).make_transfer_msg(
+                    recipient,
+                    amount
+                )?;
+                Ok(msg.into())
+            }
+            Denom::Native(denom) =>
+                Ok(
+                    (BankMsg::Send {
+                        to_address: recipient.into(),
+                        amount: coins(amount.u128(), denom),
+                    }).into()
+                ),
+        }
+    }
+}
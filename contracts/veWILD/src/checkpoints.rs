@@ -0,0 +1,390 @@
+use cosmwasm_std::{ Addr, Order, StdResult, Storage, Uint128, Uint256, Uint64 };
+use cw_storage_plus::Bound;
+
+use crate::consts::POINT_PRECISION;
+use crate::state::{ Point, GLOBAL_POINTS, USER_POINTS, SLOPE_CHANGES, USER_SLOPE_CHANGES };
+
+/// Curve-style voting power that decays linearly to zero at the unlock time.
+///
+/// Every position change replaces the position point in its owner's point and in the
+/// global point. Both slopes drop at unlock times through USER_SLOPE_CHANGES and
+/// SLOPE_CHANGES, so the balance of an account with several positions and the total
+/// supply at any time are the last point advanced over the slope changes since.
+///
+/// slope = locked_balance / max_lock_period
+/// bias = slope * (locked_until - now), lock time capped at max_lock_period
+/// Points are kept scaled up by POINT_PRECISION, balances are scaled down when read.
+///
+/// BalanceOf, TotalSupplyAt, epoch fees and gauge votes follow this decay. The cw20
+/// balance, block rewards and DAO voting power use the ve balance set_balance stores,
+/// which only changes when a position of the account is touched or the account is kicked.
+///
+/// Returns the new position point, to be passed as old_point on its next change
+pub fn checkpoint(
+    storage: &mut dyn Storage,
+    account: &Addr,
+    old_point: &Point,
+    locked_balance: Uint128,
+    locked_until: Uint64,
+    current_ts: Uint64,
+    max_lock_period: Uint64
+) -> StdResult<Point> {
+    let old_end = old_point.end();
+
+    let lock_seconds = locked_until.saturating_sub(current_ts).min(max_lock_period);
+    let new_slope = if lock_seconds.is_zero() {
+        Uint256::zero()
+    } else {
+        (Uint256::from(locked_balance) * Uint256::from(POINT_PRECISION)) /
+            Uint256::from(max_lock_period.u64())
+    };
+    let new_point = Point {
+        bias: new_slope * Uint256::from(lock_seconds.u64()),
+        slope: new_slope,
+        ts: current_ts,
+    };
+    let new_end = new_point.end();
+
+    let mut user_point = user_point_at(storage, account, current_ts)?;
+    let mut global_point = global_point_at(storage, current_ts)?;
+
+    // Old slope is already gone from the account and global points once the old lock expired
+    if old_end > current_ts {
+        let old_bias = old_point.bias_at(current_ts);
+        for point in [&mut user_point, &mut global_point] {
+            point.bias = point.bias.saturating_sub(old_bias);
+            point.slope = point.slope.saturating_sub(old_point.slope);
+        }
+        update_slope_change(storage, old_end, |slope| slope.saturating_sub(old_point.slope))?;
+        update_user_slope_change(storage, account, old_end, |slope| {
+            slope.saturating_sub(old_point.slope)
+        })?;
+    }
+    if new_end > current_ts {
+        for point in [&mut user_point, &mut global_point] {
+            point.bias += new_point.bias;
+            point.slope += new_point.slope;
+        }
+        update_slope_change(storage, new_end, |slope| slope + new_point.slope)?;
+        update_user_slope_change(storage, account, new_end, |slope| slope + new_point.slope)?;
+    }
+
+    GLOBAL_POINTS.save(storage, current_ts.u64(), &global_point)?;
+    USER_POINTS.save(storage, (account, current_ts.u64()), &user_point)?;
+
+    Ok(new_point)
+}
+
+/// Decayed voting power of the account at ts, summed over its positions
+pub fn balance_of(storage: &dyn Storage, account: &Addr, ts: Uint64) -> StdResult<Uint128> {
+    to_balance(user_point_at(storage, account, ts)?.bias)
+}
+
+/// Sum of balance_of over all accounts at ts
+pub fn total_supply_at(storage: &dyn Storage, ts: Uint64) -> StdResult<Uint128> {
+    to_balance(global_point_at(storage, ts)?.bias)
+}
+
+pub fn to_balance(bias: Uint256) -> StdResult<Uint128> {
+    Ok(Uint128::try_from(bias / Uint256::from(POINT_PRECISION))?)
+}
+
+/// Last global point before ts, advanced to ts over the scheduled slope changes
+fn global_point_at(storage: &dyn Storage, ts: Uint64) -> StdResult<Point> {
+    let last_point = GLOBAL_POINTS.range(
+        storage,
+        None,
+        Some(Bound::inclusive(ts.u64())),
+        Order::Descending
+    ).next();
+    let point = match last_point {
+        Some(item) => item?.1,
+        None => {
+            return Ok(Point { ts, ..Point::default() });
+        }
+    };
+
+    let slope_changes = SLOPE_CHANGES.range(
+        storage,
+        Some(Bound::exclusive(point.ts.u64())),
+        Some(Bound::inclusive(ts.u64())),
+        Order::Ascending
+    );
+    advance_point(point, slope_changes, ts)
+}
+
+/// Last point of the account before ts, advanced to ts over its scheduled slope changes
+fn user_point_at(storage: &dyn Storage, account: &Addr, ts: Uint64) -> StdResult<Point> {
+    let last_point = USER_POINTS.prefix(account)
+        .range(storage, None, Some(Bound::inclusive(ts.u64())), Order::Descending)
+        .next();
+    let point = match last_point {
+        Some(item) => item?.1,
+        None => {
+            return Ok(Point { ts, ..Point::default() });
+        }
+    };
+
+    let slope_changes = USER_SLOPE_CHANGES.prefix(account).range(
+        storage,
+        Some(Bound::exclusive(point.ts.u64())),
+        Some(Bound::inclusive(ts.u64())),
+        Order::Ascending
+    );
+    advance_point(point, slope_changes, ts)
+}
+
+/// Applies the slope changes up to ts in order, then decays the point to ts
+pub fn advance_point(
+    mut point: Point,
+    slope_changes: impl Iterator<Item = StdResult<(u64, Uint256)>>,
+    ts: Uint64
+) -> StdResult<Point> {
+    for item in slope_changes {
+        let (change_ts, slope_change) = item?;
+        let change_ts = Uint64::from(change_ts);
+        point.bias = point.bias_at(change_ts);
+        point.slope = point.slope.saturating_sub(slope_change);
+        point.ts = change_ts;
+    }
+    point.bias = point.bias_at(ts);
+    point.ts = ts;
+
+    Ok(point)
+}
+
+fn update_slope_change(
+    storage: &mut dyn Storage,
+    ts: Uint64,
+    action: impl FnOnce(Uint256) -> Uint256
+) -> StdResult<()> {
+    let slope = action(SLOPE_CHANGES.may_load(storage, ts.u64())?.unwrap_or_default());
+    if slope.is_zero() {
+        SLOPE_CHANGES.remove(storage, ts.u64());
+    } else {
+        SLOPE_CHANGES.save(storage, ts.u64(), &slope)?;
+    }
+    Ok(())
+}
+
+fn update_user_slope_change(
+    storage: &mut dyn Storage,
+    account: &Addr,
+    ts: Uint64,
+    action: impl FnOnce(Uint256) -> Uint256
+) -> StdResult<()> {
+    let key = (account, ts.u64());
+    let slope = action(USER_SLOPE_CHANGES.may_load(storage, key)?.unwrap_or_default());
+    if slope.is_zero() {
+        USER_SLOPE_CHANGES.remove(storage, key);
+    } else {
+        USER_SLOPE_CHANGES.save(storage, key, &slope)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod checkpoints_tests {
+    use cosmwasm_std::testing::mock_dependencies;
+
+    use super::*;
+
+    const MAX_LOCK: u64 = 1000;
+
+    #[test]
+    fn test_balance_decays_to_zero() {
+        let mut deps = mock_dependencies();
+        let account = Addr::unchecked("user");
+        let locked = Uint128::from(1_000_000u64);
+
+        checkpoint(
+            deps.as_mut().storage,
+            &account,
+            &Point::default(),
+            locked,
+            Uint64::from(600u64),
+            Uint64::from(100u64),
+            Uint64::from(MAX_LOCK)
+        ).unwrap();
+
+        let storage = deps.as_ref().storage;
+        // slope 1000 per second, 500 seconds left
+        assert_eq!(balance_of(storage, &account, Uint64::from(99u64)).unwrap(), Uint128::zero());
+        assert_eq!(
+            balance_of(storage, &account, Uint64::from(100u64)).unwrap(),
+            Uint128::from(500_000u64)
+        );
+        assert_eq!(
+            balance_of(storage, &account, Uint64::from(350u64)).unwrap(),
+            Uint128::from(250_000u64)
+        );
+        assert_eq!(balance_of(storage, &account, Uint64::from(600u64)).unwrap(), Uint128::zero());
+        assert_eq!(balance_of(storage, &account, Uint64::from(900u64)).unwrap(), Uint128::zero());
+    }
+
+    #[test]
+    fn test_lock_below_max_lock_period_keeps_power() {
+        let mut deps = mock_dependencies();
+        let account = Addr::unchecked("user");
+        let max_lock = Uint64::from(1460u64 * 24 * 3600);
+
+        // 1 token of a 6 decimals denom, far fewer base units than max_lock seconds
+        checkpoint(
+            deps.as_mut().storage,
+            &account,
+            &Point::default(),
+            Uint128::from(1_000_000u64),
+            max_lock,
+            Uint64::zero(),
+            max_lock
+        ).unwrap();
+
+        let storage = deps.as_ref().storage;
+        assert_eq!(
+            balance_of(storage, &account, Uint64::zero()).unwrap(),
+            Uint128::from(999_999u64)
+        );
+        assert_eq!(
+            balance_of(storage, &account, max_lock / Uint64::from(2u8)).unwrap(),
+            Uint128::from(499_999u64)
+        );
+        assert_eq!(total_supply_at(storage, Uint64::zero()).unwrap(), Uint128::from(999_999u64));
+    }
+
+    #[test]
+    fn test_total_supply_follows_slope_changes() {
+        let mut deps = mock_dependencies();
+        let alice = Addr::unchecked("alice");
+        let bob = Addr::unchecked("bob");
+        let max_lock = Uint64::from(MAX_LOCK);
+
+        // alice: slope 1000 until 600, bob: slope 2000 until 400
+        let alice_point = checkpoint(
+            deps.as_mut().storage,
+            &alice,
+            &Point::default(),
+            Uint128::from(1_000_000u64),
+            Uint64::from(600u64),
+            Uint64::from(100u64),
+            max_lock
+        ).unwrap();
+        let bob_point = checkpoint(
+            deps.as_mut().storage,
+            &bob,
+            &Point::default(),
+            Uint128::from(2_000_000u64),
+            Uint64::from(400u64),
+            Uint64::from(200u64),
+            max_lock
+        ).unwrap();
+
+        for ts in [100u64, 200, 300, 400, 450, 600, 700] {
+            let ts = Uint64::from(ts);
+            let storage = deps.as_ref().storage;
+            let expected =
+                balance_of(storage, &alice, ts).unwrap() + balance_of(storage, &bob, ts).unwrap();
+            assert_eq!(total_supply_at(storage, ts).unwrap(), expected);
+        }
+        assert_eq!(
+            total_supply_at(deps.as_ref().storage, Uint64::from(450u64)).unwrap(),
+            Uint128::from(150_000u64)
+        );
+
+        // alice extends at 500 after bob expired, bob's old lock no longer counts
+        checkpoint(
+            deps.as_mut().storage,
+            &alice,
+            &alice_point,
+            Uint128::from(1_000_000u64),
+            Uint64::from(900u64),
+            Uint64::from(500u64),
+            max_lock
+        ).unwrap();
+        checkpoint(
+            deps.as_mut().storage,
+            &bob,
+            &bob_point,
+            Uint128::zero(),
+            Uint64::from(400u64),
+            Uint64::from(500u64),
+            max_lock
+        ).unwrap();
+
+        let storage = deps.as_ref().storage;
+        assert_eq!(SLOPE_CHANGES.may_load(storage, 600).unwrap(), None);
+        assert_eq!(
+            total_supply_at(storage, Uint64::from(500u64)).unwrap(),
+            Uint128::from(400_000u64)
+        );
+        assert_eq!(
+            total_supply_at(storage, Uint64::from(800u64)).unwrap(),
+            Uint128::from(100_000u64)
+        );
+        assert_eq!(total_supply_at(storage, Uint64::from(900u64)).unwrap(), Uint128::zero());
+
+        // history is kept
+        assert_eq!(
+            total_supply_at(storage, Uint64::from(450u64)).unwrap(),
+            Uint128::from(150_000u64)
+        );
+    }
+
+    #[test]
+    fn test_account_balance_sums_positions() {
+        let mut deps = mock_dependencies();
+        let alice = Addr::unchecked("alice");
+        let max_lock = Uint64::from(MAX_LOCK);
+
+        // slope 1000 until 600 and slope 2000 until 400
+        let long_point = checkpoint(
+            deps.as_mut().storage,
+            &alice,
+            &Point::default(),
+            Uint128::from(1_000_000u64),
+            Uint64::from(600u64),
+            Uint64::from(100u64),
+            max_lock
+        ).unwrap();
+        checkpoint(
+            deps.as_mut().storage,
+            &alice,
+            &Point::default(),
+            Uint128::from(2_000_000u64),
+            Uint64::from(400u64),
+            Uint64::from(200u64),
+            max_lock
+        ).unwrap();
+
+        let storage = deps.as_ref().storage;
+        for (ts, expected) in [
+            (100u64, 500_000u64),
+            (200, 800_000),
+            (300, 500_000),
+            (400, 200_000),
+            (500, 100_000),
+            (600, 0),
+        ] {
+            let ts = Uint64::from(ts);
+            assert_eq!(balance_of(storage, &alice, ts).unwrap(), Uint128::from(expected));
+            assert_eq!(total_supply_at(storage, ts).unwrap(), Uint128::from(expected));
+        }
+
+        // Withdrawing the long position at 450 leaves nothing
+        checkpoint(
+            deps.as_mut().storage,
+            &alice,
+            &long_point,
+            Uint128::zero(),
+            Uint64::from(600u64),
+            Uint64::from(450u64),
+            max_lock
+        ).unwrap();
+        let storage = deps.as_ref().storage;
+        assert_eq!(balance_of(storage, &alice, Uint64::from(450u64)).unwrap(), Uint128::zero());
+        assert_eq!(USER_SLOPE_CHANGES.may_load(storage, (&alice, 600)).unwrap(), None);
+        assert_eq!(
+            balance_of(storage, &alice, Uint64::from(350u64)).unwrap(),
+            Uint128::from(350_000u64)
+        );
+    }
+}
@@ -1,6 +1,36 @@
 pub const SECONDS_PER_DAY: u64 = 60 * 60 * 24;
+// Defaults for Config, used when InstantiateMsg leaves them unset
 pub const MIN_LOCK_PERIOD: u64 = 7 * SECONDS_PER_DAY;
 pub const MAX_LOCK_PERIOD: u64 = 1460 * SECONDS_PER_DAY;
 pub const WITHDRAW_DELAY: u64 = 1 * SECONDS_PER_DAY;
 
 pub const TOKEN_DECIMALS: u32 = 18;
+
+// Voting power points keep slope and bias scaled up by this, so small locks don't round to 0
+pub const POINT_PRECISION: u128 = 1_000_000_000_000_000_000;
+
+pub const DEFAULT_QUERY_LIMIT: u32 = 10;
+pub const MAX_QUERY_LIMIT: u32 = 30;
+
+// Claim pays every reward asset, keep it bounded
+pub const MAX_REWARD_ASSETS: usize = 10;
+
+// Claim recomputes every position of the account, keep it bounded
+pub const MAX_POSITIONS_PER_ACCOUNT: usize = 20;
+
+// Every ve balance change calls every hook, keep it bounded
+pub const MAX_BALANCE_HOOKS: usize = 10;
+pub const BALANCE_HOOK_REPLY_ID: u64 = 1;
+
+// Gauge votes take effect from the next epoch
+pub const GAUGE_EPOCH: u64 = 7 * SECONDS_PER_DAY;
+pub const GAUGE_VOTE_COOLDOWN: u64 = 10 * SECONDS_PER_DAY;
+// Basis points, a voter can split at most 100% of the ve balance
+pub const MAX_GAUGE_VOTE_WEIGHT: u16 = 10_000;
+// Every ve balance change reweights every gauge the account voted for, keep it bounded
+pub const MAX_USER_GAUGE_VOTES: usize = 10;
+
+pub const FEE_EPOCH: u64 = 7 * SECONDS_PER_DAY;
+// Epochs with income paid per ClaimEpochFees, the cursor resumes from there
+pub const DEFAULT_FEE_CLAIM_EPOCHS: u32 = 50;
+pub const MAX_FEE_CLAIM_EPOCHS: u32 = 100;
@@ -3,34 +3,50 @@ use crate::error::*;
 use crate::events::*;
 use crate::msg::*;
 use crate::state::*;
-use cosmwasm_std::to_binary;
+use crate::migrations::migrate_storage;
+use crate::internal::internal_funcs;
+use crate::checkpoints;
+use crate::gauges;
+use crate::fee_distributor;
+use crate::nft;
+use crate::delegation;
+use cosmwasm_std::{ from_binary, to_binary };
 use cosmwasm_std::{
     Addr,
-    CosmosMsg,
     entry_point,
     Binary,
+    Decimal,
     Deps,
     DepsMut,
+    Empty,
     Env,
     MessageInfo,
+    Order,
+    Reply,
     Response,
+    StdError,
     StdResult,
+    Storage,
+    SubMsgResult,
     Uint64,
     Uint128,
 };
-use cw2::set_contract_version;
+use cw2::{ get_contract_version, set_contract_version };
+use cw20::Denom;
+use cw_utils::Expiration;
+use semver::Version;
 use cw20_base::state::{ MinterData, TOKEN_INFO, TokenInfo };
 use cw20_base::contract::{ query_balance, query_token_info };
 
 // version info for migration info
-const CONTRACT_NAME: &str = "crates.io:veWILD";
-const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+pub const CONTRACT_NAME: &str = "crates.io:veWILD";
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
     env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     msg: InstantiateMsg
 ) -> Result<Response, ContractError> {
     // nonpayable(&info)?;
@@ -50,6 +66,15 @@ pub fn instantiate(
     };
     TOKEN_INFO.save(deps.storage, &data)?;
 
+    let default_config = Config::default();
+    let config = Config {
+        min_lock_period: msg.min_lock_period.unwrap_or(default_config.min_lock_period),
+        max_lock_period: msg.max_lock_period.unwrap_or(default_config.max_lock_period),
+        withdraw_delay: msg.withdraw_delay.unwrap_or(default_config.withdraw_delay),
+    };
+    config.validate()?;
+    CONFIG.save(deps.storage, &config)?;
+
     let mut token_state: TokenState = TokenState::default();
     TOKEN_STATE.save(deps.storage, &token_state)?;
 
@@ -60,15 +85,52 @@ pub fn instantiate(
         msg.distribution_period
     )?;
 
-    token_state.locked_token = msg.locked_token;
+    token_state.locked_token = internal_funcs::validate_asset(deps.api, msg.locked_token)?;
     token_state.last_accrue_block = current_block;
 
     TOKEN_STATE.save(deps.storage, &token_state)?;
 
-    //TODO: set/manage owner (?)
+    let dao = match msg.dao {
+        Some(dao) => deps.api.addr_validate(&dao)?,
+        None => info.sender.clone(),
+    };
+    DAO.save(deps.storage, &dao)?;
+
+    OWNERSHIP.save(deps.storage, &Ownership::new(info.sender))?;
+    PAUSE_STATE.save(deps.storage, &PauseState::default())?;
+
     Ok(response)
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(mut deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let previous = get_contract_version(deps.storage)?;
+    if previous.contract != CONTRACT_NAME {
+        return Result::Err(ContractError::InvalidMigrationContract {
+            previous_contract: previous.contract,
+        });
+    }
+
+    let previous_version: Version = previous.version.parse()?;
+    let new_version: Version = CONTRACT_VERSION.parse()?;
+    if previous_version > new_version {
+        return Result::Err(ContractError::MigrationDowngrade {
+            previous_version: previous.version,
+            new_version: CONTRACT_VERSION.to_string(),
+        });
+    }
+
+    migrate_storage(deps.branch(), &env, &previous_version, &msg)?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let event = ContractEvent::Migrate {
+        from_version: previous.version,
+        to_version: CONTRACT_VERSION.to_string(),
+    };
+
+    Ok(Response::new().add_event(event.to_cosmos_event()))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -78,52 +140,225 @@ pub fn execute(
 ) -> Result<Response, ContractError> {
     use crate::msg::ExecuteMsg::*;
     match msg {
-        Lock { amount, new_locked_until } =>
-            exec::execute_lock(deps, env, info, amount, new_locked_until),
-        Claim {} => exec::execute_claim(deps, env, info),
+        Lock { amount, new_locked_until } => {
+            exec::assert_direct_deposit(deps.as_ref(), &info, amount)?;
+            exec::execute_lock(deps, env, info, amount, new_locked_until)
+        }
+        IncreaseAmount { position, amount } => {
+            exec::assert_direct_deposit(deps.as_ref(), &info, amount)?;
+            exec::execute_increase_amount(deps, env, info, position, amount)
+        }
+        ExtendLock { position, new_locked_until } =>
+            exec::execute_extend_lock(deps, env, info, position, new_locked_until),
+        LockFor { beneficiary, amount, locked_until } => {
+            exec::assert_direct_deposit(deps.as_ref(), &info, amount)?;
+            exec::execute_lock_for(deps, env, info, beneficiary, amount, locked_until)
+        }
+        AllowLockFor { locker, max_locked_until, max_positions } =>
+            exec::execute_allow_lock_for(deps, info, locker, max_locked_until, max_positions),
+        DisallowLockFor { locker } => exec::execute_disallow_lock_for(deps, info, locker),
+        Claim { recipient } => {
+            let recipient = recipient
+                .map(|recipient| deps.api.addr_validate(&recipient))
+                .transpose()?;
+            exec::execute_claim(deps, env, info, recipient)
+        }
+        ClaimFor { account } => exec::execute_claim_for(deps, env, info, account),
+        Kick { account } => exec::execute_kick(deps, env, info, account),
+        Compound {} => exec::execute_compound(deps, env, info),
+        CompoundFor { account } => exec::execute_compound_for(deps, env, account),
+        SetAutoCompound { enabled } => exec::execute_set_auto_compound(deps, info, enabled),
+        ApproveClaimOperator { operator } =>
+            exec::execute_set_claim_operator(deps, info, operator, true),
+        RevokeClaimOperator { operator } =>
+            exec::execute_set_claim_operator(deps, info, operator, false),
         SetDistributionPeriod { blocks } =>
             exec::execute_set_distribution_period(deps, env, info, blocks),
-        RequestWithdraw {} => exec::execute_request_withdraw(deps, env, info),
-        Withdraw {} => exec::execute_withdraw(deps, env, info),
-        AddIncome { add_amount } => exec::execute_add_income(deps, env, info, add_amount),
+        RequestWithdraw { position, amount } =>
+            exec::execute_request_withdraw(deps, env, info, position, amount),
+        Withdraw { position } => exec::execute_withdraw(deps, env, info, position),
+        CancelWithdrawRequest { position, relock_until } =>
+            exec::execute_cancel_withdraw_request(deps, env, info, position, relock_until),
+        EarlyWithdraw { position } => exec::execute_early_withdraw(deps, env, info, position),
+        Merge { from, into } => exec::execute_merge(deps, env, info, from, into),
+        Split { position, amounts } => exec::execute_split(deps, env, info, position, amounts),
+        TransferNft { recipient, token_id } =>
+            exec::execute_transfer_nft(deps, env, info, recipient, token_id),
+        SendNft { contract, token_id, msg } =>
+            exec::execute_send_nft(deps, env, info, contract, token_id, msg),
+        Approve { spender, token_id, expires } =>
+            exec::execute_approve(deps, env, info, spender, token_id, expires),
+        Revoke { spender, token_id } => exec::execute_revoke(deps, env, info, spender, token_id),
+        ApproveAll { operator, expires } =>
+            exec::execute_approve_all(deps, env, info, operator, expires),
+        RevokeAll { operator } => exec::execute_revoke_all(deps, info, operator),
+        AddIncome { add_amount } => {
+            exec::assert_direct_deposit(deps.as_ref(), &info, add_amount)?;
+            exec::execute_add_income(deps, env, info, add_amount)
+        }
+        AddEpochIncome { add_amount } => {
+            exec::assert_direct_deposit(deps.as_ref(), &info, add_amount)?;
+            exec::execute_add_epoch_income(deps, env, info, add_amount)
+        }
+        ClaimEpochFees { limit } => exec::execute_claim_epoch_fees(deps, env, info, limit),
+        AddRewardAsset { asset, distribution_period } =>
+            exec::execute_add_reward_asset(deps, env, info, asset, distribution_period),
+        AddBalanceHook { address, on_error } =>
+            exec::execute_add_balance_hook(deps, info, address, on_error),
+        RemoveBalanceHook { address } => exec::execute_remove_balance_hook(deps, info, address),
+        AddGauge { address } => exec::execute_add_gauge(deps, info, address),
+        VoteGauge { gauge, weight } => exec::execute_vote_gauge(deps, env, info, gauge, weight),
+        Delegate { to } => exec::execute_delegate(deps, env, info, to),
+        Undelegate {} => exec::execute_undelegate(deps, env, info),
+        AddRewardIncome { asset, add_amount } => {
+            exec::assert_asset_deposit(&info, &asset, add_amount)?;
+            exec::execute_add_reward_income(deps, env, info, asset, add_amount)
+        }
+        Receive(cw20_msg) => exec::execute_receive(deps, env, info, cw20_msg),
+        ProposeOwner { new_owner } => exec::execute_propose_owner(deps, info, new_owner),
+        AcceptOwnership {} => exec::execute_accept_ownership(deps, info),
+        RenounceOwnership {} => exec::execute_renounce_ownership(deps, info),
+        GrantRole { role, address } => exec::execute_grant_role(deps, info, role, address),
+        RevokeRole { role, address } => exec::execute_revoke_role(deps, info, role, address),
+        Pause { actions } => exec::execute_set_paused(deps, info, actions, true),
+        Unpause { actions } => exec::execute_set_paused(deps, info, actions, false),
+        EmergencyShutdown { reward_policy } =>
+            exec::execute_emergency_shutdown(deps, env, info, reward_policy),
+        SweepForfeitedRewards { recipient } =>
+            exec::execute_sweep_forfeited_rewards(deps, info, recipient),
+        UpdateConfig { min_lock_period, max_lock_period, withdraw_delay } =>
+            exec::execute_update_config(
+                deps,
+                info,
+                min_lock_period,
+                max_lock_period,
+                withdraw_delay
+            ),
+        SetEarlyWithdrawConfig { config } =>
+            exec::execute_set_early_withdraw_config(deps, info, config),
         _ => Result::Err(ContractError::Unimplemented {}),
     }
 }
 
 mod exec {
-    use crate::{ internal::internal_funcs, cw20_client::CW20Client };
+    use cw20::Cw20ReceiveMsg;
+    use cw_utils::{ must_pay, nonpayable };
+
+    use crate::asset_client::AssetClient;
 
     use super::*;
 
-    pub fn execute_lock(
-        mut deps: DepsMut,
+    /// Exec handlers that credit a deposited amount (locks and income) trust it to be held
+    /// by the contract already: execute checks it here or with assert_asset_deposit, or
+    /// execute_receive got it through a CW20 Send
+    pub fn assert_direct_deposit(
+        deps: Deps,
+        info: &MessageInfo,
+        amount: Uint128
+    ) -> Result<(), ContractError> {
+        let locked_token = TOKEN_STATE.load(deps.storage)?.locked_token;
+        assert_asset_deposit(info, &locked_token, amount)
+    }
+
+    /// CW20 tokens only come in through Send, which credits exactly the amount received.
+    /// Native tokens come with the message and must match amount
+    pub fn assert_asset_deposit(
+        info: &MessageInfo,
+        asset: &Denom,
+        amount: Uint128
+    ) -> Result<(), ContractError> {
+        match asset {
+            Denom::Cw20(_) => {
+                nonpayable(info)?;
+                if !amount.is_zero() {
+                    return Result::Err(ContractError::Cw20SendRequired {});
+                }
+            }
+            Denom::Native(_) if amount.is_zero() => nonpayable(info)?,
+            Denom::Native(denom) => {
+                let received = must_pay(info, denom)?;
+                if received != amount {
+                    return Result::Err(ContractError::FundsMismatch {
+                        expected: amount,
+                        received,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn execute_receive(
+        deps: DepsMut,
         env: Env,
         info: MessageInfo,
-        amount: Uint128,
-        new_locked_until: Uint64
+        cw20_msg: Cw20ReceiveMsg
     ) -> Result<Response, ContractError> {
+        let token = Denom::Cw20(info.sender);
+        let is_locked_token = TOKEN_STATE.load(deps.storage)?.locked_token == token;
+
+        // act on behalf of the account that sent the tokens
+        let sender_info = MessageInfo {
+            sender: deps.api.addr_validate(&cw20_msg.sender)?,
+            funds: vec![],
+        };
+
+        match from_binary(&cw20_msg.msg)? {
+            ReceiveMsg::Lock { new_locked_until } if is_locked_token =>
+                execute_lock(deps, env, sender_info, cw20_msg.amount, new_locked_until),
+            ReceiveMsg::IncreaseAmount { position } if is_locked_token =>
+                execute_increase_amount(deps, env, sender_info, position, cw20_msg.amount),
+            ReceiveMsg::LockFor { beneficiary, locked_until } if is_locked_token =>
+                execute_lock_for(
+                    deps,
+                    env,
+                    sender_info,
+                    beneficiary,
+                    cw20_msg.amount,
+                    locked_until
+                ),
+            ReceiveMsg::AddIncome {} if is_locked_token =>
+                execute_add_income(deps, env, sender_info, cw20_msg.amount),
+            ReceiveMsg::AddEpochIncome {} if is_locked_token =>
+                execute_add_epoch_income(deps, env, sender_info, cw20_msg.amount),
+            // unregistered tokens are rejected with RewardAssetNotFound
+            ReceiveMsg::AddRewardIncome {} =>
+                execute_add_reward_income(deps, env, sender_info, token, cw20_msg.amount),
+            _ => Result::Err(ContractError::InvalidToken {}),
+        }
+    }
+
+    /// Fails unless new_locked_until is between the min and max lock period from now
+    fn assert_lock_period(
+        storage: &dyn Storage,
+        env: &Env,
+        new_locked_until: Uint64
+    ) -> Result<(), ContractError> {
         let current_ts = Uint64::from(env.block.time.seconds());
 
         let lock_seconds: Uint64 = new_locked_until
             .checked_sub(current_ts)
             .unwrap_or(Uint64::zero());
 
-        if lock_seconds < Uint64::from(MIN_LOCK_PERIOD) {
+        let config = CONFIG.load(storage)?;
+        if lock_seconds < config.min_lock_period {
             return Result::Err(ContractError::LockPeriodTooShort {});
         }
-        if lock_seconds > Uint64::from(MAX_LOCK_PERIOD) {
+        if lock_seconds > config.max_lock_period {
             return Result::Err(ContractError::LockPeriodTooLong {});
         }
+        Ok(())
+    }
 
-        let user_state = USER_STATE.load(deps.storage, &info.sender).unwrap_or_else(|_err| {
-            let state = UserState::default();
-            USER_STATE.save(deps.storage, &info.sender, &state).unwrap();
-            state
-        });
-        if new_locked_until < user_state.locked_until {
-            return Result::Err(ContractError::CannotReduceLockedTime {});
-        }
-
+    /// Pays out rewards, then adds amount to the position and moves its unlock time
+    fn lock_position(
+        mut deps: DepsMut,
+        env: &Env,
+        info: &MessageInfo,
+        position_id: u64,
+        amount: Uint128,
+        new_locked_until: Uint64
+    ) -> Result<Response, ContractError> {
         // NOTE:implement
         /*         if is_contract(&info.sender) {
                    return Result::Err(ContractError::CannotLockContract {})
@@ -132,67 +367,79 @@ mod exec {
 
         let mut response = Response::new();
 
-        let claim_response = internal_funcs::claim(deps.branch(), &env, &info)?;
-        let cosmos_messages: Vec<CosmosMsg> = claim_response.messages
-            .iter()
-            .map(|msg| msg.msg.clone())
-            .collect();
+        let claim_response = internal_funcs::claim(deps.branch(), env, info)?;
         response = response
-            .add_messages(cosmos_messages)
+            .add_submessages(claim_response.messages)
             .add_events(claim_response.events)
             .add_attributes(claim_response.attributes);
 
-        let mut token_state = TOKEN_STATE.load(deps.storage)?;
-        let mut user_state = USER_STATE.load(deps.storage, &info.sender)?;
-
-        let mut messages: Vec<CosmosMsg> = vec![];
         if !amount.is_zero() {
-            // TODO: Do I need to handle it on Reply?
+            let mut token_state = TOKEN_STATE.load(deps.storage)?;
+            let mut user_state = USER_STATE.load(deps.storage, &info.sender)?;
+            let mut position = POSITIONS.load(deps.storage, position_id)?;
 
+            position.locked_balance += amount;
             user_state.locked_balance += amount;
             token_state.total_locked += amount;
 
-            // TODO: check returns
-            let msg = CW20Client::new(
-                &deps.querier,
-                token_state.locked_token.clone()
-            ).make_transfer_from_msg(
-                info.sender.to_owned(),
-                env.contract.address.to_owned(),
-                amount
-            )?;
-            messages.push(CosmosMsg::Wasm(msg));
+            POSITIONS.save(deps.storage, position_id, &position)?;
+            USER_STATE.save(deps.storage, &info.sender, &user_state)?;
+            TOKEN_STATE.save(deps.storage, &token_state)?;
         }
-        // TODO: check for submessage
-        response = response.add_messages(messages);
-
-        USER_STATE.save(deps.storage, &info.sender, &user_state)?;
-        TOKEN_STATE.save(deps.storage, &token_state)?;
 
         let update_lock_response = internal_funcs::update_lock(
             deps.branch(),
-            &env,
-            &info,
-            &info.sender,
+            env,
+            info,
+            position_id,
             new_locked_until
         )?;
-        let cosmos_messages: Vec<CosmosMsg> = update_lock_response.messages
-            .iter()
-            .map(|msg| msg.msg.clone())
-            .collect();
         response = response
-            .add_messages(cosmos_messages)
+            .add_submessages(update_lock_response.messages)
             .add_events(update_lock_response.events)
             .add_attributes(update_lock_response.attributes);
 
-        internal_funcs::check_reserves(deps.as_ref(), &env)?;
+        internal_funcs::check_reserves(deps.as_ref(), env)?;
+
+        Ok(response)
+    }
+
+    pub fn execute_lock(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        amount: Uint128,
+        new_locked_until: Uint64
+    ) -> Result<Response, ContractError> {
+        PauseState::load(deps.storage)?.assert_not_paused(PausableAction::Lock)?;
+        Shutdown::assert_not_shutdown(deps.storage)?;
+
+        assert_lock_period(deps.storage, &env, new_locked_until)?;
+        if amount.is_zero() {
+            return Result::Err(ContractError::NothingToLock {});
+        }
+
+        if !USER_STATE.has(deps.storage, &info.sender) {
+            USER_STATE.save(deps.storage, &info.sender, &UserState::default())?;
+        }
+        let position_id = internal_funcs::create_position(deps.storage, &info.sender)?;
+
+        let mut response = lock_position(
+            deps.branch(),
+            &env,
+            &info,
+            position_id,
+            amount,
+            new_locked_until
+        )?;
 
         let ve_balance = query_balance(deps.as_ref(), info.sender.to_owned().into_string())?;
 
         let event = ContractEvent::Lock {
             account: info.sender.to_string(),
+            position: position_id,
             locked_until: new_locked_until,
-            locked_balance: user_state.locked_balance,
+            locked_balance: amount,
             ve_balance: ve_balance.balance,
         };
 
@@ -201,38 +448,194 @@ mod exec {
         Ok(response)
     }
 
+    pub fn execute_increase_amount(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        position_id: u64,
+        amount: Uint128
+    ) -> Result<Response, ContractError> {
+        PauseState::load(deps.storage)?.assert_not_paused(PausableAction::Lock)?;
+        Shutdown::assert_not_shutdown(deps.storage)?;
+
+        let position = internal_funcs::load_position(deps.storage, position_id, &info.sender)?;
+        if amount.is_zero() {
+            return Result::Err(ContractError::NothingToIncrease {});
+        }
+        // No lock period check, the unlock time stays as it is
+        if Uint64::from(env.block.time.seconds()) >= position.locked_until {
+            return Result::Err(ContractError::IncreaseExpiredLock {});
+        }
+
+        let response = lock_position(
+            deps.branch(),
+            &env,
+            &info,
+            position_id,
+            amount,
+            position.locked_until
+        )?;
+
+        let ve_balance = query_balance(deps.as_ref(), info.sender.to_string())?;
+        let event = ContractEvent::make_increase_amount(
+            info.sender.to_string(),
+            position_id,
+            amount,
+            position.locked_balance + amount,
+            ve_balance.balance
+        );
+
+        Ok(response.add_event(event.to_cosmos_event()))
+    }
+
+    pub fn execute_extend_lock(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        position_id: u64,
+        new_locked_until: Uint64
+    ) -> Result<Response, ContractError> {
+        PauseState::load(deps.storage)?.assert_not_paused(PausableAction::Lock)?;
+        Shutdown::assert_not_shutdown(deps.storage)?;
+
+        let position = internal_funcs::load_position(deps.storage, position_id, &info.sender)?;
+        if !position.withdraw_at.is_zero() {
+            return Result::Err(ContractError::WithdrawRequested { id: position_id });
+        }
+        if new_locked_until <= position.locked_until {
+            return Result::Err(ContractError::LockNotExtended {});
+        }
+        assert_lock_period(deps.storage, &env, new_locked_until)?;
+
+        let response = lock_position(
+            deps.branch(),
+            &env,
+            &info,
+            position_id,
+            Uint128::zero(),
+            new_locked_until
+        )?;
+
+        let ve_balance = query_balance(deps.as_ref(), info.sender.to_string())?;
+        let event = ContractEvent::make_extend_lock(
+            info.sender.to_string(),
+            position_id,
+            new_locked_until,
+            ve_balance.balance
+        );
+
+        Ok(response.add_event(event.to_cosmos_event()))
+    }
+
+    pub fn execute_lock_for(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        beneficiary: String,
+        amount: Uint128,
+        locked_until: Uint64
+    ) -> Result<Response, ContractError> {
+        let beneficiary = deps.api.addr_validate(&beneficiary)?;
+        let mut events = vec![];
+        if beneficiary != info.sender {
+            events = internal_funcs::use_lock_for_allowance(
+                deps.storage,
+                &beneficiary,
+                &info.sender,
+                locked_until
+            )?;
+        }
+
+        // Always a new position, so the beneficiary's own locks keep their unlock times
+        let beneficiary_info = MessageInfo { sender: beneficiary.clone(), funds: vec![] };
+        let response = execute_lock(deps.branch(), env, beneficiary_info, amount, locked_until)?;
+
+        let event = ContractEvent::make_lock_for(
+            info.sender.to_string(),
+            beneficiary.to_string(),
+            POSITION_COUNT.load(deps.storage)?,
+            amount
+        );
+        Ok(response.add_event(event.to_cosmos_event()).add_events(events))
+    }
+
+    pub fn execute_allow_lock_for(
+        deps: DepsMut,
+        info: MessageInfo,
+        locker: String,
+        max_locked_until: Uint64,
+        max_positions: u32
+    ) -> Result<Response, ContractError> {
+        let locker = deps.api.addr_validate(&locker)?;
+        if max_positions == 0 {
+            return Result::Err(ContractError::NoLockForPositions {});
+        }
+
+        let allowance = LockForAllowanceState { max_locked_until, positions: max_positions };
+        LOCK_FOR_ALLOWANCES.save(deps.storage, (&info.sender, &locker), &allowance)?;
+
+        let event = ContractEvent::make_lock_for_allowance(
+            info.sender.to_string(),
+            locker.to_string(),
+            max_locked_until,
+            max_positions
+        );
+        Ok(Response::new().add_event(event.to_cosmos_event()))
+    }
+
+    pub fn execute_disallow_lock_for(
+        deps: DepsMut,
+        info: MessageInfo,
+        locker: String
+    ) -> Result<Response, ContractError> {
+        let locker = deps.api.addr_validate(&locker)?;
+
+        LOCK_FOR_ALLOWANCES.remove(deps.storage, (&info.sender, &locker));
+
+        let event = ContractEvent::make_lock_for_allowance(
+            info.sender.to_string(),
+            locker.to_string(),
+            Uint64::zero(),
+            0
+        );
+        Ok(Response::new().add_event(event.to_cosmos_event()))
+    }
+
     pub fn execute_request_withdraw(
         mut deps: DepsMut,
         env: Env,
-        info: MessageInfo
+        info: MessageInfo,
+        position_id: u64,
+        amount: Option<Uint128>
     ) -> Result<Response, ContractError> {
-        let user_state: UserState = USER_STATE.load(deps.storage, &info.sender)?;
+        let position = internal_funcs::load_position(deps.storage, position_id, &info.sender)?;
 
-        let withdraw_amount = user_state.locked_balance;
-        if withdraw_amount.is_zero() {
+        if position.locked_balance.is_zero() {
             return Result::Err(ContractError::NothingToWithdraw {});
         }
+        let withdraw_amount = amount.unwrap_or(position.locked_balance);
+        if withdraw_amount.is_zero() || withdraw_amount > position.locked_balance {
+            return Result::Err(ContractError::InvalidWithdrawAmount {});
+        }
 
         let current_time = Uint64::from(env.block.time.seconds());
-        if current_time < user_state.locked_until {
+        if current_time < position.locked_until {
             return Result::Err(ContractError::WithdrawBeforeUnlock {});
         }
 
         let mut response = internal_funcs::claim(deps.branch(), &env, &info)?;
 
-        let withdraw_at = current_time + Uint64::from(WITHDRAW_DELAY);
-        USER_STATE.update(
-            deps.storage,
-            &info.sender.clone(),
-            |state_opt| -> StdResult<UserState> {
-                let mut state = state_opt.unwrap();
-                state.withdraw_at = withdraw_at.clone();
-                Ok(state)
-            }
-        )?;
+        let withdraw_at = current_time + CONFIG.load(deps.storage)?.withdraw_delay;
+        POSITIONS.update(deps.storage, position_id, |position_opt| -> StdResult<Position> {
+            let mut position = position_opt.unwrap();
+            position.withdraw_at = withdraw_at;
+            position.withdraw_amount = withdraw_amount;
+            Ok(position)
+        })?;
 
         let event = ContractEvent::WithdrawRequest {
             account: info.sender.to_string(),
+            position: position_id,
             amount: withdraw_amount,
             withdraw_at: withdraw_at,
         };
@@ -245,46 +648,84 @@ mod exec {
     pub fn execute_withdraw(
         mut deps: DepsMut,
         env: Env,
-        info: MessageInfo
+        info: MessageInfo,
+        position_id: u64
     ) -> Result<Response, ContractError> {
-        let mut user_state = USER_STATE.load(deps.storage, &info.sender)?;
+        let position = internal_funcs::load_position(deps.storage, position_id, &info.sender)?;
+        let shutdown = SHUTDOWN.may_load(deps.storage)?;
 
-        let withdraw_at = user_state.withdraw_at;
+        let withdraw_at = position.withdraw_at;
         let current_time = Uint64::from(env.block.time.seconds());
 
-        if current_time < withdraw_at || withdraw_at.is_zero() {
+        // After emergency shutdown neither the lock nor the withdraw delay apply
+        if shutdown.is_none() && (current_time < withdraw_at || withdraw_at.is_zero()) {
             return Result::Err(ContractError::WithdrawDelayNotOver {});
         }
+        if position.locked_balance.is_zero() {
+            return Result::Err(ContractError::NothingToWithdraw {});
+        }
 
-        let mut response = internal_funcs::claim(deps.branch(), &env, &info).unwrap();
+        let mut response = match shutdown {
+            Some(Shutdown { reward_policy: ShutdownRewardPolicy::Forfeit, .. }) => {
+                internal_funcs::forfeit_rewards(deps.branch(), &env, &info.sender)?;
+                Response::new()
+            }
+            _ => internal_funcs::claim(deps.branch(), &env, &info)?,
+        };
+
+        // claim has moved reward snapshot and position points
+        let mut user_state = USER_STATE.load(deps.storage, &info.sender)?;
+        let mut position = POSITIONS.load(deps.storage, position_id)?;
 
-        let withdraw_amount = user_state.locked_balance;
-        user_state.withdraw_at = Uint64::zero();
+        // Positions withdrawn after shutdown without a request go whole
+        let withdraw_amount = if withdraw_at.is_zero() {
+            position.locked_balance
+        } else {
+            position.withdraw_amount
+        };
 
         let mut token_state: TokenState = TOKEN_STATE.load(deps.storage)?;
         token_state.total_locked -= withdraw_amount;
-        user_state.locked_balance = Uint128::zero();
+        user_state.locked_balance -= withdraw_amount;
 
         USER_STATE.save(deps.storage, &info.sender, &user_state)?;
         TOKEN_STATE.save(deps.storage, &token_state)?;
 
-        let set_balance_resp = internal_funcs::set_balance(
+        if withdraw_amount < position.locked_balance {
+            // The rest stays locked as it was, without a request
+            position.locked_balance -= withdraw_amount;
+            position.withdraw_at = Uint64::zero();
+            position.withdraw_amount = Uint128::zero();
+            let locked_until = position.locked_until;
+            internal_funcs::refresh_position(deps.storage, &env, &mut position, locked_until)?;
+            POSITIONS.save(deps.storage, position_id, &position)?;
+        } else {
+            // Voting power of a lock withdrawn after shutdown ends now
+            let max_lock_period = CONFIG.load(deps.storage)?.max_lock_period;
+            checkpoints::checkpoint(
+                deps.storage,
+                &info.sender,
+                &position.point,
+                Uint128::zero(),
+                position.locked_until,
+                current_time,
+                max_lock_period
+            )?;
+            internal_funcs::remove_position(deps.storage, position_id, &info.sender)?;
+        }
+
+        let set_balance_resp = internal_funcs::update_account_balance(
             deps.branch(),
             &env,
             &info,
-            &info.sender.to_owned(),
-            Uint128::zero()
+            &info.sender.to_owned()
         )?;
-        let cosmos_messages: Vec<CosmosMsg> = set_balance_resp.messages
-            .iter()
-            .map(|msg| msg.msg.clone())
-            .collect();
         response = response
-            .add_messages(cosmos_messages)
+            .add_submessages(set_balance_resp.messages)
             .add_events(set_balance_resp.events)
             .add_attributes(set_balance_resp.attributes);
 
-        let cosmos_messages = CW20Client::new(
+        let cosmos_messages = AssetClient::new(
             &deps.querier,
             token_state.locked_token.clone()
         ).make_transfer_msg(info.sender.to_owned(), withdraw_amount)?;
@@ -295,6 +736,7 @@ mod exec {
         let event = ContractEvent::Withdraw {
             amount: withdraw_amount,
             account: info.sender.to_string(),
+            position: position_id,
         };
 
         response = response.add_event(event.to_cosmos_event());
@@ -302,79 +744,1265 @@ mod exec {
         Ok(response)
     }
 
-    pub fn execute_claim(
+    pub fn execute_cancel_withdraw_request(
         mut deps: DepsMut,
         env: Env,
-        info: MessageInfo
+        info: MessageInfo,
+        position_id: u64,
+        relock_until: Option<Uint64>
     ) -> Result<Response, ContractError> {
-        let mut response = Response::new();
+        let position = internal_funcs::load_position(deps.storage, position_id, &info.sender)?;
+        if position.withdraw_at.is_zero() {
+            return Result::Err(ContractError::NoWithdrawRequest { id: position_id });
+        }
 
-        let claim_resp = internal_funcs::claim(deps.branch(), &env, &info)?;
-        let cosmos_messages: Vec<CosmosMsg> = claim_resp.messages
-            .iter()
-            .map(|msg| msg.msg.clone())
-            .collect();
+        POSITIONS.update(deps.storage, position_id, |position_opt| -> StdResult<Position> {
+            let mut position = position_opt.unwrap();
+            position.withdraw_at = Uint64::zero();
+            position.withdraw_amount = Uint128::zero();
+            Ok(position)
+        })?;
 
-        response = response.add_messages(cosmos_messages).add_events(claim_resp.events);
-        internal_funcs::check_reserves(deps.as_ref(), &env)?;
+        let mut response = match relock_until {
+            Some(relock_until) =>
+                execute_extend_lock(deps.branch(), env, info.clone(), position_id, relock_until)?,
+            None => Response::new(),
+        };
+
+        let event = ContractEvent::make_withdraw_request_cancelled(
+            info.sender.to_string(),
+            position_id,
+            position.withdraw_amount
+        );
+        response = response.add_event(event.to_cosmos_event());
 
         Ok(response)
     }
 
-    pub fn execute_add_income(
-        deps: DepsMut,
+    pub fn execute_early_withdraw(
+        mut deps: DepsMut,
         env: Env,
         info: MessageInfo,
-        add_amount: Uint128
+        position_id: u64
     ) -> Result<Response, ContractError> {
-        let mut token_state = TOKEN_STATE.load(deps.storage)?;
-        let current_block = Uint64::from(env.block.height);
+        PauseState::load(deps.storage)?.assert_not_paused(PausableAction::EarlyWithdraw)?;
+        Shutdown::assert_not_shutdown(deps.storage)?;
 
-        token_state.accrue(deps.storage, current_block)?;
+        let early_withdraw_config = EARLY_WITHDRAW_CONFIG.may_load(deps.storage)?.ok_or(
+            ContractError::EarlyWithdrawDisabled {}
+        )?;
+        let position = internal_funcs::load_position(deps.storage, position_id, &info.sender)?;
 
-        let transfer_message = CW20Client::new(
-            &deps.querier,
-            token_state.locked_token.clone()
-        ).make_transfer_from_msg(info.sender, env.contract.address.clone(), add_amount)?;
-        let response = Response::new().add_message(transfer_message);
+        let current_time = Uint64::from(env.block.time.seconds());
+        if current_time >= position.locked_until {
+            return Result::Err(ContractError::LockExpired {});
+        }
+        if position.locked_balance.is_zero() {
+            return Result::Err(ContractError::NothingToWithdraw {});
+        }
 
-        let unvested_income = token_state.update_reward_rate(deps.storage, UpdateRewardRateInput {
-            add_amount: add_amount,
-            new_distribution_period: token_state.distribution_period,
-            current_block,
-        })?;
+        let mut response = internal_funcs::claim(deps.branch(), &env, &info)?;
 
-        // TODO: ensure that transfer_from message is accepted by the token contract
-        internal_funcs::check_reserves(deps.as_ref(), &env)?;
+        // claim has moved reward snapshot and position points
+        let mut user_state = USER_STATE.load(deps.storage, &info.sender)?;
+        let position = POSITIONS.load(deps.storage, position_id)?;
+        let max_lock_period = CONFIG.load(deps.storage)?.max_lock_period;
+
+        let locked_balance = position.locked_balance;
+        let penalty = early_withdraw_config.penalty(
+            locked_balance,
+            position.locked_until - current_time,
+            max_lock_period
+        );
+        let withdraw_amount = locked_balance - penalty;
 
-        let event = ContractEvent::NewIncome {
-            add_amount: add_amount,
-            remaining_amount: unvested_income,
-            reward_rate: token_state.reward_rate_stored,
-        };
-        let response = response.add_event(event.to_cosmos_event());
+        let mut token_state: TokenState = TOKEN_STATE.load(deps.storage)?;
+        token_state.total_locked -= locked_balance;
+        user_state.locked_balance -= locked_balance;
 
-        Ok(response)
-    }
+        USER_STATE.save(deps.storage, &info.sender, &user_state)?;
+        TOKEN_STATE.save(deps.storage, &token_state)?;
 
-    pub fn execute_set_distribution_period(
-        deps: DepsMut,
-        env: Env,
-        _info: MessageInfo,
-        new_distribution_period: Uint64
-    ) -> Result<Response, ContractError> {
-        let mut token_state = TOKEN_STATE.load(deps.storage)?;
-        let current_block = Uint64::from(env.block.height);
-        return token_state.set_distribution_period(
+        checkpoints::checkpoint(
             deps.storage,
-            current_block,
-            new_distribution_period
+            &info.sender,
+            &position.point,
+            Uint128::zero(),
+            position.locked_until,
+            current_time,
+            max_lock_period
+        )?;
+        internal_funcs::remove_position(deps.storage, position_id, &info.sender)?;
+
+        let set_balance_resp = internal_funcs::update_account_balance(
+            deps.branch(),
+            &env,
+            &info,
+            &info.sender.to_owned()
+        )?;
+        response = response
+            .add_submessages(set_balance_resp.messages)
+            .add_events(set_balance_resp.events)
+            .add_attributes(set_balance_resp.attributes);
+
+        let asset_client = AssetClient::new(&deps.querier, token_state.locked_token.clone());
+        if !withdraw_amount.is_zero() {
+            response = response.add_message(
+                asset_client.make_transfer_msg(info.sender.to_owned(), withdraw_amount)?
+            );
+        }
+
+        if !penalty.is_zero() {
+            match &early_withdraw_config.penalty_destination {
+                PenaltyDestination::Lockers => {
+                    // The penalty stays in the contract and vests like income
+                    let mut token_state = TOKEN_STATE.load(deps.storage)?;
+                    let current_block = Uint64::from(env.block.height);
+                    token_state.accrue(deps.storage, current_block)?;
+                    token_state.update_reward_rate(deps.storage, UpdateRewardRateInput {
+                        add_amount: penalty,
+                        new_distribution_period: token_state.distribution_period,
+                        current_block,
+                    })?;
+                }
+                PenaltyDestination::Treasury { address } => {
+                    response = response.add_message(
+                        asset_client.make_transfer_msg(address.to_owned(), penalty)?
+                    );
+                }
+            }
+        }
+
+        internal_funcs::check_reserves(deps.as_ref(), &env)?;
+
+        let event = ContractEvent::make_early_withdraw(
+            info.sender.to_string(),
+            position_id,
+            withdraw_amount,
+            penalty,
+            early_withdraw_config.penalty_destination.as_str().to_string()
         );
+
+        Ok(response.add_event(event.to_cosmos_event()))
     }
-}
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    pub fn execute_merge(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        from: u64,
+        into: u64
+    ) -> Result<Response, ContractError> {
+        PauseState::load(deps.storage)?.assert_not_paused(PausableAction::Lock)?;
+        Shutdown::assert_not_shutdown(deps.storage)?;
+
+        if from == into {
+            return Result::Err(ContractError::MergeSamePosition {});
+        }
+        for id in [from, into] {
+            let position = internal_funcs::load_position(deps.storage, id, &info.sender)?;
+            if !position.withdraw_at.is_zero() {
+                return Result::Err(ContractError::WithdrawRequested { id });
+            }
+        }
+
+        let mut response = internal_funcs::claim(deps.branch(), &env, &info)?;
+
+        // claim has moved position points
+        let from_position = POSITIONS.load(deps.storage, from)?;
+        let mut into_position = POSITIONS.load(deps.storage, into)?;
+
+        let max_lock_period = CONFIG.load(deps.storage)?.max_lock_period;
+        checkpoints::checkpoint(
+            deps.storage,
+            &info.sender,
+            &from_position.point,
+            Uint128::zero(),
+            from_position.locked_until,
+            Uint64::from(env.block.time.seconds()),
+            max_lock_period
+        )?;
+        internal_funcs::remove_position(deps.storage, from, &info.sender)?;
+
+        into_position.locked_balance += from_position.locked_balance;
+        POSITIONS.save(deps.storage, into, &into_position)?;
+
+        let locked_until = from_position.locked_until.max(into_position.locked_until);
+        let update_lock_response = internal_funcs::update_lock(
+            deps.branch(),
+            &env,
+            &info,
+            into,
+            locked_until
+        )?;
+        response = response
+            .add_submessages(update_lock_response.messages)
+            .add_events(update_lock_response.events)
+            .add_attributes(update_lock_response.attributes);
+
+        let event = ContractEvent::make_merge(
+            info.sender.to_string(),
+            from,
+            into,
+            into_position.locked_balance,
+            locked_until
+        );
+        Ok(response.add_event(event.to_cosmos_event()))
+    }
+
+    pub fn execute_split(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        position_id: u64,
+        amounts: Vec<Uint128>
+    ) -> Result<Response, ContractError> {
+        PauseState::load(deps.storage)?.assert_not_paused(PausableAction::Lock)?;
+        Shutdown::assert_not_shutdown(deps.storage)?;
+
+        let position = internal_funcs::load_position(deps.storage, position_id, &info.sender)?;
+        if !position.withdraw_at.is_zero() {
+            return Result::Err(ContractError::WithdrawRequested { id: position_id });
+        }
+        let split_amount: Uint128 = amounts.iter().sum();
+        if amounts.is_empty() || amounts.iter().any(Uint128::is_zero) ||
+            split_amount >= position.locked_balance
+        {
+            return Result::Err(ContractError::InvalidSplit {});
+        }
+
+        let mut response = internal_funcs::claim(deps.branch(), &env, &info)?;
+
+        // claim has moved the position point
+        let mut position = POSITIONS.load(deps.storage, position_id)?;
+        position.locked_balance -= split_amount;
+        POSITIONS.save(deps.storage, position_id, &position)?;
+
+        let mut new_positions = vec![];
+        for amount in amounts {
+            let id = internal_funcs::create_position(deps.storage, &info.sender)?;
+            let mut new_position = POSITIONS.load(deps.storage, id)?;
+            new_position.locked_balance = amount;
+            internal_funcs::refresh_position(
+                deps.storage,
+                &env,
+                &mut new_position,
+                position.locked_until
+            )?;
+            POSITIONS.save(deps.storage, id, &new_position)?;
+            new_positions.push(id);
+        }
+
+        let update_lock_response = internal_funcs::update_lock(
+            deps.branch(),
+            &env,
+            &info,
+            position_id,
+            position.locked_until
+        )?;
+        response = response
+            .add_submessages(update_lock_response.messages)
+            .add_events(update_lock_response.events)
+            .add_attributes(update_lock_response.attributes);
+
+        let event = ContractEvent::make_split(
+            info.sender.to_string(),
+            position_id,
+            new_positions,
+            position.locked_balance
+        );
+        Ok(response.add_event(event.to_cosmos_event()))
+    }
+
+    pub fn execute_claim(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        recipient: Option<Addr>
+    ) -> Result<Response, ContractError> {
+        PauseState::load(deps.storage)?.assert_not_paused(PausableAction::Claim)?;
+        if let Some(shutdown) = SHUTDOWN.may_load(deps.storage)? {
+            if shutdown.reward_policy == ShutdownRewardPolicy::Forfeit {
+                return Result::Err(ContractError::Shutdown {});
+            }
+        }
+
+        let mut response = Response::new();
+
+        let recipient = recipient.unwrap_or_else(|| info.sender.clone());
+        let claim_resp = internal_funcs::claim_to(deps.branch(), &env, &info, &recipient)?;
+        response = response.add_submessages(claim_resp.messages).add_events(claim_resp.events);
+        internal_funcs::check_reserves(deps.as_ref(), &env)?;
+
+        Ok(response)
+    }
+
+    pub fn execute_compound(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo
+    ) -> Result<Response, ContractError> {
+        let pause_state = PauseState::load(deps.storage)?;
+        pause_state.assert_not_paused(PausableAction::Claim)?;
+        pause_state.assert_not_paused(PausableAction::Lock)?;
+        Shutdown::assert_not_shutdown(deps.storage)?;
+
+        let current_ts = Uint64::from(env.block.time.seconds());
+        let (position_id, _) = internal_funcs::account_positions(deps.storage, &info.sender)?
+            .into_iter()
+            .filter(|(_, position)| position.locked_until > current_ts)
+            .max_by_key(|(_, position)| position.locked_until)
+            .ok_or(ContractError::NoActiveLock {})?;
+
+        // The reward is already held by the contract, it only moves to total_locked
+        let amount = internal_funcs::take_pending_reward(deps.storage, &env, &info.sender)?;
+        if amount.is_zero() {
+            return Result::Err(ContractError::NothingToCompound {});
+        }
+
+        // Pays the reward assets and refreshes the positions
+        let mut response = internal_funcs::claim(deps.branch(), &env, &info)?;
+
+        let mut token_state = TOKEN_STATE.load(deps.storage)?;
+        let mut user_state = USER_STATE.load(deps.storage, &info.sender)?;
+        let mut position = POSITIONS.load(deps.storage, position_id)?;
+
+        position.locked_balance += amount;
+        user_state.locked_balance += amount;
+        token_state.total_locked += amount;
+
+        POSITIONS.save(deps.storage, position_id, &position)?;
+        USER_STATE.save(deps.storage, &info.sender, &user_state)?;
+        TOKEN_STATE.save(deps.storage, &token_state)?;
+
+        let update_lock_response = internal_funcs::update_lock(
+            deps.branch(),
+            &env,
+            &info,
+            position_id,
+            position.locked_until
+        )?;
+        response = response
+            .add_submessages(update_lock_response.messages)
+            .add_events(update_lock_response.events)
+            .add_attributes(update_lock_response.attributes);
+
+        internal_funcs::check_reserves(deps.as_ref(), &env)?;
+
+        let ve_balance = query_balance(deps.as_ref(), info.sender.to_string())?.balance;
+        let event = ContractEvent::make_compound(
+            info.sender.to_string(),
+            position_id,
+            amount,
+            position.locked_balance,
+            ve_balance
+        );
+        Ok(response.add_event(event.to_cosmos_event()))
+    }
+
+    pub fn execute_compound_for(
+        deps: DepsMut,
+        env: Env,
+        account: String
+    ) -> Result<Response, ContractError> {
+        let account = deps.api.addr_validate(&account)?;
+        if !AUTO_COMPOUND.has(deps.storage, &account) {
+            return Result::Err(ContractError::AutoCompoundDisabled {});
+        }
+
+        let account_info = MessageInfo { sender: account, funds: vec![] };
+        execute_compound(deps, env, account_info)
+    }
+
+    pub fn execute_set_auto_compound(
+        deps: DepsMut,
+        info: MessageInfo,
+        enabled: bool
+    ) -> Result<Response, ContractError> {
+        if enabled {
+            AUTO_COMPOUND.save(deps.storage, &info.sender, &Empty {})?;
+        } else {
+            AUTO_COMPOUND.remove(deps.storage, &info.sender);
+        }
+
+        let event = ContractEvent::make_auto_compound(info.sender.to_string(), enabled);
+        Ok(Response::new().add_event(event.to_cosmos_event()))
+    }
+
+    pub fn execute_claim_for(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        account: String
+    ) -> Result<Response, ContractError> {
+        let account = deps.api.addr_validate(&account)?;
+        if !CLAIM_OPERATORS.has(deps.storage, (&account, &info.sender)) {
+            return Result::Err(ContractError::Unauthorized {});
+        }
+
+        // Operators only trigger the claim, the rewards never leave the account
+        let account_info = MessageInfo { sender: account, funds: vec![] };
+        execute_claim(deps, env, account_info, None)
+    }
+
+    pub fn execute_kick(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        account: String
+    ) -> Result<Response, ContractError> {
+        let account = deps.api.addr_validate(&account)?;
+
+        let current_ts = Uint64::from(env.block.time.seconds());
+        let max_lock_period = CONFIG.load(deps.storage)?.max_lock_period;
+        let stored_balance = USER_STATE.may_load(deps.storage, &account)?
+            .map(|user_state| user_state.balance)
+            .unwrap_or_default();
+        let current_balance = internal_funcs::account_positions(deps.storage, &account)?
+            .into_iter()
+            .map(|(_, position)| {
+                internal_funcs::position_balance(
+                    position.locked_balance,
+                    position.locked_until,
+                    current_ts,
+                    max_lock_period
+                )
+            })
+            .sum::<Uint128>();
+        if stored_balance <= current_balance {
+            return Result::Err(ContractError::NothingToKick {});
+        }
+
+        // Claiming refreshes every position of the account and its stored balance
+        let account_info = MessageInfo { sender: account.clone(), funds: vec![] };
+        let response = execute_claim(deps, env, account_info, None)?;
+
+        let event = ContractEvent::make_kick(
+            account.to_string(),
+            info.sender.to_string(),
+            stored_balance,
+            current_balance
+        );
+        Ok(response.add_event(event.to_cosmos_event()))
+    }
+
+    pub fn execute_set_claim_operator(
+        deps: DepsMut,
+        info: MessageInfo,
+        operator: String,
+        approved: bool
+    ) -> Result<Response, ContractError> {
+        let operator = deps.api.addr_validate(&operator)?;
+
+        if approved {
+            CLAIM_OPERATORS.save(deps.storage, (&info.sender, &operator), &Empty {})?;
+        } else {
+            CLAIM_OPERATORS.remove(deps.storage, (&info.sender, &operator));
+        }
+
+        let event = ContractEvent::make_claim_operator(
+            info.sender.to_string(),
+            operator.to_string(),
+            approved
+        );
+        Ok(Response::new().add_event(event.to_cosmos_event()))
+    }
+
+    pub fn execute_add_income(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        add_amount: Uint128
+    ) -> Result<Response, ContractError> {
+        internal_funcs::assert_role(deps.storage, &info.sender, Role::IncomeManager)?;
+        PauseState::load(deps.storage)?.assert_not_paused(PausableAction::AddIncome)?;
+        Shutdown::assert_not_shutdown(deps.storage)?;
+
+        let mut token_state = TOKEN_STATE.load(deps.storage)?;
+        let current_block = Uint64::from(env.block.height);
+
+        token_state.accrue(deps.storage, current_block)?;
+
+        let unvested_income = token_state.update_reward_rate(deps.storage, UpdateRewardRateInput {
+            add_amount: add_amount,
+            new_distribution_period: token_state.distribution_period,
+            current_block,
+        })?;
+
+        internal_funcs::check_reserves(deps.as_ref(), &env)?;
+
+        let event = ContractEvent::NewIncome {
+            add_amount: add_amount,
+            remaining_amount: unvested_income,
+            reward_rate: token_state.reward_rate_stored,
+        };
+        let response = Response::new().add_event(event.to_cosmos_event());
+
+        Ok(response)
+    }
+
+    pub fn execute_add_epoch_income(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        add_amount: Uint128
+    ) -> Result<Response, ContractError> {
+        internal_funcs::assert_role(deps.storage, &info.sender, Role::IncomeManager)?;
+        PauseState::load(deps.storage)?.assert_not_paused(PausableAction::AddIncome)?;
+        Shutdown::assert_not_shutdown(deps.storage)?;
+
+        let current_ts = Uint64::from(env.block.time.seconds());
+        let epoch_start = fee_distributor::add_fees(deps.storage, current_ts, add_amount)?;
+
+        internal_funcs::check_reserves(deps.as_ref(), &env)?;
+
+        let event = ContractEvent::make_epoch_income(epoch_start, add_amount);
+        Ok(Response::new().add_event(event.to_cosmos_event()))
+    }
+
+    pub fn execute_claim_epoch_fees(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        limit: Option<u32>
+    ) -> Result<Response, ContractError> {
+        PauseState::load(deps.storage)?.assert_not_paused(PausableAction::Claim)?;
+        if let Some(shutdown) = SHUTDOWN.may_load(deps.storage)? {
+            if shutdown.reward_policy == ShutdownRewardPolicy::Forfeit {
+                return Result::Err(ContractError::Shutdown {});
+            }
+        }
+
+        let limit = limit.unwrap_or(DEFAULT_FEE_CLAIM_EPOCHS).min(MAX_FEE_CLAIM_EPOCHS) as usize;
+        let current_ts = Uint64::from(env.block.time.seconds());
+        let (claim_amount, next_epoch) = fee_distributor::claim_fees(
+            deps.storage,
+            &info.sender,
+            current_ts,
+            limit
+        )?;
+
+        let mut response = Response::new();
+        if !claim_amount.is_zero() {
+            let locked_token = TOKEN_STATE.load(deps.storage)?.locked_token;
+            let transfer_msg = AssetClient::new(&deps.querier, locked_token).make_transfer_msg(
+                info.sender.clone(),
+                claim_amount
+            )?;
+            response = response.add_message(transfer_msg);
+        }
+        internal_funcs::check_reserves(deps.as_ref(), &env)?;
+
+        let event = ContractEvent::make_epoch_fee_claim(
+            info.sender.to_string(),
+            claim_amount,
+            next_epoch
+        );
+        Ok(response.add_event(event.to_cosmos_event()))
+    }
+
+    pub fn execute_add_reward_asset(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        asset: Denom,
+        distribution_period: Uint64
+    ) -> Result<Response, ContractError> {
+        internal_funcs::assert_role(deps.storage, &info.sender, Role::ConfigAdmin)?;
+
+        if distribution_period.is_zero() {
+            return Result::Err(ContractError::ZeroDistributionPeriod {});
+        }
+
+        // Locked token income goes through AddIncome
+        let asset = internal_funcs::validate_asset(deps.api, asset)?;
+        if asset == TOKEN_STATE.load(deps.storage)?.locked_token {
+            return Result::Err(ContractError::InvalidToken {});
+        }
+
+        let asset_key = RewardAssetState::key(&asset);
+        if REWARD_ASSETS.has(deps.storage, &asset_key) {
+            return Result::Err(ContractError::RewardAssetExists {});
+        }
+        let reward_assets_count = REWARD_ASSETS.keys(
+            deps.storage,
+            None,
+            None,
+            Order::Ascending
+        ).count();
+        if reward_assets_count >= MAX_REWARD_ASSETS {
+            return Result::Err(ContractError::TooManyRewardAssets {});
+        }
+
+        let current_block = Uint64::from(env.block.height);
+        let asset_state = RewardAssetState::new(asset, distribution_period, current_block);
+        REWARD_ASSETS.save(deps.storage, &asset_key, &asset_state)?;
+
+        let event = ContractEvent::RewardAssetAdded {
+            asset: asset_key,
+            distribution_period,
+        };
+        Ok(Response::new().add_event(event.to_cosmos_event()))
+    }
+
+    pub fn execute_add_reward_income(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        asset: Denom,
+        add_amount: Uint128
+    ) -> Result<Response, ContractError> {
+        internal_funcs::assert_role(deps.storage, &info.sender, Role::IncomeManager)?;
+        PauseState::load(deps.storage)?.assert_not_paused(PausableAction::AddIncome)?;
+        Shutdown::assert_not_shutdown(deps.storage)?;
+
+        let asset_key = RewardAssetState::key(&asset);
+        let mut asset_state = REWARD_ASSETS.may_load(deps.storage, &asset_key)?.ok_or(
+            ContractError::RewardAssetNotFound {}
+        )?;
+
+        let total_supply = TOKEN_STATE.load(deps.storage)?.total_supply;
+        let current_block = Uint64::from(env.block.height);
+
+        asset_state.accrue(total_supply, current_block);
+        let unvested_income = asset_state.update_reward_rate(add_amount, current_block)?;
+        REWARD_ASSETS.save(deps.storage, &asset_key, &asset_state)?;
+
+        internal_funcs::check_reserves(deps.as_ref(), &env)?;
+
+        let event = ContractEvent::RewardAssetIncome {
+            asset: asset_key,
+            add_amount,
+            remaining_amount: unvested_income,
+            reward_rate: asset_state.reward_rate_stored,
+        };
+        Ok(Response::new().add_event(event.to_cosmos_event()))
+    }
+
+    pub fn execute_set_distribution_period(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        new_distribution_period: Uint64
+    ) -> Result<Response, ContractError> {
+        internal_funcs::assert_role(deps.storage, &info.sender, Role::ConfigAdmin)?;
+
+        let mut token_state = TOKEN_STATE.load(deps.storage)?;
+        let current_block = Uint64::from(env.block.height);
+        return token_state.set_distribution_period(
+            deps.storage,
+            current_block,
+            new_distribution_period
+        );
+    }
+
+    pub fn execute_propose_owner(
+        deps: DepsMut,
+        info: MessageInfo,
+        new_owner: String
+    ) -> Result<Response, ContractError> {
+        let mut ownership = OWNERSHIP.load(deps.storage)?;
+        ownership.assert_owner(&info.sender)?;
+
+        let new_owner = deps.api.addr_validate(&new_owner)?;
+        ownership.pending_owner = Some(new_owner.clone());
+        OWNERSHIP.save(deps.storage, &ownership)?;
+
+        let event = ContractEvent::OwnershipProposed {
+            owner: info.sender.to_string(),
+            pending_owner: new_owner.to_string(),
+        };
+
+        Ok(Response::new().add_event(event.to_cosmos_event()))
+    }
+
+    pub fn execute_accept_ownership(
+        deps: DepsMut,
+        info: MessageInfo
+    ) -> Result<Response, ContractError> {
+        let ownership = OWNERSHIP.load(deps.storage)?;
+
+        let pending_owner = ownership.pending_owner.ok_or(ContractError::NoPendingOwner {})?;
+        if pending_owner != info.sender {
+            return Result::Err(ContractError::Unauthorized {});
+        }
+
+        let previous_owner = ownership.owner.map(|owner| owner.to_string()).unwrap_or_default();
+        OWNERSHIP.save(deps.storage, &Ownership::new(pending_owner))?;
+
+        let event = ContractEvent::OwnershipTransferred {
+            previous_owner,
+            new_owner: info.sender.to_string(),
+        };
+
+        Ok(Response::new().add_event(event.to_cosmos_event()))
+    }
+
+    pub fn execute_renounce_ownership(
+        deps: DepsMut,
+        info: MessageInfo
+    ) -> Result<Response, ContractError> {
+        OWNERSHIP.load(deps.storage)?.assert_owner(&info.sender)?;
+
+        OWNERSHIP.save(deps.storage, &(Ownership {
+            owner: None,
+            pending_owner: None,
+        }))?;
+
+        let event = ContractEvent::OwnershipRenounced {
+            previous_owner: info.sender.to_string(),
+        };
+
+        Ok(Response::new().add_event(event.to_cosmos_event()))
+    }
+
+    pub fn execute_grant_role(
+        deps: DepsMut,
+        info: MessageInfo,
+        role: Role,
+        address: String
+    ) -> Result<Response, ContractError> {
+        OWNERSHIP.load(deps.storage)?.assert_owner(&info.sender)?;
+
+        let account = deps.api.addr_validate(&address)?;
+        ROLES.save(deps.storage, (role.as_str(), &account), &Empty {})?;
+
+        let event = ContractEvent::RoleGranted {
+            role: role.as_str().to_string(),
+            account: account.to_string(),
+        };
+
+        Ok(Response::new().add_event(event.to_cosmos_event()))
+    }
+
+    pub fn execute_revoke_role(
+        deps: DepsMut,
+        info: MessageInfo,
+        role: Role,
+        address: String
+    ) -> Result<Response, ContractError> {
+        OWNERSHIP.load(deps.storage)?.assert_owner(&info.sender)?;
+
+        let account = deps.api.addr_validate(&address)?;
+        ROLES.remove(deps.storage, (role.as_str(), &account));
+
+        let event = ContractEvent::RoleRevoked {
+            role: role.as_str().to_string(),
+            account: account.to_string(),
+        };
+
+        Ok(Response::new().add_event(event.to_cosmos_event()))
+    }
+
+    pub fn execute_set_paused(
+        deps: DepsMut,
+        info: MessageInfo,
+        actions: Vec<PausableAction>,
+        paused: bool
+    ) -> Result<Response, ContractError> {
+        internal_funcs::assert_role(deps.storage, &info.sender, Role::Pauser)?;
+
+        let mut pause_state = PauseState::load(deps.storage)?;
+        for action in actions.iter() {
+            pause_state.set(*action, paused);
+        }
+        PAUSE_STATE.save(deps.storage, &pause_state)?;
+
+        let event = ContractEvent::PauseStatusChanged {
+            actions: actions
+                .iter()
+                .map(|action| action.as_str().to_string())
+                .collect(),
+            paused,
+        };
+
+        Ok(Response::new().add_event(event.to_cosmos_event()))
+    }
+
+    pub fn execute_emergency_shutdown(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        reward_policy: ShutdownRewardPolicy
+    ) -> Result<Response, ContractError> {
+        OWNERSHIP.load(deps.storage)?.assert_owner(&info.sender)?;
+        Shutdown::assert_not_shutdown(deps.storage)?;
+
+        let shutdown = Shutdown {
+            activated_at: Uint64::from(env.block.time.seconds()),
+            reward_policy,
+        };
+        SHUTDOWN.save(deps.storage, &shutdown)?;
+
+        // Epoch fees can't be claimed anymore, they are no longer reserved for the accounts
+        if reward_policy == ShutdownRewardPolicy::Forfeit {
+            let unclaimed_fees = UNCLAIMED_EPOCH_FEES.may_load(deps.storage)?.unwrap_or_default();
+            UNCLAIMED_EPOCH_FEES.save(deps.storage, &Uint128::zero())?;
+            let locked_token = TOKEN_STATE.load(deps.storage)?.locked_token;
+            internal_funcs::add_forfeited_reward(deps.storage, locked_token, unclaimed_fees)?;
+        }
+
+        let event = ContractEvent::EmergencyShutdown {
+            reward_policy: reward_policy.as_str().to_string(),
+            activated_at: shutdown.activated_at,
+        };
+
+        Ok(Response::new().add_event(event.to_cosmos_event()))
+    }
+
+    pub fn execute_sweep_forfeited_rewards(
+        deps: DepsMut,
+        info: MessageInfo,
+        recipient: String
+    ) -> Result<Response, ContractError> {
+        OWNERSHIP.load(deps.storage)?.assert_owner(&info.sender)?;
+        let recipient = deps.api.addr_validate(&recipient)?;
+
+        let rewards = FORFEITED_REWARDS.range(deps.storage, None, None, Order::Ascending).collect::<
+            StdResult<Vec<(String, ForfeitedReward)>>
+        >()?;
+        if rewards.is_empty() {
+            return Result::Err(ContractError::NothingToSweep {});
+        }
+
+        let mut response = Response::new();
+        for (key, reward) in rewards {
+            FORFEITED_REWARDS.remove(deps.storage, &key);
+
+            let transfer_msg = AssetClient::new(&deps.querier, reward.asset).make_transfer_msg(
+                recipient.clone(),
+                reward.amount
+            )?;
+            let event = ContractEvent::make_forfeited_reward_swept(
+                recipient.to_string(),
+                key,
+                reward.amount
+            );
+            response = response.add_message(transfer_msg).add_event(event.to_cosmos_event());
+        }
+
+        Ok(response)
+    }
+
+    pub fn execute_update_config(
+        deps: DepsMut,
+        info: MessageInfo,
+        min_lock_period: Option<Uint64>,
+        max_lock_period: Option<Uint64>,
+        withdraw_delay: Option<Uint64>
+    ) -> Result<Response, ContractError> {
+        internal_funcs::assert_role(deps.storage, &info.sender, Role::ConfigAdmin)?;
+
+        let mut config = CONFIG.load(deps.storage)?;
+        if let Some(min_lock_period) = min_lock_period {
+            config.min_lock_period = min_lock_period;
+        }
+        if let Some(max_lock_period) = max_lock_period {
+            config.max_lock_period = max_lock_period;
+        }
+        if let Some(withdraw_delay) = withdraw_delay {
+            config.withdraw_delay = withdraw_delay;
+        }
+        config.validate()?;
+        CONFIG.save(deps.storage, &config)?;
+
+        let event = ContractEvent::ConfigUpdated {
+            min_lock_period: config.min_lock_period,
+            max_lock_period: config.max_lock_period,
+            withdraw_delay: config.withdraw_delay,
+        };
+
+        Ok(Response::new().add_event(event.to_cosmos_event()))
+    }
+
+    pub fn execute_set_early_withdraw_config(
+        deps: DepsMut,
+        info: MessageInfo,
+        config: Option<EarlyWithdrawConfig>
+    ) -> Result<Response, ContractError> {
+        internal_funcs::assert_role(deps.storage, &info.sender, Role::ConfigAdmin)?;
+
+        let event = match config {
+            Some(config) => {
+                config.validate()?;
+                if let PenaltyDestination::Treasury { address } = &config.penalty_destination {
+                    deps.api.addr_validate(address.as_str())?;
+                }
+                EARLY_WITHDRAW_CONFIG.save(deps.storage, &config)?;
+                ContractEvent::make_early_withdraw_config_updated(
+                    config.penalty_rate,
+                    config.penalty_floor,
+                    config.penalty_destination.as_str().to_string()
+                )
+            }
+            None => {
+                EARLY_WITHDRAW_CONFIG.remove(deps.storage);
+                ContractEvent::make_early_withdraw_config_updated(
+                    Decimal::zero(),
+                    Decimal::zero(),
+                    String::new()
+                )
+            }
+        };
+
+        Ok(Response::new().add_event(event.to_cosmos_event()))
+    }
+
+    pub fn execute_add_balance_hook(
+        deps: DepsMut,
+        info: MessageInfo,
+        address: String,
+        on_error: HookErrorPolicy
+    ) -> Result<Response, ContractError> {
+        internal_funcs::assert_role(deps.storage, &info.sender, Role::ConfigAdmin)?;
+
+        let address = deps.api.addr_validate(&address)?;
+        if BALANCE_HOOKS.has(deps.storage, &address) {
+            return Result::Err(ContractError::HookExists {});
+        }
+        let hooks_count = BALANCE_HOOKS.keys(deps.storage, None, None, Order::Ascending).count();
+        if hooks_count >= MAX_BALANCE_HOOKS {
+            return Result::Err(ContractError::TooManyHooks {});
+        }
+
+        BALANCE_HOOKS.save(deps.storage, &address, &on_error)?;
+
+        let event = ContractEvent::make_balance_hook_added(
+            address.to_string(),
+            on_error.as_str().to_string()
+        );
+        Ok(Response::new().add_event(event.to_cosmos_event()))
+    }
+
+    pub fn execute_remove_balance_hook(
+        deps: DepsMut,
+        info: MessageInfo,
+        address: String
+    ) -> Result<Response, ContractError> {
+        internal_funcs::assert_role(deps.storage, &info.sender, Role::ConfigAdmin)?;
+
+        let address = deps.api.addr_validate(&address)?;
+        if !BALANCE_HOOKS.has(deps.storage, &address) {
+            return Result::Err(ContractError::HookNotFound {});
+        }
+
+        BALANCE_HOOKS.remove(deps.storage, &address);
+
+        let event = ContractEvent::make_balance_hook_removed(address.to_string());
+        Ok(Response::new().add_event(event.to_cosmos_event()))
+    }
+
+    pub fn execute_add_gauge(
+        deps: DepsMut,
+        info: MessageInfo,
+        address: String
+    ) -> Result<Response, ContractError> {
+        internal_funcs::assert_role(deps.storage, &info.sender, Role::ConfigAdmin)?;
+
+        let gauge = deps.api.addr_validate(&address)?;
+        if GAUGES.has(deps.storage, &gauge) {
+            return Result::Err(ContractError::GaugeExists {});
+        }
+        GAUGES.save(deps.storage, &gauge, &Empty {})?;
+
+        let event = ContractEvent::make_gauge_added(gauge.to_string());
+        Ok(Response::new().add_event(event.to_cosmos_event()))
+    }
+
+    pub fn execute_vote_gauge(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        gauge: String,
+        weight: u16
+    ) -> Result<Response, ContractError> {
+        Shutdown::assert_not_shutdown(deps.storage)?;
+
+        let gauge = deps.api.addr_validate(&gauge)?;
+        if !GAUGES.has(deps.storage, &gauge) {
+            return Result::Err(ContractError::GaugeNotFound {});
+        }
+        if weight > MAX_GAUGE_VOTE_WEIGHT {
+            return Result::Err(ContractError::InvalidGaugeWeight {});
+        }
+
+        let current_ts = Uint64::from(env.block.time.seconds());
+        let last_vote = USER_GAUGE_LAST_VOTE.may_load(deps.storage, (&info.sender, &gauge))?;
+        if let Some(last_vote) = last_vote {
+            if current_ts < last_vote + Uint64::from(GAUGE_VOTE_COOLDOWN) {
+                return Result::Err(ContractError::GaugeVoteTooSoon {});
+            }
+        }
+
+        let locked_until = internal_funcs::account_positions(deps.storage, &info.sender)?
+            .iter()
+            .map(|(_, position)| position.locked_until)
+            .max()
+            .unwrap_or_default();
+        if weight > 0 && locked_until <= gauges::next_epoch_start(current_ts) {
+            return Result::Err(ContractError::LockExpiresTooSoon {});
+        }
+        let votes = USER_GAUGE_VOTES.prefix(&info.sender)
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<(Addr, u16)>>>()?;
+        let old_weight = votes
+            .iter()
+            .find(|(voted_gauge, _)| *voted_gauge == gauge)
+            .map(|(_, weight)| *weight);
+        let used_weight: u16 = votes
+            .iter()
+            .map(|(_, weight)| *weight)
+            .sum();
+        if used_weight - old_weight.unwrap_or_default() + weight > MAX_GAUGE_VOTE_WEIGHT {
+            return Result::Err(ContractError::GaugeWeightExceeded {});
+        }
+        if old_weight.is_none() && weight > 0 && votes.len() >= MAX_USER_GAUGE_VOTES {
+            return Result::Err(ContractError::TooManyGaugeVotes {});
+        }
+
+        // Follows the positions as they decay, not the ve balance of the last position change
+        let new_power = gauges::set_vote(deps.storage, &info.sender, &gauge, weight, current_ts)?;
+        USER_GAUGE_LAST_VOTE.save(deps.storage, (&info.sender, &gauge), &current_ts)?;
+
+        let event = ContractEvent::make_gauge_vote(
+            info.sender.to_string(),
+            gauge.to_string(),
+            weight,
+            new_power
+        );
+        Ok(Response::new().add_event(event.to_cosmos_event()))
+    }
+
+    pub fn execute_delegate(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        to: String
+    ) -> Result<Response, ContractError> {
+        let to = deps.api.addr_validate(&to)?;
+        if to == info.sender {
+            return Result::Err(ContractError::DelegateToSelf {});
+        }
+        if DELEGATIONS.may_load(deps.storage, &info.sender)? == Some(to.clone()) {
+            return Result::Err(ContractError::AlreadyDelegated {});
+        }
+
+        let balance = query_balance(deps.as_ref(), info.sender.to_string())?.balance;
+        delegation::delegate(deps.storage, &info.sender, &to, balance, env.block.height)?;
+
+        let event = ContractEvent::make_delegate(info.sender.to_string(), to.to_string(), balance);
+        Ok(Response::new().add_event(event.to_cosmos_event()))
+    }
+
+    pub fn execute_undelegate(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo
+    ) -> Result<Response, ContractError> {
+        let delegate = DELEGATIONS.may_load(deps.storage, &info.sender)?.ok_or(
+            ContractError::NotDelegated {}
+        )?;
+
+        let balance = query_balance(deps.as_ref(), info.sender.to_string())?.balance;
+        delegation::undelegate(deps.storage, &info.sender, balance, env.block.height)?;
+
+        let event = ContractEvent::make_undelegate(
+            info.sender.to_string(),
+            delegate.to_string(),
+            balance
+        );
+        Ok(Response::new().add_event(event.to_cosmos_event()))
+    }
+
+    pub fn execute_transfer_nft(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        recipient: String,
+        token_id: String
+    ) -> Result<Response, ContractError> {
+        let recipient = deps.api.addr_validate(&recipient)?;
+        transfer_nft(deps, &env, &info, &recipient, &token_id)
+    }
+
+    pub fn execute_send_nft(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        contract: String,
+        token_id: String,
+        msg: Binary
+    ) -> Result<Response, ContractError> {
+        let contract = deps.api.addr_validate(&contract)?;
+        let response = transfer_nft(deps, &env, &info, &contract, &token_id)?;
+
+        let receive_msg = nft::Cw721ReceiveMsg {
+            sender: info.sender.to_string(),
+            token_id,
+            msg,
+        };
+        Ok(response.add_message(receive_msg.into_cosmos_msg(contract)?))
+    }
+
+    fn transfer_nft(
+        mut deps: DepsMut,
+        env: &Env,
+        info: &MessageInfo,
+        recipient: &Addr,
+        token_id: &str
+    ) -> Result<Response, ContractError> {
+        Shutdown::assert_not_shutdown(deps.storage)?;
+
+        let id = nft::parse_token_id(token_id)?;
+        let position = POSITIONS.may_load(deps.storage, id)?.ok_or(
+            ContractError::PositionNotFound { id }
+        )?;
+        nft::assert_can_send(deps.storage, &env.block, &position, &info.sender)?;
+
+        // Nobody gets positions pushed into their account unless they allowed the sender
+        // with AllowLockFor, the same way as locks opened for them
+        let mut events = vec![];
+        if *recipient != info.sender && *recipient != position.owner {
+            events = internal_funcs::use_lock_for_allowance(
+                deps.storage,
+                recipient,
+                &info.sender,
+                position.locked_until
+            )?;
+        }
+
+        let response = internal_funcs::transfer_position(deps.branch(), env, id, recipient)?;
+
+        internal_funcs::check_reserves(deps.as_ref(), env)?;
+
+        let event = ContractEvent::make_transfer_nft(
+            info.sender.to_string(),
+            position.owner.to_string(),
+            recipient.to_string(),
+            id
+        );
+        Ok(response.add_event(event.to_cosmos_event()).add_events(events))
+    }
+
+    pub fn execute_approve(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        spender: String,
+        token_id: String,
+        expires: Option<Expiration>
+    ) -> Result<Response, ContractError> {
+        let spender = deps.api.addr_validate(&spender)?;
+        let expires = expires.unwrap_or_default();
+        if expires.is_expired(&env.block) {
+            return Result::Err(ContractError::ApprovalExpired {});
+        }
+
+        let id = nft::parse_token_id(&token_id)?;
+        let mut position = POSITIONS.may_load(deps.storage, id)?.ok_or(
+            ContractError::PositionNotFound { id }
+        )?;
+        nft::assert_can_approve(deps.storage, &env.block, &position, &info.sender)?;
+
+        position.approvals.retain(|approval| approval.spender != spender);
+        position.approvals.push(NftApproval { spender: spender.clone(), expires });
+        POSITIONS.save(deps.storage, id, &position)?;
+
+        let event = ContractEvent::make_nft_approval(
+            position.owner.to_string(),
+            spender.to_string(),
+            id,
+            true
+        );
+        Ok(Response::new().add_event(event.to_cosmos_event()))
+    }
+
+    pub fn execute_revoke(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        spender: String,
+        token_id: String
+    ) -> Result<Response, ContractError> {
+        let spender = deps.api.addr_validate(&spender)?;
+
+        let id = nft::parse_token_id(&token_id)?;
+        let mut position = POSITIONS.may_load(deps.storage, id)?.ok_or(
+            ContractError::PositionNotFound { id }
+        )?;
+        nft::assert_can_approve(deps.storage, &env.block, &position, &info.sender)?;
+
+        position.approvals.retain(|approval| approval.spender != spender);
+        POSITIONS.save(deps.storage, id, &position)?;
+
+        let event = ContractEvent::make_nft_approval(
+            position.owner.to_string(),
+            spender.to_string(),
+            id,
+            false
+        );
+        Ok(Response::new().add_event(event.to_cosmos_event()))
+    }
+
+    pub fn execute_approve_all(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        operator: String,
+        expires: Option<Expiration>
+    ) -> Result<Response, ContractError> {
+        let operator = deps.api.addr_validate(&operator)?;
+        let expires = expires.unwrap_or_default();
+        if expires.is_expired(&env.block) {
+            return Result::Err(ContractError::ApprovalExpired {});
+        }
+
+        NFT_OPERATORS.save(deps.storage, (&info.sender, &operator), &expires)?;
+
+        let event = ContractEvent::make_nft_operator(
+            info.sender.to_string(),
+            operator.to_string(),
+            true
+        );
+        Ok(Response::new().add_event(event.to_cosmos_event()))
+    }
+
+    pub fn execute_revoke_all(
+        deps: DepsMut,
+        info: MessageInfo,
+        operator: String
+    ) -> Result<Response, ContractError> {
+        let operator = deps.api.addr_validate(&operator)?;
+
+        NFT_OPERATORS.remove(deps.storage, (&info.sender, &operator));
+
+        let event = ContractEvent::make_nft_operator(
+            info.sender.to_string(),
+            operator.to_string(),
+            false
+        );
+        Ok(Response::new().add_event(event.to_cosmos_event()))
+    }
+}
+
+/// Only balance hooks registered with HookErrorPolicy::Catch reply, on error
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(_deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        BALANCE_HOOK_REPLY_ID => {
+            let mut response = Response::new();
+            if let SubMsgResult::Err(error) = msg.result {
+                let event = ContractEvent::make_balance_hook_failed(error);
+                response = response.add_event(event.to_cosmos_event());
+            }
+            Ok(response)
+        }
+        id => Result::Err(ContractError::UnknownReplyId { id }),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     use QueryMsg::*;
     use query::*;
 
@@ -382,14 +2010,75 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         Balance { address } => to_binary(&query_balance(deps, address)?),
         TokenInfo {} => to_binary(&query_token_info(deps)?),
         RewardRate {} => to_binary(&query_reward_rate(deps, env)?),
+        RewardAssets {} => to_binary(&query_reward_assets(deps)?),
+        BalanceHooks {} => to_binary(&query_balance_hooks(deps)?),
+        EpochFees { time } => to_binary(&query_epoch_fees(deps, time)?),
+        PendingEpochFees { address } =>
+            to_binary(&query_pending_epoch_fees(deps, env, address)?),
+        Gauges { start_after, limit } => to_binary(&query_gauges(deps, start_after, limit)?),
+        GaugeWeight { gauge, time } => to_binary(&query_gauge_weight(deps, env, gauge, time)?),
+        UserVotes { address } => to_binary(&query_user_votes(deps, env, address)?),
+        AutoCompoundAccounts { start_after, limit } =>
+            to_binary(&query_auto_compound_accounts(deps, start_after, limit)?),
+        ClaimOperators { account, start_after, limit } =>
+            to_binary(&query_claim_operators(deps, account, start_after, limit)?),
+        VotingPower { address } => to_binary(&query_voting_power(deps, address)?),
+        Delegations { delegate, start_after, limit } =>
+            to_binary(&query_delegations(deps, delegate, start_after, limit)?),
         PendingAccountReward { address } =>
             to_binary(&query_pending_account_reward(deps, env, address)?),
         UserInfo { address } => to_binary(&query_user_info(deps, address)?),
+        Positions { owner, start_after, limit } =>
+            to_binary(&query_positions(deps, owner, start_after, limit)?),
+        Position { id } => to_binary(&query_position(deps, id)?),
+        LockForAllowance { beneficiary, locker } =>
+            to_binary(&query_lock_for_allowance(deps, beneficiary, locker)?),
+        OwnerOf { token_id, include_expired } =>
+            to_binary(&query_owner_of(deps, env, token_id, include_expired)?),
+        Approval { token_id, spender, include_expired } =>
+            to_binary(&query_approval(deps, env, token_id, spender, include_expired)?),
+        Approvals { token_id, include_expired } =>
+            to_binary(&query_approvals(deps, env, token_id, include_expired)?),
+        AllOperators { owner, include_expired, start_after, limit } =>
+            to_binary(
+                &query_all_operators(deps, env, owner, include_expired, start_after, limit)?
+            ),
+        NumTokens {} => to_binary(&query_num_tokens(deps)?),
+        ContractInfo {} => to_binary(&query_nft_contract_info(deps)?),
+        NftInfo { token_id } => to_binary(&query_nft_info(deps, token_id)?),
+        AllNftInfo { token_id, include_expired } =>
+            to_binary(&query_all_nft_info(deps, env, token_id, include_expired)?),
+        Tokens { owner, start_after, limit } =>
+            to_binary(&query_tokens(deps, owner, start_after, limit)?),
+        AllTokens { start_after, limit } => to_binary(&query_all_tokens(deps, start_after, limit)?),
         VeTokenInfo {} => to_binary(&query_ve_token_info(deps)?),
+        BalanceOf { address, timestamp } =>
+            to_binary(&query_balance_of(deps, env, address, timestamp)?),
+        TotalSupplyAt { timestamp } => to_binary(&query_total_supply_at(deps, env, timestamp)?),
+        BalanceAtHeight { address, height } =>
+            to_binary(&query_balance_at_height(deps, address, height)?),
+        TotalSupplyAtHeight { height } => to_binary(&query_total_supply_at_height(deps, height)?),
+        Owner {} => to_binary(&query_owner(deps)?),
+        VotingPowerAtHeight { address, height } =>
+            to_binary(&query_voting_power_at_height(deps, env, address, height)?),
+        TotalPowerAtHeight { height } =>
+            to_binary(&query_total_power_at_height(deps, env, height)?),
+        Info {} => to_binary(&query_info(deps)?),
+        Dao {} => to_binary(&DAO.load(deps.storage)?),
+        RoleMembers { role, start_after, limit } =>
+            to_binary(&query_role_members(deps, role, start_after, limit)?),
+        PauseStatus {} => to_binary(&query_pause_status(deps)?),
+        ShutdownStatus {} => to_binary(&query_shutdown_status(deps)?),
+        Config {} => to_binary(&query_config(deps)?),
+        EarlyWithdrawConfig {} => to_binary(&query_early_withdraw_config(deps)?),
+        PreviewEarlyWithdraw { address } =>
+            to_binary(&query_preview_early_withdraw(deps, env, address)?),
     }
 }
 
 mod query {
+    use cw_storage_plus::Bound;
+
     use super::*;
 
     pub fn query_reward_rate(deps: Deps, env: Env) -> StdResult<RewardRateResponse> {
@@ -414,24 +2103,597 @@ mod query {
 
         let user_state = USER_STATE.load(deps.storage, &account).unwrap_or_default();
 
-        let pending_account_reward = user_state.pending_reward(pending_reward_per_token);
+        let mut pending_account_rewards = vec![PendingReward {
+            asset: token_state.locked_token,
+            amount: user_state.pending_reward(pending_reward_per_token),
+        }];
+
+        let current_block = Uint64::from(env.block.height);
+        for item in REWARD_ASSETS.range(deps.storage, None, None, Order::Ascending) {
+            let (key, asset_state) = item?;
+            let pending_reward_per_token =
+                asset_state.reward_per_token +
+                asset_state.pending_reward_per_token(token_state.total_supply, current_block);
+            let reward_snapshot = USER_REWARD_SNAPSHOTS.may_load(deps.storage, (&account, &key))?;
+
+            pending_account_rewards.push(PendingReward {
+                asset: asset_state.asset,
+                amount: user_state.pending_asset_reward(
+                    pending_reward_per_token,
+                    reward_snapshot.unwrap_or_default()
+                ),
+            });
+        }
 
         let resp = PendingAccountRewardResponse {
-            pending_account_reward,
+            pending_account_rewards,
         };
 
         Ok(resp)
     }
 
+    pub fn query_reward_assets(deps: Deps) -> StdResult<RewardAssetsResponse> {
+        let reward_assets = REWARD_ASSETS.range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, asset_state)| asset_state))
+            .collect::<StdResult<Vec<RewardAssetState>>>()?;
+
+        Ok(RewardAssetsResponse { reward_assets })
+    }
+
+    pub fn query_balance_hooks(deps: Deps) -> StdResult<BalanceHooksResponse> {
+        let hooks = BALANCE_HOOKS.range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(address, on_error)| BalanceHook { address, on_error }))
+            .collect::<StdResult<Vec<BalanceHook>>>()?;
+
+        Ok(BalanceHooksResponse { hooks })
+    }
+
+    pub fn query_epoch_fees(deps: Deps, time: Uint64) -> StdResult<EpochFeesResponse> {
+        let epoch_start = fee_distributor::epoch_start(time);
+
+        Ok(EpochFeesResponse {
+            epoch_start,
+            fees: EPOCH_FEES.may_load(deps.storage, epoch_start.u64())?.unwrap_or_default(),
+            total_supply: checkpoints::total_supply_at(deps.storage, epoch_start)?,
+        })
+    }
+
+    pub fn query_pending_epoch_fees(
+        deps: Deps,
+        env: Env,
+        address: String
+    ) -> StdResult<PendingEpochFeesResponse> {
+        let account = deps.api.addr_validate(&address)?;
+        let (amount, next_epoch) = fee_distributor::pending_fees(
+            deps.storage,
+            &account,
+            Uint64::from(env.block.time.seconds()),
+            MAX_FEE_CLAIM_EPOCHS as usize
+        )?;
+
+        Ok(PendingEpochFeesResponse { amount, next_epoch })
+    }
+
+    pub fn query_gauges(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>
+    ) -> StdResult<GaugesResponse> {
+        let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+        let start_after = start_after.map(Addr::unchecked);
+        let start = start_after.as_ref().map(Bound::exclusive);
+
+        let gauges = GAUGES.keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<Vec<Addr>>>()?;
+
+        Ok(GaugesResponse { gauges })
+    }
+
+    pub fn query_gauge_weight(
+        deps: Deps,
+        env: Env,
+        gauge: String,
+        time: Option<Uint64>
+    ) -> StdResult<GaugeWeightResponse> {
+        let gauge = deps.api.addr_validate(&gauge)?;
+        let time = time.unwrap_or(Uint64::from(env.block.time.seconds()));
+
+        Ok(GaugeWeightResponse {
+            epoch_start: gauges::epoch_start(time),
+            weight: gauges::gauge_weight_at(deps.storage, &gauge, time)?,
+            total_weight: gauges::total_weight_at(deps.storage, time)?,
+            relative_weight: gauges::relative_weight_at(deps.storage, &gauge, time)?,
+            gauge,
+        })
+    }
+
+    pub fn query_user_votes(
+        deps: Deps,
+        env: Env,
+        address: String
+    ) -> StdResult<UserVotesResponse> {
+        let account = deps.api.addr_validate(&address)?;
+        let current_ts = Uint64::from(env.block.time.seconds());
+
+        let votes = USER_GAUGE_VOTES.prefix(&account)
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| {
+                let (gauge, weight) = item?;
+                let last_vote = USER_GAUGE_LAST_VOTE.load(deps.storage, (&account, &gauge))?;
+                Ok(UserVote {
+                    power: gauges::vote_power_at(deps.storage, &account, &gauge, current_ts)?,
+                    gauge,
+                    weight,
+                    last_vote,
+                })
+            })
+            .collect::<StdResult<Vec<UserVote>>>()?;
+        let used_weight = votes
+            .iter()
+            .map(|vote| vote.weight)
+            .sum();
+
+        Ok(UserVotesResponse { votes, used_weight })
+    }
+
+    pub fn query_auto_compound_accounts(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>
+    ) -> StdResult<AutoCompoundAccountsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+        let start_after = start_after.map(Addr::unchecked);
+        let start = start_after.as_ref().map(Bound::exclusive);
+
+        let accounts = AUTO_COMPOUND.keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<Vec<Addr>>>()?;
+
+        Ok(AutoCompoundAccountsResponse { accounts })
+    }
+
+    pub fn query_claim_operators(
+        deps: Deps,
+        account: String,
+        start_after: Option<String>,
+        limit: Option<u32>
+    ) -> StdResult<ClaimOperatorsResponse> {
+        let account = deps.api.addr_validate(&account)?;
+        let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+        let start_after = start_after.map(Addr::unchecked);
+        let start = start_after.as_ref().map(Bound::exclusive);
+
+        let operators = CLAIM_OPERATORS.prefix(&account)
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<Vec<Addr>>>()?;
+
+        Ok(ClaimOperatorsResponse { operators })
+    }
+
+    pub fn query_lock_for_allowance(
+        deps: Deps,
+        beneficiary: String,
+        locker: String
+    ) -> StdResult<LockForAllowanceResponse> {
+        let beneficiary = deps.api.addr_validate(&beneficiary)?;
+        let locker = deps.api.addr_validate(&locker)?;
+
+        let allowance = LOCK_FOR_ALLOWANCES.may_load(deps.storage, (&beneficiary, &locker))?;
+        Ok(LockForAllowanceResponse {
+            max_locked_until: allowance.as_ref().map(|allowance| allowance.max_locked_until),
+            positions: allowance.map(|allowance| allowance.positions).unwrap_or_default(),
+        })
+    }
+
+    pub fn query_voting_power(deps: Deps, address: String) -> StdResult<VotingPowerResponse> {
+        let address = deps.api.addr_validate(&address)?;
+
+        Ok(VotingPowerResponse {
+            power: VOTING_POWER.may_load(deps.storage, &address)?.unwrap_or_default(),
+            delegated_to: DELEGATIONS.may_load(deps.storage, &address)?,
+        })
+    }
+
+    pub fn query_delegations(
+        deps: Deps,
+        delegate: String,
+        start_after: Option<String>,
+        limit: Option<u32>
+    ) -> StdResult<DelegationsResponse> {
+        let delegate = deps.api.addr_validate(&delegate)?;
+        let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+        let start_after = start_after.map(Addr::unchecked);
+        let start = start_after.as_ref().map(Bound::exclusive);
+
+        let delegations = DELEGATORS.prefix(&delegate)
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|delegator| {
+                let delegator = delegator?;
+                let power = query_balance(deps, delegator.to_string())?.balance;
+                Ok(Delegation { delegator, power })
+            })
+            .collect::<StdResult<Vec<Delegation>>>()?;
+
+        Ok(DelegationsResponse { delegations })
+    }
+
     pub fn query_user_info(deps: Deps, account: Addr) -> StdResult<UserInfoResponse> {
         let user_state = USER_STATE.load(deps.storage, &account).unwrap_or_default();
 
         Ok(UserInfoResponse::from_user_state(user_state))
     }
 
+    pub fn query_positions(
+        deps: Deps,
+        owner: String,
+        start_after: Option<u64>,
+        limit: Option<u32>
+    ) -> StdResult<PositionsResponse> {
+        let owner = deps.api.addr_validate(&owner)?;
+        let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+
+        let positions = OWNER_POSITIONS.prefix(&owner)
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|id| {
+                let id = id?;
+                Ok(PositionResponse::from_position(id, POSITIONS.load(deps.storage, id)?))
+            })
+            .collect::<StdResult<Vec<PositionResponse>>>()?;
+
+        Ok(PositionsResponse { positions })
+    }
+
+    pub fn query_position(deps: Deps, id: u64) -> StdResult<PositionResponse> {
+        let position = POSITIONS.load(deps.storage, id)?;
+
+        Ok(PositionResponse::from_position(id, position))
+    }
+
     pub fn query_ve_token_info(deps: Deps) -> StdResult<VeTokenInfoResponse> {
         let token_state = TOKEN_STATE.load(deps.storage)?;
 
         Ok(VeTokenInfoResponse::from_token_state(token_state))
     }
+
+    pub fn query_balance_of(
+        deps: Deps,
+        env: Env,
+        account: Addr,
+        timestamp: Option<Uint64>
+    ) -> StdResult<BalanceOfResponse> {
+        let timestamp = timestamp.unwrap_or(Uint64::from(env.block.time.seconds()));
+
+        Ok(BalanceOfResponse {
+            balance: checkpoints::balance_of(deps.storage, &account, timestamp)?,
+        })
+    }
+
+    pub fn query_total_supply_at(
+        deps: Deps,
+        env: Env,
+        timestamp: Option<Uint64>
+    ) -> StdResult<TotalSupplyAtResponse> {
+        let timestamp = timestamp.unwrap_or(Uint64::from(env.block.time.seconds()));
+
+        Ok(TotalSupplyAtResponse {
+            total_supply: checkpoints::total_supply_at(deps.storage, timestamp)?,
+        })
+    }
+
+    pub fn query_balance_at_height(
+        deps: Deps,
+        account: Addr,
+        height: Uint64
+    ) -> StdResult<BalanceAtHeightResponse> {
+        let balance = BALANCE_SNAPSHOTS.may_load_at_height(deps.storage, &account, height.u64())?;
+
+        Ok(BalanceAtHeightResponse {
+            balance: balance.unwrap_or_default(),
+            height,
+        })
+    }
+
+    pub fn query_total_supply_at_height(
+        deps: Deps,
+        height: Uint64
+    ) -> StdResult<TotalSupplyAtHeightResponse> {
+        let total_supply = TOTAL_SUPPLY_SNAPSHOTS.may_load_at_height(deps.storage, height.u64())?;
+
+        Ok(TotalSupplyAtHeightResponse {
+            total_supply: total_supply.unwrap_or_default(),
+            height,
+        })
+    }
+
+    pub fn query_voting_power_at_height(
+        deps: Deps,
+        env: Env,
+        address: String,
+        height: Option<u64>
+    ) -> StdResult<VotingPowerAtHeightResponse> {
+        let address = deps.api.addr_validate(&address)?;
+        let height = height.unwrap_or(env.block.height);
+        let power = VOTING_POWER.may_load_at_height(deps.storage, &address, height)?;
+
+        Ok(VotingPowerAtHeightResponse {
+            power: power.unwrap_or_default(),
+            height,
+        })
+    }
+
+    pub fn query_total_power_at_height(
+        deps: Deps,
+        env: Env,
+        height: Option<u64>
+    ) -> StdResult<TotalPowerAtHeightResponse> {
+        let height = height.unwrap_or(env.block.height);
+        let power = TOTAL_SUPPLY_SNAPSHOTS.may_load_at_height(deps.storage, height)?;
+
+        Ok(TotalPowerAtHeightResponse {
+            power: power.unwrap_or_default(),
+            height,
+        })
+    }
+
+    pub fn query_info(deps: Deps) -> StdResult<InfoResponse> {
+        Ok(InfoResponse {
+            info: get_contract_version(deps.storage)?,
+        })
+    }
+
+    pub fn query_owner(deps: Deps) -> StdResult<OwnerResponse> {
+        let ownership = OWNERSHIP.load(deps.storage)?;
+
+        Ok(OwnerResponse {
+            owner: ownership.owner,
+            pending_owner: ownership.pending_owner,
+        })
+    }
+
+    pub fn query_role_members(
+        deps: Deps,
+        role: Role,
+        start_after: Option<String>,
+        limit: Option<u32>
+    ) -> StdResult<RoleMembersResponse> {
+        let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+        let start_after = start_after.map(Addr::unchecked);
+        let start = start_after.as_ref().map(Bound::exclusive);
+
+        let members = ROLES.prefix(role.as_str())
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<Vec<Addr>>>()?;
+
+        Ok(RoleMembersResponse { members })
+    }
+
+    pub fn query_pause_status(deps: Deps) -> StdResult<PauseStatusResponse> {
+        let pause_state = PauseState::load(deps.storage)?;
+
+        Ok(PauseStatusResponse::from_pause_state(pause_state))
+    }
+
+    pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+        let config = CONFIG.load(deps.storage)?;
+
+        Ok(ConfigResponse::from_config(config))
+    }
+
+    pub fn query_early_withdraw_config(deps: Deps) -> StdResult<EarlyWithdrawConfigResponse> {
+        Ok(EarlyWithdrawConfigResponse {
+            config: EARLY_WITHDRAW_CONFIG.may_load(deps.storage)?,
+        })
+    }
+
+    /// Fails while early withdraw is disabled
+    pub fn query_preview_early_withdraw(
+        deps: Deps,
+        env: Env,
+        address: String
+    ) -> StdResult<PreviewEarlyWithdrawResponse> {
+        let address = deps.api.addr_validate(&address)?;
+        let early_withdraw_config = EARLY_WITHDRAW_CONFIG.load(deps.storage)?;
+        let max_lock_period = CONFIG.load(deps.storage)?.max_lock_period;
+        let current_time = Uint64::from(env.block.time.seconds());
+
+        let positions = internal_funcs::account_positions(deps.storage, &address)?
+            .into_iter()
+            .filter(|(_, position)| {
+                current_time < position.locked_until && !position.locked_balance.is_zero()
+            })
+            .map(|(id, position)| {
+                let penalty = early_withdraw_config.penalty(
+                    position.locked_balance,
+                    position.locked_until - current_time,
+                    max_lock_period
+                );
+                EarlyWithdrawPreview {
+                    position: id,
+                    locked_balance: position.locked_balance,
+                    penalty,
+                    amount: position.locked_balance - penalty,
+                }
+            })
+            .collect();
+
+        Ok(PreviewEarlyWithdrawResponse { positions })
+    }
+
+    pub fn query_shutdown_status(deps: Deps) -> StdResult<ShutdownStatusResponse> {
+        Ok(ShutdownStatusResponse {
+            shutdown: SHUTDOWN.may_load(deps.storage)?,
+        })
+    }
+
+    fn load_nft(deps: Deps, token_id: &str) -> StdResult<Position> {
+        POSITIONS.load(deps.storage, nft::parse_token_id(token_id)?)
+    }
+
+    fn unexpired_approvals(
+        env: &Env,
+        approvals: Vec<NftApproval>,
+        include_expired: Option<bool>
+    ) -> Vec<Approval> {
+        approvals
+            .into_iter()
+            .filter(|approval| {
+                include_expired.unwrap_or(false) || !approval.expires.is_expired(&env.block)
+            })
+            .map(Approval::from_nft_approval)
+            .collect()
+    }
+
+    pub fn query_owner_of(
+        deps: Deps,
+        env: Env,
+        token_id: String,
+        include_expired: Option<bool>
+    ) -> StdResult<OwnerOfResponse> {
+        let position = load_nft(deps, &token_id)?;
+
+        Ok(OwnerOfResponse {
+            owner: position.owner.to_string(),
+            approvals: unexpired_approvals(&env, position.approvals, include_expired),
+        })
+    }
+
+    pub fn query_approval(
+        deps: Deps,
+        env: Env,
+        token_id: String,
+        spender: String,
+        include_expired: Option<bool>
+    ) -> StdResult<ApprovalResponse> {
+        let position = load_nft(deps, &token_id)?;
+
+        unexpired_approvals(&env, position.approvals, include_expired)
+            .into_iter()
+            .find(|approval| approval.spender == spender)
+            .map(|approval| ApprovalResponse { approval })
+            .ok_or_else(|| StdError::not_found("Approval"))
+    }
+
+    pub fn query_approvals(
+        deps: Deps,
+        env: Env,
+        token_id: String,
+        include_expired: Option<bool>
+    ) -> StdResult<ApprovalsResponse> {
+        let position = load_nft(deps, &token_id)?;
+
+        Ok(ApprovalsResponse {
+            approvals: unexpired_approvals(&env, position.approvals, include_expired),
+        })
+    }
+
+    pub fn query_all_operators(
+        deps: Deps,
+        env: Env,
+        owner: String,
+        include_expired: Option<bool>,
+        start_after: Option<String>,
+        limit: Option<u32>
+    ) -> StdResult<OperatorsResponse> {
+        let owner = deps.api.addr_validate(&owner)?;
+        let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+        let start_after = start_after.map(Addr::unchecked);
+        let start = start_after.as_ref().map(Bound::exclusive);
+
+        let operators = NFT_OPERATORS.prefix(&owner)
+            .range(deps.storage, start, None, Order::Ascending)
+            .filter(|item| {
+                include_expired.unwrap_or(false) ||
+                    item.as_ref().map_or(true, |(_, expires)| !expires.is_expired(&env.block))
+            })
+            .take(limit)
+            .map(|item| {
+                let (spender, expires) = item?;
+                Ok(Approval { spender: spender.into_string(), expires })
+            })
+            .collect::<StdResult<Vec<Approval>>>()?;
+
+        Ok(OperatorsResponse { operators })
+    }
+
+    pub fn query_num_tokens(deps: Deps) -> StdResult<NumTokensResponse> {
+        Ok(NumTokensResponse {
+            count: NUM_POSITIONS.may_load(deps.storage)?.unwrap_or_default(),
+        })
+    }
+
+    pub fn query_nft_contract_info(deps: Deps) -> StdResult<NftContractInfoResponse> {
+        let token_info = TOKEN_INFO.load(deps.storage)?;
+
+        Ok(NftContractInfoResponse {
+            name: token_info.name,
+            symbol: token_info.symbol,
+        })
+    }
+
+    pub fn query_nft_info(deps: Deps, token_id: String) -> StdResult<NftInfoResponse> {
+        let position = load_nft(deps, &token_id)?;
+
+        Ok(NftInfoResponse {
+            token_uri: None,
+            extension: nft::PositionMetadata {
+                locked_balance: position.locked_balance,
+                locked_until: position.locked_until,
+            },
+        })
+    }
+
+    pub fn query_all_nft_info(
+        deps: Deps,
+        env: Env,
+        token_id: String,
+        include_expired: Option<bool>
+    ) -> StdResult<AllNftInfoResponse> {
+        Ok(AllNftInfoResponse {
+            access: query_owner_of(deps, env, token_id.clone(), include_expired)?,
+            info: query_nft_info(deps, token_id)?,
+        })
+    }
+
+    pub fn query_tokens(
+        deps: Deps,
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>
+    ) -> StdResult<TokensResponse> {
+        let owner = deps.api.addr_validate(&owner)?;
+        let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+        let start_after = start_after.map(|token_id| nft::parse_token_id(&token_id)).transpose()?;
+        let start = start_after.map(Bound::exclusive);
+
+        let tokens = OWNER_POSITIONS.prefix(&owner)
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|id| id.map(|id| id.to_string()))
+            .collect::<StdResult<Vec<String>>>()?;
+
+        Ok(TokensResponse { tokens })
+    }
+
+    pub fn query_all_tokens(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>
+    ) -> StdResult<TokensResponse> {
+        let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+        let start_after = start_after.map(|token_id| nft::parse_token_id(&token_id)).transpose()?;
+        let start = start_after.map(Bound::exclusive);
+
+        let tokens = POSITIONS.keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|id| id.map(|id| id.to_string()))
+            .collect::<StdResult<Vec<String>>>()?;
+
+        Ok(TokensResponse { tokens })
+    }
 }
@@ -0,0 +1,74 @@
+use cosmwasm_std::{ Addr, Empty, StdResult, Storage, Uint128 };
+
+use crate::state::{ DELEGATIONS, DELEGATORS, VOTING_POWER };
+
+/// Delegation of governance voting power.
+///
+/// VOTING_POWER of an address is its own ve balance unless delegated, plus the ve
+/// balances delegated to it. Locked tokens, ve balances and so rewards and gauge votes
+/// stay with the delegator: set_balance only moves its balance changes to the delegate.
+///
+/// Address holding the voting power of account
+pub fn voting_power_holder(storage: &dyn Storage, account: &Addr) -> StdResult<Addr> {
+    Ok(DELEGATIONS.may_load(storage, account)?.unwrap_or_else(|| account.clone()))
+}
+
+/// Moves amount of voting power from one address to another, from the given height on
+pub fn move_voting_power(
+    storage: &mut dyn Storage,
+    from: &Addr,
+    to: &Addr,
+    amount: Uint128,
+    height: u64
+) -> StdResult<()> {
+    if amount.is_zero() {
+        return Ok(());
+    }
+    let from_power = VOTING_POWER.may_load(storage, from)?.unwrap_or_default();
+    VOTING_POWER.save(storage, from, &from_power.checked_sub(amount)?, height)?;
+    let to_power = VOTING_POWER.may_load(storage, to)?.unwrap_or_default();
+    VOTING_POWER.save(storage, to, &(to_power + amount), height)
+}
+
+/// Moves the balance change of the account to whoever holds its voting power
+pub fn update_voting_power(
+    storage: &mut dyn Storage,
+    account: &Addr,
+    old_balance: Uint128,
+    new_balance: Uint128,
+    height: u64
+) -> StdResult<()> {
+    let holder = voting_power_holder(storage, account)?;
+    let power = VOTING_POWER.may_load(storage, &holder)?.unwrap_or_default();
+    let power = (power + new_balance).checked_sub(old_balance)?;
+    VOTING_POWER.save(storage, &holder, &power, height)
+}
+
+/// Moves the voting power of delegator, with ve balance, to delegate
+pub fn delegate(
+    storage: &mut dyn Storage,
+    delegator: &Addr,
+    delegate: &Addr,
+    balance: Uint128,
+    height: u64
+) -> StdResult<()> {
+    undelegate(storage, delegator, balance, height)?;
+    move_voting_power(storage, delegator, delegate, balance, height)?;
+    DELEGATIONS.save(storage, delegator, delegate)?;
+    DELEGATORS.save(storage, (delegate, delegator), &Empty {})
+}
+
+/// Gives the voting power of delegator, with ve balance, back to it
+pub fn undelegate(
+    storage: &mut dyn Storage,
+    delegator: &Addr,
+    balance: Uint128,
+    height: u64
+) -> StdResult<()> {
+    if let Some(delegate) = DELEGATIONS.may_load(storage, delegator)? {
+        move_voting_power(storage, &delegate, delegator, balance, height)?;
+        DELEGATIONS.remove(storage, delegator);
+        DELEGATORS.remove(storage, (&delegate, delegator));
+    }
+    Ok(())
+}
@@ -1,24 +1,110 @@
-use cosmwasm_std::{ StdError };
+use cosmwasm_std::{ StdError, Uint128 };
+use cw_utils::PaymentError;
 use thiserror::Error;
 
+impl From<semver::Error> for ContractError {
+    fn from(err: semver::Error) -> Self {
+        ContractError::SemVer(err.to_string())
+    }
+}
+
 #[derive(Error, Debug, PartialEq)]
 pub enum ContractError {
     #[error("{0}")] Std(#[from] StdError),
+    #[error("{0}")] Payment(#[from] PaymentError),
     #[error("VeToken: accrue first")] AccrueFirst {},
     #[error("VeToken: claim first")] ClaimFirst {},
+    #[error("VeToken: ve balance is up to date")] NothingToKick {},
     #[error("VeToken: distribution period must be >= 100 blocks")] ZeroDistributionPeriod {},
     #[error("VeToken: reserve balance too low")] InsufficientReserves {},
     #[error("Unauthorized")] Unauthorized {},
+    #[error("VeToken: unsupported token")] InvalidToken {},
+    #[error("VeToken: tokens must be sent with CW20 Send")] Cw20SendRequired {},
+    #[error("VeToken: sent {received} but expected {expected}")] FundsMismatch {
+        expected: Uint128,
+        received: Uint128,
+    },
+    #[error("VeToken: no pending owner")] NoPendingOwner {},
+    #[error("VeToken: {action} is paused")] Paused {
+        action: String,
+    },
+    #[error("VeToken: contract is shut down")] Shutdown {},
+
+    #[error("VeToken: reward asset already registered")] RewardAssetExists {},
+    #[error("VeToken: reward asset not registered")] RewardAssetNotFound {},
+    #[error("VeToken: too many reward assets")] TooManyRewardAssets {},
+
+    #[error("VeToken: balance hook already registered")] HookExists {},
+    #[error("VeToken: balance hook not registered")] HookNotFound {},
+    #[error("VeToken: too many balance hooks")] TooManyHooks {},
+    #[error("VeToken: gauge already registered")] GaugeExists {},
+    #[error("VeToken: gauge not registered")] GaugeNotFound {},
+    #[error("VeToken: gauge vote weight above 10000 bps")] InvalidGaugeWeight {},
+    #[error("VeToken: gauge votes above 10000 bps in total")] GaugeWeightExceeded {},
+    #[error("VeToken: too many gauge votes")] TooManyGaugeVotes {},
+    #[error("VeToken: gauge vote cooldown not over")] GaugeVoteTooSoon {},
+    #[error("VeToken: lock expires before the next epoch")] LockExpiresTooSoon {},
+    #[error("VeToken: cannot delegate to self")] DelegateToSelf {},
+    #[error("VeToken: already delegated to this address")] AlreadyDelegated {},
+    #[error("VeToken: voting power not delegated")] NotDelegated {},
+    #[error("VeToken: unknown reply id {id}")] UnknownReplyId {
+        id: u64,
+    },
 
     #[error("VeToken: lock time too long")] LockPeriodTooLong {},
-    #[error("VeToken: cannot reduce locked time")] CannotReduceLockedTime {},
+    #[error("VeToken: new unlock time must be after the current one")] LockNotExtended {},
     #[error("VeToken: lock time too short")] LockPeriodTooShort {},
+    #[error("VeToken: invalid lock periods")] InvalidLockPeriods {},
+    #[error("VeToken: cannot open an empty position")] NothingToLock {},
+    #[error("VeToken: increase amount must be nonzero")] NothingToIncrease {},
+    #[error("VeToken: cannot add to an expired lock")] IncreaseExpiredLock {},
+    #[error("VeToken: nothing to compound")] NothingToCompound {},
+    #[error("VeToken: no forfeited rewards to sweep")] NothingToSweep {},
+    #[error("VeToken: no unexpired position to compound into")] NoActiveLock {},
+    #[error("VeToken: auto-compound not enabled")] AutoCompoundDisabled {},
+    #[error("VeToken: beneficiary has not allowed positions from sender")] LockForNotAllowed {},
+    #[error("VeToken: lock ends after the time allowed by the beneficiary")] LockForTooLong {},
+    #[error("VeToken: allowance must permit at least one position")] NoLockForPositions {},
+
+    #[error("VeToken: position {id} not found")] PositionNotFound {
+        id: u64,
+    },
+    #[error("VeToken: too many positions")] TooManyPositions {},
+    #[error("VeToken: cannot merge a position into itself")] MergeSamePosition {},
+    #[error("VeToken: split amounts must be nonzero and below the locked balance")] InvalidSplit {},
+    #[error("VeToken: position {id} has a pending withdraw request")] WithdrawRequested {
+        id: u64,
+    },
+    #[error("VeToken: approval already expired")] ApprovalExpired {},
 
     #[error("VeToken: nothing to withdraw")] NothingToWithdraw {},
     #[error("VeToken: cannot withdraw before unlock")] WithdrawBeforeUnlock {},
     #[error("VeToken: withdraw delay not over")] WithdrawDelayNotOver {},
+    #[error("VeToken: withdraw amount must be nonzero and at most the locked balance")]
+    InvalidWithdrawAmount {},
+    #[error("VeToken: position {id} has no pending withdraw request")] NoWithdrawRequest {
+        id: u64,
+    },
+    #[error("VeToken: early withdraw is disabled")] EarlyWithdrawDisabled {},
+    #[error("VeToken: lock already expired, use RequestWithdraw")] LockExpired {},
+    #[error("VeToken: penalty rate and floor must be at most 1")] InvalidPenalty {},
+
+    #[error("VeToken: cannot migrate from contract {previous_contract}")] InvalidMigrationContract {
+        previous_contract: String,
+    },
+    #[error(
+        "VeToken: cannot migrate from version {previous_version} to older {new_version}"
+    )] MigrationDowngrade {
+        previous_version: String,
+        new_version: String,
+    },
+    #[error("VeToken: owner is required to migrate from this version")] MigrationOwnerRequired {},
+    #[error("VeToken: no migration from version {previous_version}")] UnsupportedMigration {
+        previous_version: String,
+    },
 
     #[error("Unimplemented")] Unimplemented {},
 
     #[error("{0}")] CW20BaseError(String),
+    #[error("{0}")] SemVer(String),
 }
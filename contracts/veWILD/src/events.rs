@@ -1,24 +1,91 @@
-use cosmwasm_std::{ Uint128, Uint64, Attribute, Event };
+use cosmwasm_std::{ Decimal, Uint128, Uint64, Attribute, Event };
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum ContractEvent {
     Lock {
         account: String,
+        position: u64,
         locked_balance: Uint128,
         ve_balance: Uint128,
         locked_until: Uint64,
     },
+    IncreaseAmount {
+        account: String,
+        position: u64,
+        amount: Uint128,
+        locked_balance: Uint128,
+        ve_balance: Uint128,
+    },
+    ExtendLock {
+        account: String,
+        position: u64,
+        locked_until: Uint64,
+        ve_balance: Uint128,
+    },
+    LockFor {
+        locker: String,
+        beneficiary: String,
+        position: u64,
+        amount: Uint128,
+    },
+    /// max_locked_until and positions are 0 once disallowed or used up
+    LockForAllowance {
+        beneficiary: String,
+        locker: String,
+        max_locked_until: Uint64,
+        positions: u32,
+    },
     WithdrawRequest {
         account: String,
+        position: u64,
         amount: Uint128,
         withdraw_at: Uint64,
     },
     Withdraw {
         account: String,
+        position: u64,
         amount: Uint128,
     },
+    WithdrawRequestCancelled {
+        account: String,
+        position: u64,
+        amount: Uint128,
+    },
+    /// amount is what the account received, penalty went to penalty_destination
+    EarlyWithdraw {
+        account: String,
+        position: u64,
+        amount: Uint128,
+        penalty: Uint128,
+        penalty_destination: String,
+    },
+    Merge {
+        account: String,
+        from: u64,
+        into: u64,
+        locked_balance: Uint128,
+        locked_until: Uint64,
+    },
+    Split {
+        account: String,
+        position: u64,
+        new_positions: Vec<u64>,
+        locked_balance: Uint128,
+    },
+    Compound {
+        account: String,
+        position: u64,
+        amount: Uint128,
+        locked_balance: Uint128,
+        ve_balance: Uint128,
+    },
+    AutoCompound {
+        account: String,
+        enabled: bool,
+    },
     Claim {
         account: String,
+        recipient: String,
         claim_amount: Uint128,
         ve_balance: Uint128,
     },
@@ -38,41 +105,317 @@ pub enum ContractEvent {
         amount: Uint128,
         to: String,
     },
+    OwnershipProposed {
+        owner: String,
+        pending_owner: String,
+    },
+    OwnershipTransferred {
+        previous_owner: String,
+        new_owner: String,
+    },
+    OwnershipRenounced {
+        previous_owner: String,
+    },
+    RoleGranted {
+        role: String,
+        account: String,
+    },
+    RoleRevoked {
+        role: String,
+        account: String,
+    },
+    PauseStatusChanged {
+        actions: Vec<String>,
+        paused: bool,
+    },
+    EmergencyShutdown {
+        reward_policy: String,
+        activated_at: Uint64,
+    },
+    ForfeitedRewardSwept {
+        recipient: String,
+        asset: String,
+        amount: Uint128,
+    },
+    Migrate {
+        from_version: String,
+        to_version: String,
+    },
+    ConfigUpdated {
+        min_lock_period: Uint64,
+        max_lock_period: Uint64,
+        withdraw_delay: Uint64,
+    },
+    /// all zero and empty once disabled
+    EarlyWithdrawConfigUpdated {
+        penalty_rate: Decimal,
+        penalty_floor: Decimal,
+        penalty_destination: String,
+    },
+    RewardAssetAdded {
+        asset: String,
+        distribution_period: Uint64,
+    },
+    RewardAssetIncome {
+        asset: String,
+        add_amount: Uint128,
+        remaining_amount: Uint128,
+        reward_rate: Uint128,
+    },
+    RewardAssetClaim {
+        account: String,
+        recipient: String,
+        asset: String,
+        claim_amount: Uint128,
+    },
+    BalanceHookAdded {
+        address: String,
+        on_error: String,
+    },
+    BalanceHookRemoved {
+        address: String,
+    },
+    BalanceHookFailed {
+        error: String,
+    },
+    EpochIncome {
+        epoch_start: Uint64,
+        add_amount: Uint128,
+    },
+    EpochFeeClaim {
+        account: String,
+        claim_amount: Uint128,
+        next_epoch: Uint64,
+    },
+    GaugeAdded {
+        gauge: String,
+    },
+    GaugeVote {
+        account: String,
+        gauge: String,
+        weight: u16,
+        power: Uint128,
+    },
+    TransferNft {
+        sender: String,
+        owner: String,
+        recipient: String,
+        position: u64,
+    },
+    NftApproval {
+        owner: String,
+        spender: String,
+        position: u64,
+        approved: bool,
+    },
+    NftOperator {
+        owner: String,
+        operator: String,
+        approved: bool,
+    },
+    ClaimOperator {
+        account: String,
+        operator: String,
+        approved: bool,
+    },
+    Kick {
+        account: String,
+        sender: String,
+        old_balance: Uint128,
+        new_balance: Uint128,
+    },
+    Delegate {
+        delegator: String,
+        delegate: String,
+        power: Uint128,
+    },
+    Undelegate {
+        delegator: String,
+        delegate: String,
+        power: Uint128,
+    },
 }
 
 impl ContractEvent {
     pub fn make_lock(
         account: String,
+        position: u64,
         locked_balance: Uint128,
         ve_balance: Uint128,
         locked_until: Uint64
     ) -> Self {
         ContractEvent::Lock {
             account,
+            position,
             locked_balance,
             ve_balance,
             locked_until,
         }
     }
 
-    pub fn make_withdraw_request(account: String, amount: Uint128, withdraw_at: Uint64) -> Self {
+    pub fn make_increase_amount(
+        account: String,
+        position: u64,
+        amount: Uint128,
+        locked_balance: Uint128,
+        ve_balance: Uint128
+    ) -> Self {
+        ContractEvent::IncreaseAmount {
+            account,
+            position,
+            amount,
+            locked_balance,
+            ve_balance,
+        }
+    }
+
+    pub fn make_extend_lock(
+        account: String,
+        position: u64,
+        locked_until: Uint64,
+        ve_balance: Uint128
+    ) -> Self {
+        ContractEvent::ExtendLock {
+            account,
+            position,
+            locked_until,
+            ve_balance,
+        }
+    }
+
+    pub fn make_lock_for(
+        locker: String,
+        beneficiary: String,
+        position: u64,
+        amount: Uint128
+    ) -> Self {
+        ContractEvent::LockFor {
+            locker,
+            beneficiary,
+            position,
+            amount,
+        }
+    }
+
+    pub fn make_lock_for_allowance(
+        beneficiary: String,
+        locker: String,
+        max_locked_until: Uint64,
+        positions: u32
+    ) -> Self {
+        ContractEvent::LockForAllowance {
+            beneficiary,
+            locker,
+            max_locked_until,
+            positions,
+        }
+    }
+
+    pub fn make_withdraw_request(
+        account: String,
+        position: u64,
+        amount: Uint128,
+        withdraw_at: Uint64
+    ) -> Self {
         ContractEvent::WithdrawRequest {
             account,
+            position,
             amount,
             withdraw_at,
         }
     }
 
-    pub fn make_withdraw(account: String, amount: Uint128) -> Self {
+    pub fn make_withdraw(account: String, position: u64, amount: Uint128) -> Self {
         ContractEvent::Withdraw {
             account,
+            position,
+            amount,
+        }
+    }
+
+    pub fn make_withdraw_request_cancelled(account: String, position: u64, amount: Uint128) -> Self {
+        ContractEvent::WithdrawRequestCancelled {
+            account,
+            position,
             amount,
         }
     }
 
-    pub fn make_claim(account: String, claim_amount: Uint128, ve_balance: Uint128) -> Self {
+    pub fn make_early_withdraw(
+        account: String,
+        position: u64,
+        amount: Uint128,
+        penalty: Uint128,
+        penalty_destination: String
+    ) -> Self {
+        ContractEvent::EarlyWithdraw {
+            account,
+            position,
+            amount,
+            penalty,
+            penalty_destination,
+        }
+    }
+
+    pub fn make_merge(
+        account: String,
+        from: u64,
+        into: u64,
+        locked_balance: Uint128,
+        locked_until: Uint64
+    ) -> Self {
+        ContractEvent::Merge {
+            account,
+            from,
+            into,
+            locked_balance,
+            locked_until,
+        }
+    }
+
+    pub fn make_split(
+        account: String,
+        position: u64,
+        new_positions: Vec<u64>,
+        locked_balance: Uint128
+    ) -> Self {
+        ContractEvent::Split {
+            account,
+            position,
+            new_positions,
+            locked_balance,
+        }
+    }
+
+    pub fn make_compound(
+        account: String,
+        position: u64,
+        amount: Uint128,
+        locked_balance: Uint128,
+        ve_balance: Uint128
+    ) -> Self {
+        ContractEvent::Compound {
+            account,
+            position,
+            amount,
+            locked_balance,
+            ve_balance,
+        }
+    }
+
+    pub fn make_auto_compound(account: String, enabled: bool) -> Self {
+        ContractEvent::AutoCompound { account, enabled }
+    }
+
+    pub fn make_claim(
+        account: String,
+        recipient: String,
+        claim_amount: Uint128,
+        ve_balance: Uint128
+    ) -> Self {
         ContractEvent::Claim {
             account,
+            recipient,
             claim_amount,
             ve_balance,
         }
@@ -101,33 +444,364 @@ impl ContractEvent {
     pub fn make_mint(amount: Uint128, to: String) -> Self {
         ContractEvent::Mint { amount, to }
     }
+
+    pub fn make_ownership_proposed(owner: String, pending_owner: String) -> Self {
+        ContractEvent::OwnershipProposed { owner, pending_owner }
+    }
+
+    pub fn make_ownership_transferred(previous_owner: String, new_owner: String) -> Self {
+        ContractEvent::OwnershipTransferred { previous_owner, new_owner }
+    }
+
+    pub fn make_ownership_renounced(previous_owner: String) -> Self {
+        ContractEvent::OwnershipRenounced { previous_owner }
+    }
+
+    pub fn make_role_granted(role: String, account: String) -> Self {
+        ContractEvent::RoleGranted { role, account }
+    }
+
+    pub fn make_role_revoked(role: String, account: String) -> Self {
+        ContractEvent::RoleRevoked { role, account }
+    }
+
+    pub fn make_pause_status_changed(actions: Vec<String>, paused: bool) -> Self {
+        ContractEvent::PauseStatusChanged { actions, paused }
+    }
+
+    pub fn make_emergency_shutdown(reward_policy: String, activated_at: Uint64) -> Self {
+        ContractEvent::EmergencyShutdown { reward_policy, activated_at }
+    }
+
+    pub fn make_forfeited_reward_swept(recipient: String, asset: String, amount: Uint128) -> Self {
+        ContractEvent::ForfeitedRewardSwept { recipient, asset, amount }
+    }
+
+    pub fn make_migrate(from_version: String, to_version: String) -> Self {
+        ContractEvent::Migrate { from_version, to_version }
+    }
+
+    pub fn make_config_updated(
+        min_lock_period: Uint64,
+        max_lock_period: Uint64,
+        withdraw_delay: Uint64
+    ) -> Self {
+        ContractEvent::ConfigUpdated {
+            min_lock_period,
+            max_lock_period,
+            withdraw_delay,
+        }
+    }
+
+    pub fn make_early_withdraw_config_updated(
+        penalty_rate: Decimal,
+        penalty_floor: Decimal,
+        penalty_destination: String
+    ) -> Self {
+        ContractEvent::EarlyWithdrawConfigUpdated {
+            penalty_rate,
+            penalty_floor,
+            penalty_destination,
+        }
+    }
+
+    pub fn make_reward_asset_added(asset: String, distribution_period: Uint64) -> Self {
+        ContractEvent::RewardAssetAdded {
+            asset,
+            distribution_period,
+        }
+    }
+
+    pub fn make_reward_asset_income(
+        asset: String,
+        add_amount: Uint128,
+        remaining_amount: Uint128,
+        reward_rate: Uint128
+    ) -> Self {
+        ContractEvent::RewardAssetIncome {
+            asset,
+            add_amount,
+            remaining_amount,
+            reward_rate,
+        }
+    }
+
+    pub fn make_reward_asset_claim(
+        account: String,
+        recipient: String,
+        asset: String,
+        claim_amount: Uint128
+    ) -> Self {
+        ContractEvent::RewardAssetClaim {
+            account,
+            recipient,
+            asset,
+            claim_amount,
+        }
+    }
+    pub fn make_balance_hook_added(address: String, on_error: String) -> Self {
+        ContractEvent::BalanceHookAdded {
+            address,
+            on_error,
+        }
+    }
+
+    pub fn make_balance_hook_removed(address: String) -> Self {
+        ContractEvent::BalanceHookRemoved {
+            address,
+        }
+    }
+
+    pub fn make_balance_hook_failed(error: String) -> Self {
+        ContractEvent::BalanceHookFailed {
+            error,
+        }
+    }
+
+    pub fn make_epoch_income(epoch_start: Uint64, add_amount: Uint128) -> Self {
+        ContractEvent::EpochIncome {
+            epoch_start,
+            add_amount,
+        }
+    }
+
+    pub fn make_epoch_fee_claim(account: String, claim_amount: Uint128, next_epoch: Uint64) -> Self {
+        ContractEvent::EpochFeeClaim {
+            account,
+            claim_amount,
+            next_epoch,
+        }
+    }
+
+    pub fn make_gauge_added(gauge: String) -> Self {
+        ContractEvent::GaugeAdded {
+            gauge,
+        }
+    }
+
+    pub fn make_gauge_vote(account: String, gauge: String, weight: u16, power: Uint128) -> Self {
+        ContractEvent::GaugeVote {
+            account,
+            gauge,
+            weight,
+            power,
+        }
+    }
+
+    pub fn make_transfer_nft(
+        sender: String,
+        owner: String,
+        recipient: String,
+        position: u64
+    ) -> Self {
+        ContractEvent::TransferNft {
+            sender,
+            owner,
+            recipient,
+            position,
+        }
+    }
+
+    pub fn make_nft_approval(
+        owner: String,
+        spender: String,
+        position: u64,
+        approved: bool
+    ) -> Self {
+        ContractEvent::NftApproval {
+            owner,
+            spender,
+            position,
+            approved,
+        }
+    }
+
+    pub fn make_nft_operator(owner: String, operator: String, approved: bool) -> Self {
+        ContractEvent::NftOperator {
+            owner,
+            operator,
+            approved,
+        }
+    }
+
+    pub fn make_claim_operator(account: String, operator: String, approved: bool) -> Self {
+        ContractEvent::ClaimOperator {
+            account,
+            operator,
+            approved,
+        }
+    }
+
+    pub fn make_kick(
+        account: String,
+        sender: String,
+        old_balance: Uint128,
+        new_balance: Uint128
+    ) -> Self {
+        ContractEvent::Kick {
+            account,
+            sender,
+            old_balance,
+            new_balance,
+        }
+    }
+
+    pub fn make_delegate(delegator: String, delegate: String, power: Uint128) -> Self {
+        ContractEvent::Delegate {
+            delegator,
+            delegate,
+            power,
+        }
+    }
+
+    pub fn make_undelegate(delegator: String, delegate: String, power: Uint128) -> Self {
+        ContractEvent::Undelegate {
+            delegator,
+            delegate,
+            power,
+        }
+    }
+
     pub fn to_cosmos_event(&self) -> Event {
         match self {
-            ContractEvent::Lock { account, locked_balance, ve_balance, locked_until } =>
+            ContractEvent::Lock { account, position, locked_balance, ve_balance, locked_until } =>
                 Event::new("lock").add_attributes(
                     vec![
                         attr("account", account.as_str()),
+                        attr("position", &position.to_string()),
                         attr("locked_balance", &locked_balance.to_string()),
                         attr("ve_balance", &ve_balance.to_string()),
                         attr("locked_until", &locked_until.to_string())
                     ]
                 ),
-            ContractEvent::WithdrawRequest { account, amount, withdraw_at } =>
+            ContractEvent::IncreaseAmount {
+                account,
+                position,
+                amount,
+                locked_balance,
+                ve_balance,
+            } =>
+                Event::new("increase_amount").add_attributes(
+                    vec![
+                        attr("account", account.as_str()),
+                        attr("position", &position.to_string()),
+                        attr("amount", &amount.to_string()),
+                        attr("locked_balance", &locked_balance.to_string()),
+                        attr("ve_balance", &ve_balance.to_string())
+                    ]
+                ),
+            ContractEvent::ExtendLock { account, position, locked_until, ve_balance } =>
+                Event::new("extend_lock").add_attributes(
+                    vec![
+                        attr("account", account.as_str()),
+                        attr("position", &position.to_string()),
+                        attr("locked_until", &locked_until.to_string()),
+                        attr("ve_balance", &ve_balance.to_string())
+                    ]
+                ),
+            ContractEvent::LockFor { locker, beneficiary, position, amount } =>
+                Event::new("lock_for").add_attributes(
+                    vec![
+                        attr("locker", locker.as_str()),
+                        attr("beneficiary", beneficiary.as_str()),
+                        attr("position", &position.to_string()),
+                        attr("amount", &amount.to_string())
+                    ]
+                ),
+            ContractEvent::LockForAllowance { beneficiary, locker, max_locked_until, positions } =>
+                Event::new("lock_for_allowance").add_attributes(
+                    vec![
+                        attr("beneficiary", beneficiary.as_str()),
+                        attr("locker", locker.as_str()),
+                        attr("max_locked_until", &max_locked_until.to_string()),
+                        attr("positions", &positions.to_string())
+                    ]
+                ),
+            ContractEvent::WithdrawRequest { account, position, amount, withdraw_at } =>
                 Event::new("withdraw_request").add_attributes(
                     vec![
                         attr("account", account.as_str()),
+                        attr("position", &position.to_string()),
                         attr("amount", &amount.to_string()),
                         attr("withdraw_at", &withdraw_at.to_string())
                     ]
                 ),
-            ContractEvent::Withdraw { account, amount } =>
+            ContractEvent::Withdraw { account, position, amount } =>
                 Event::new("withdraw").add_attributes(
-                    vec![attr("account", account.as_str()), attr("amount", &amount.to_string())]
+                    vec![
+                        attr("account", account.as_str()),
+                        attr("position", &position.to_string()),
+                        attr("amount", &amount.to_string())
+                    ]
                 ),
-            ContractEvent::Claim { account, claim_amount, ve_balance } =>
+            ContractEvent::WithdrawRequestCancelled { account, position, amount } =>
+                Event::new("withdraw_request_cancelled").add_attributes(
+                    vec![
+                        attr("account", account.as_str()),
+                        attr("position", &position.to_string()),
+                        attr("amount", &amount.to_string())
+                    ]
+                ),
+            ContractEvent::EarlyWithdraw {
+                account,
+                position,
+                amount,
+                penalty,
+                penalty_destination,
+            } =>
+                Event::new("early_withdraw").add_attributes(
+                    vec![
+                        attr("account", account.as_str()),
+                        attr("position", &position.to_string()),
+                        attr("amount", &amount.to_string()),
+                        attr("penalty", &penalty.to_string()),
+                        attr("penalty_destination", penalty_destination.as_str())
+                    ]
+                ),
+            ContractEvent::Merge { account, from, into, locked_balance, locked_until } =>
+                Event::new("merge").add_attributes(
+                    vec![
+                        attr("account", account.as_str()),
+                        attr("from", &from.to_string()),
+                        attr("into", &into.to_string()),
+                        attr("locked_balance", &locked_balance.to_string()),
+                        attr("locked_until", &locked_until.to_string())
+                    ]
+                ),
+            ContractEvent::Split { account, position, new_positions, locked_balance } => {
+                let new_positions = new_positions
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<String>>()
+                    .join(",");
+                Event::new("split").add_attributes(
+                    vec![
+                        attr("account", account.as_str()),
+                        attr("position", &position.to_string()),
+                        attr("new_positions", &new_positions),
+                        attr("locked_balance", &locked_balance.to_string())
+                    ]
+                )
+            }
+            ContractEvent::Compound { account, position, amount, locked_balance, ve_balance } =>
+                Event::new("compound").add_attributes(
+                    vec![
+                        attr("account", account.as_str()),
+                        attr("position", &position.to_string()),
+                        attr("amount", &amount.to_string()),
+                        attr("locked_balance", &locked_balance.to_string()),
+                        attr("ve_balance", &ve_balance.to_string())
+                    ]
+                ),
+            ContractEvent::AutoCompound { account, enabled } =>
+                Event::new("auto_compound").add_attributes(
+                    vec![attr("account", account.as_str()), attr("enabled", &enabled.to_string())]
+                ),
+            ContractEvent::Claim { account, recipient, claim_amount, ve_balance } =>
                 Event::new("claim").add_attributes(
                     vec![
                         attr("account", account.as_str()),
+                        attr("recipient", recipient.as_str()),
                         attr("claim_amount", &claim_amount.to_string()),
                         attr("ve_balance", &ve_balance.to_string())
                     ]
@@ -152,6 +826,197 @@ impl ContractEvent {
                 Event::new("mint").add_attributes(
                     vec![attr("amount", &amount.to_string()), attr("to", to.as_str())]
                 ),
+            ContractEvent::OwnershipProposed { owner, pending_owner } =>
+                Event::new("ownership_proposed").add_attributes(
+                    vec![attr("owner", owner.as_str()), attr("pending_owner", pending_owner.as_str())]
+                ),
+            ContractEvent::OwnershipTransferred { previous_owner, new_owner } =>
+                Event::new("ownership_transferred").add_attributes(
+                    vec![
+                        attr("previous_owner", previous_owner.as_str()),
+                        attr("new_owner", new_owner.as_str())
+                    ]
+                ),
+            ContractEvent::OwnershipRenounced { previous_owner } =>
+                Event::new("ownership_renounced").add_attributes(
+                    vec![attr("previous_owner", previous_owner.as_str())]
+                ),
+            ContractEvent::RoleGranted { role, account } =>
+                Event::new("role_granted").add_attributes(
+                    vec![attr("role", role.as_str()), attr("account", account.as_str())]
+                ),
+            ContractEvent::RoleRevoked { role, account } =>
+                Event::new("role_revoked").add_attributes(
+                    vec![attr("role", role.as_str()), attr("account", account.as_str())]
+                ),
+            ContractEvent::PauseStatusChanged { actions, paused } =>
+                Event::new("pause_status_changed").add_attributes(
+                    vec![attr("actions", &actions.join(",")), attr("paused", &paused.to_string())]
+                ),
+            ContractEvent::EmergencyShutdown { reward_policy, activated_at } =>
+                Event::new("emergency_shutdown").add_attributes(
+                    vec![
+                        attr("reward_policy", reward_policy.as_str()),
+                        attr("activated_at", &activated_at.to_string())
+                    ]
+                ),
+            ContractEvent::ForfeitedRewardSwept { recipient, asset, amount } =>
+                Event::new("forfeited_reward_swept").add_attributes(
+                    vec![
+                        attr("recipient", recipient.as_str()),
+                        attr("asset", asset.as_str()),
+                        attr("amount", &amount.to_string())
+                    ]
+                ),
+            ContractEvent::Migrate { from_version, to_version } =>
+                Event::new("migrate").add_attributes(
+                    vec![
+                        attr("from_version", from_version.as_str()),
+                        attr("to_version", to_version.as_str())
+                    ]
+                ),
+            ContractEvent::ConfigUpdated { min_lock_period, max_lock_period, withdraw_delay } =>
+                Event::new("config_updated").add_attributes(
+                    vec![
+                        attr("min_lock_period", &min_lock_period.to_string()),
+                        attr("max_lock_period", &max_lock_period.to_string()),
+                        attr("withdraw_delay", &withdraw_delay.to_string())
+                    ]
+                ),
+            ContractEvent::EarlyWithdrawConfigUpdated {
+                penalty_rate,
+                penalty_floor,
+                penalty_destination,
+            } =>
+                Event::new("early_withdraw_config_updated").add_attributes(
+                    vec![
+                        attr("penalty_rate", &penalty_rate.to_string()),
+                        attr("penalty_floor", &penalty_floor.to_string()),
+                        attr("penalty_destination", penalty_destination.as_str())
+                    ]
+                ),
+            ContractEvent::RewardAssetAdded { asset, distribution_period } =>
+                Event::new("reward_asset_added").add_attributes(
+                    vec![
+                        attr("asset", asset.as_str()),
+                        attr("distribution_period", &distribution_period.to_string())
+                    ]
+                ),
+            ContractEvent::RewardAssetIncome { asset, add_amount, remaining_amount, reward_rate } =>
+                Event::new("reward_asset_income").add_attributes(
+                    vec![
+                        attr("asset", asset.as_str()),
+                        attr("add_amount", &add_amount.to_string()),
+                        attr("remaining_amount", &remaining_amount.to_string()),
+                        attr("reward_rate", &reward_rate.to_string())
+                    ]
+                ),
+            ContractEvent::RewardAssetClaim { account, recipient, asset, claim_amount } =>
+                Event::new("reward_asset_claim").add_attributes(
+                    vec![
+                        attr("account", account.as_str()),
+                        attr("recipient", recipient.as_str()),
+                        attr("asset", asset.as_str()),
+                        attr("claim_amount", &claim_amount.to_string())
+                    ]
+                ),
+            ContractEvent::BalanceHookAdded { address, on_error } =>
+                Event::new("balance_hook_added").add_attributes(
+                    vec![attr("address", address.as_str()), attr("on_error", on_error.as_str())]
+                ),
+            ContractEvent::BalanceHookRemoved { address } =>
+                Event::new("balance_hook_removed").add_attributes(
+                    vec![attr("address", address.as_str())]
+                ),
+            ContractEvent::BalanceHookFailed { error } =>
+                Event::new("balance_hook_failed").add_attributes(
+                    vec![attr("error", error.as_str())]
+                ),
+            ContractEvent::EpochIncome { epoch_start, add_amount } =>
+                Event::new("epoch_income").add_attributes(
+                    vec![
+                        attr("epoch_start", &epoch_start.to_string()),
+                        attr("add_amount", &add_amount.to_string())
+                    ]
+                ),
+            ContractEvent::EpochFeeClaim { account, claim_amount, next_epoch } =>
+                Event::new("epoch_fee_claim").add_attributes(
+                    vec![
+                        attr("account", account.as_str()),
+                        attr("claim_amount", &claim_amount.to_string()),
+                        attr("next_epoch", &next_epoch.to_string())
+                    ]
+                ),
+            ContractEvent::GaugeAdded { gauge } =>
+                Event::new("gauge_added").add_attributes(vec![attr("gauge", gauge.as_str())]),
+            ContractEvent::GaugeVote { account, gauge, weight, power } =>
+                Event::new("gauge_vote").add_attributes(
+                    vec![
+                        attr("account", account.as_str()),
+                        attr("gauge", gauge.as_str()),
+                        attr("weight", &weight.to_string()),
+                        attr("power", &power.to_string())
+                    ]
+                ),
+            ContractEvent::TransferNft { sender, owner, recipient, position } =>
+                Event::new("transfer_nft").add_attributes(
+                    vec![
+                        attr("sender", sender.as_str()),
+                        attr("owner", owner.as_str()),
+                        attr("recipient", recipient.as_str()),
+                        attr("position", &position.to_string())
+                    ]
+                ),
+            ContractEvent::NftApproval { owner, spender, position, approved } =>
+                Event::new("nft_approval").add_attributes(
+                    vec![
+                        attr("owner", owner.as_str()),
+                        attr("spender", spender.as_str()),
+                        attr("position", &position.to_string()),
+                        attr("approved", &approved.to_string())
+                    ]
+                ),
+            ContractEvent::NftOperator { owner, operator, approved } =>
+                Event::new("nft_operator").add_attributes(
+                    vec![
+                        attr("owner", owner.as_str()),
+                        attr("operator", operator.as_str()),
+                        attr("approved", &approved.to_string())
+                    ]
+                ),
+            ContractEvent::ClaimOperator { account, operator, approved } =>
+                Event::new("claim_operator").add_attributes(
+                    vec![
+                        attr("account", account.as_str()),
+                        attr("operator", operator.as_str()),
+                        attr("approved", &approved.to_string())
+                    ]
+                ),
+            ContractEvent::Kick { account, sender, old_balance, new_balance } =>
+                Event::new("kick").add_attributes(
+                    vec![
+                        attr("account", account.as_str()),
+                        attr("sender", sender.as_str()),
+                        attr("old_balance", &old_balance.to_string()),
+                        attr("new_balance", &new_balance.to_string())
+                    ]
+                ),
+            ContractEvent::Delegate { delegator, delegate, power } =>
+                Event::new("delegate").add_attributes(
+                    vec![
+                        attr("delegator", delegator.as_str()),
+                        attr("delegate", delegate.as_str()),
+                        attr("power", &power.to_string())
+                    ]
+                ),
+            ContractEvent::Undelegate { delegator, delegate, power } =>
+                Event::new("undelegate").add_attributes(
+                    vec![
+                        attr("delegator", delegator.as_str()),
+                        attr("delegate", delegate.as_str()),
+                        attr("power", &power.to_string())
+                    ]
+                ),
         }
     }
 }
@@ -0,0 +1,324 @@
+use cosmwasm_std::{ Addr, Order, StdResult, Storage, Uint128, Uint64 };
+use cw_storage_plus::Bound;
+
+use crate::checkpoints;
+use crate::consts::FEE_EPOCH;
+use crate::state::{
+    EPOCH_FEES,
+    FORWARDED_FEE_EPOCH,
+    UNCLAIMED_EPOCH_FEES,
+    USER_FEE_CURSORS,
+    USER_POINTS,
+};
+
+pub fn epoch_start(ts: Uint64) -> Uint64 {
+    Uint64::from((ts.u64() / FEE_EPOCH) * FEE_EPOCH)
+}
+
+/// Weekly fee distribution, an alternative to streaming income over blocks.
+///
+/// Income is bucketed into the epoch it arrives in. Once the epoch is over, each
+/// account gets fees * balance_of(epoch start) / total_supply_at(epoch start),
+/// using the decaying voting power of checkpoints.rs.
+///
+/// Returns the epoch the income was added to
+pub fn add_fees(
+    storage: &mut dyn Storage,
+    current_ts: Uint64,
+    amount: Uint128
+) -> StdResult<Uint64> {
+    roll_forwarded_fees(storage, current_ts)?;
+    let epoch = add_epoch_fees(storage, current_ts, amount)?;
+
+    let unclaimed = UNCLAIMED_EPOCH_FEES.may_load(storage)?.unwrap_or_default();
+    UNCLAIMED_EPOCH_FEES.save(storage, &(unclaimed + amount))?;
+
+    Ok(epoch)
+}
+
+fn add_epoch_fees(
+    storage: &mut dyn Storage,
+    current_ts: Uint64,
+    amount: Uint128
+) -> StdResult<Uint64> {
+    let mut epoch = epoch_start(current_ts);
+    // Nobody could claim it, the next epoch gets it
+    if checkpoints::total_supply_at(storage, epoch)?.is_zero() {
+        epoch += Uint64::from(FEE_EPOCH);
+        FORWARDED_FEE_EPOCH.save(storage, &epoch.u64())?;
+    }
+
+    let fees = EPOCH_FEES.may_load(storage, epoch.u64())?.unwrap_or_default();
+    EPOCH_FEES.save(storage, epoch.u64(), &(fees + amount))?;
+
+    Ok(epoch)
+}
+
+/// Fees given to the next epoch stay there only if it started with supply, otherwise they
+/// move on again, until an epoch with supply gets them. Done before fees are added or claimed
+fn roll_forwarded_fees(storage: &mut dyn Storage, current_ts: Uint64) -> StdResult<()> {
+    let epoch = match FORWARDED_FEE_EPOCH.may_load(storage)? {
+        Some(epoch) if epoch <= epoch_start(current_ts).u64() => epoch,
+        _ => {
+            return Ok(());
+        }
+    };
+    FORWARDED_FEE_EPOCH.remove(storage);
+    if !checkpoints::total_supply_at(storage, Uint64::from(epoch))?.is_zero() {
+        return Ok(());
+    }
+
+    let fees = EPOCH_FEES.may_load(storage, epoch)?.unwrap_or_default();
+    EPOCH_FEES.remove(storage, epoch);
+    add_epoch_fees(storage, current_ts, fees)?;
+
+    Ok(())
+}
+
+/// Fees of the finished epochs from the account's cursor on, going over at most
+/// limit epochs with income. Returns the amount and the cursor to resume from
+pub fn pending_fees(
+    storage: &dyn Storage,
+    account: &Addr,
+    current_ts: Uint64,
+    limit: usize
+) -> StdResult<(Uint128, Uint64)> {
+    let current_epoch = epoch_start(current_ts);
+    let cursor = match USER_FEE_CURSORS.may_load(storage, account)? {
+        Some(cursor) => Uint64::from(cursor),
+        // Nothing to claim before the first lock
+        None =>
+            match first_user_point_ts(storage, account)? {
+                Some(ts) => epoch_start(ts),
+                None => current_epoch,
+            }
+    };
+    if limit == 0 || cursor >= current_epoch {
+        return Ok((Uint128::zero(), cursor));
+    }
+
+    let epochs = EPOCH_FEES.range(
+        storage,
+        Some(Bound::inclusive(cursor.u64())),
+        Some(Bound::exclusive(current_epoch.u64())),
+        Order::Ascending
+    )
+        .take(limit)
+        .collect::<StdResult<Vec<(u64, Uint128)>>>()?;
+
+    let mut amount = Uint128::zero();
+    for (epoch, fees) in &epochs {
+        let epoch = Uint64::from(*epoch);
+        let total_supply = checkpoints::total_supply_at(storage, epoch)?;
+        if !total_supply.is_zero() {
+            let balance = checkpoints::balance_of(storage, account, epoch)?;
+            amount += fees.multiply_ratio(balance, total_supply);
+        }
+    }
+
+    let next_cursor = match epochs.last() {
+        Some((epoch, _)) if epochs.len() == limit => Uint64::from(*epoch + FEE_EPOCH),
+        _ => current_epoch,
+    };
+    Ok((amount, next_cursor))
+}
+
+/// pending_fees, moving the cursor and releasing the reserved fees
+pub fn claim_fees(
+    storage: &mut dyn Storage,
+    account: &Addr,
+    current_ts: Uint64,
+    limit: usize
+) -> StdResult<(Uint128, Uint64)> {
+    roll_forwarded_fees(storage, current_ts)?;
+    let (amount, next_cursor) = pending_fees(storage, account, current_ts, limit)?;
+    USER_FEE_CURSORS.save(storage, account, &next_cursor.u64())?;
+
+    // Shares round down, so this only saturates on rounding dust
+    let unclaimed = UNCLAIMED_EPOCH_FEES.may_load(storage)?.unwrap_or_default();
+    UNCLAIMED_EPOCH_FEES.save(storage, &unclaimed.saturating_sub(amount))?;
+
+    Ok((amount, next_cursor))
+}
+
+fn first_user_point_ts(storage: &dyn Storage, account: &Addr) -> StdResult<Option<Uint64>> {
+    USER_POINTS.prefix(account)
+        .keys(storage, None, None, Order::Ascending)
+        .next()
+        .transpose()
+        .map(|ts| ts.map(Uint64::from))
+}
+
+#[cfg(test)]
+mod fee_distributor_tests {
+    use cosmwasm_std::testing::mock_dependencies;
+
+    use crate::state::Point;
+
+    use super::*;
+
+    const MAX_LOCK: u64 = FEE_EPOCH * 10;
+
+    #[test]
+    fn test_fees_split_by_balance_at_epoch_start() {
+        let mut deps = mock_dependencies();
+        let alice = Addr::unchecked("alice");
+        let bob = Addr::unchecked("bob");
+        let max_lock = Uint64::from(MAX_LOCK);
+
+        let epoch = Uint64::from(FEE_EPOCH * 100);
+        let week = Uint64::from(FEE_EPOCH);
+
+        // 1. No supply at the epoch start, income moves to the next epoch
+        checkpoints::checkpoint(
+            deps.as_mut().storage,
+            &alice,
+            &Point::default(),
+            Uint128::from(1_000_000_000_000u64),
+            epoch + max_lock,
+            epoch + Uint64::from(1u8),
+            max_lock
+        ).unwrap();
+        let fees_epoch = add_fees(
+            deps.as_mut().storage,
+            epoch + Uint64::from(10u8),
+            Uint128::from(300u16)
+        ).unwrap();
+        assert_eq!(fees_epoch, epoch + week);
+
+        // bob locks twice as much for a shorter time during the epoch
+        checkpoints::checkpoint(
+            deps.as_mut().storage,
+            &bob,
+            &Point::default(),
+            Uint128::from(2_000_000_000_000u64),
+            epoch + week + max_lock / Uint64::from(2u8),
+            epoch + Uint64::from(20u8),
+            max_lock
+        ).unwrap();
+
+        // 2. Nothing claimable before the epoch is over
+        let now = fees_epoch + Uint64::from(10u8);
+        assert_eq!(pending_fees(deps.as_ref().storage, &alice, now, 10).unwrap(), (
+            Uint128::zero(),
+            fees_epoch,
+        ));
+
+        let now = fees_epoch + week;
+        let alice_power = checkpoints::balance_of(deps.as_ref().storage, &alice, fees_epoch);
+        let bob_power = checkpoints::balance_of(deps.as_ref().storage, &bob, fees_epoch);
+        let total_supply = checkpoints::total_supply_at(deps.as_ref().storage, fees_epoch).unwrap();
+        // balances are rounded down one by one, the total once
+        let summed_power = alice_power.unwrap() + bob_power.unwrap();
+        assert!(summed_power <= total_supply);
+        assert!(summed_power + Uint128::one() >= total_supply);
+
+        let (alice_fees, cursor) = claim_fees(deps.as_mut().storage, &alice, now, 10).unwrap();
+        let (bob_fees, _) = claim_fees(deps.as_mut().storage, &bob, now, 10).unwrap();
+        assert_eq!(cursor, now);
+        assert!(alice_fees + bob_fees <= Uint128::from(300u16));
+        assert!(alice_fees + bob_fees >= Uint128::from(299u16));
+        assert!(bob_fees > alice_fees);
+
+        // 3. Claimed epochs are not paid twice
+        assert_eq!(pending_fees(deps.as_ref().storage, &alice, now, 10).unwrap(), (
+            Uint128::zero(),
+            now,
+        ));
+        assert!(UNCLAIMED_EPOCH_FEES.load(deps.as_ref().storage).unwrap() <= Uint128::one());
+    }
+
+    #[test]
+    fn test_claim_resumes_from_cursor() {
+        let mut deps = mock_dependencies();
+        let alice = Addr::unchecked("alice");
+        let week = Uint64::from(FEE_EPOCH);
+        let epoch = Uint64::from(FEE_EPOCH * 100);
+
+        checkpoints::checkpoint(
+            deps.as_mut().storage,
+            &alice,
+            &Point::default(),
+            Uint128::from(1_000_000_000_000u64),
+            epoch + Uint64::from(MAX_LOCK),
+            epoch,
+            Uint64::from(MAX_LOCK)
+        ).unwrap();
+
+        // income in 3 epochs, alice holds all the supply
+        for i in 0..3u64 {
+            let ts = epoch + week * Uint64::from(i) + Uint64::from(10u8);
+            add_fees(deps.as_mut().storage, ts, Uint128::from(100u8)).unwrap();
+        }
+
+        let now = epoch + week * Uint64::from(5u8);
+        assert_eq!(claim_fees(deps.as_mut().storage, &alice, now, 2).unwrap(), (
+            Uint128::from(200u8),
+            epoch + week * Uint64::from(2u8),
+        ));
+        assert_eq!(claim_fees(deps.as_mut().storage, &alice, now, 2).unwrap(), (
+            Uint128::from(100u8),
+            epoch_start(now),
+        ));
+        assert_eq!(claim_fees(deps.as_mut().storage, &alice, now, 2).unwrap(), (
+            Uint128::zero(),
+            epoch_start(now),
+        ));
+    }
+
+    #[test]
+    fn test_fees_roll_over_empty_epochs() {
+        let mut deps = mock_dependencies();
+        let alice = Addr::unchecked("alice");
+        let week = Uint64::from(FEE_EPOCH);
+        let epoch = Uint64::from(FEE_EPOCH * 100);
+
+        // 1. Two epochs in a row start without supply
+        let fees_epoch = add_fees(
+            deps.as_mut().storage,
+            epoch + Uint64::from(10u8),
+            Uint128::from(300u16)
+        ).unwrap();
+        assert_eq!(fees_epoch, epoch + week);
+
+        let fees_epoch = add_fees(
+            deps.as_mut().storage,
+            epoch + week + Uint64::from(10u8),
+            Uint128::from(100u8)
+        ).unwrap();
+        assert_eq!(fees_epoch, epoch + week * Uint64::from(2u8));
+        assert!(!EPOCH_FEES.has(deps.as_ref().storage, (epoch + week).u64()));
+        assert_eq!(
+            EPOCH_FEES.load(deps.as_ref().storage, fees_epoch.u64()).unwrap(),
+            Uint128::from(400u16)
+        );
+
+        // alice locks only after the second one started
+        checkpoints::checkpoint(
+            deps.as_mut().storage,
+            &alice,
+            &Point::default(),
+            Uint128::from(1_000_000_000_000u64),
+            epoch + Uint64::from(MAX_LOCK),
+            fees_epoch + Uint64::from(10u8),
+            Uint64::from(MAX_LOCK)
+        ).unwrap();
+
+        // 2. Claiming moves the fees to the first epoch with supply
+        let now = fees_epoch + week + Uint64::from(10u8);
+        assert_eq!(claim_fees(deps.as_mut().storage, &alice, now, 10).unwrap(), (
+            Uint128::zero(),
+            epoch_start(now),
+        ));
+        assert!(!EPOCH_FEES.has(deps.as_ref().storage, fees_epoch.u64()));
+        assert!(FORWARDED_FEE_EPOCH.may_load(deps.as_ref().storage).unwrap().is_none());
+
+        // 3. and once that epoch is over nothing is left reserved
+        let now = epoch_start(now) + week;
+        assert_eq!(claim_fees(deps.as_mut().storage, &alice, now, 10).unwrap(), (
+            Uint128::from(400u16),
+            now,
+        ));
+        assert!(UNCLAIMED_EPOCH_FEES.load(deps.as_ref().storage).unwrap().is_zero());
+    }
+}
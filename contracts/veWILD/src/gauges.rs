@@ -0,0 +1,397 @@
+use cosmwasm_std::{ Addr, Decimal, Order, StdResult, Storage, Uint128, Uint256, Uint64 };
+use cw_storage_plus::{ Bound, Map, PrimaryKey };
+
+use crate::checkpoints;
+use crate::consts::{ GAUGE_EPOCH, MAX_GAUGE_VOTE_WEIGHT };
+use crate::state::{
+    Point,
+    GAUGE_POINTS,
+    GAUGE_SLOPE_CHANGES,
+    TOTAL_GAUGE_POINTS,
+    TOTAL_GAUGE_SLOPE_CHANGES,
+    USER_GAUGE_VOTES,
+    USER_GAUGE_VOTE_POINTS,
+    USER_GAUGE_VOTE_SLOPE_CHANGES,
+    USER_SLOPE_CHANGES,
+};
+
+pub fn epoch_start(ts: Uint64) -> Uint64 {
+    Uint64::from((ts.u64() / GAUGE_EPOCH) * GAUGE_EPOCH)
+}
+
+pub fn next_epoch_start(ts: Uint64) -> Uint64 {
+    epoch_start(ts) + Uint64::from(GAUGE_EPOCH)
+}
+
+/// Gauge controller: ve holders split their ve balance across gauges.
+///
+/// A vote is a share in basis points of the voter's decaying balance from checkpoints.rs.
+/// It copies the voter's slope changes scheduled after the next epoch start, scaled by the
+/// share, so the vote decays with the voter's positions and is gone once they unlock, with
+/// nobody touching them. Gauge and total weights are points advanced over the slope changes
+/// of their votes and read at epoch starts. Votes take effect at the next epoch start, so
+/// the relative weights of an epoch are final once it starts.
+///
+/// Replaces the account's vote for the gauge, a zero weight removes it.
+/// Returns the power of the new vote at the next epoch start
+pub fn set_vote(
+    storage: &mut dyn Storage,
+    account: &Addr,
+    gauge: &Addr,
+    weight: u16,
+    current_ts: Uint64
+) -> StdResult<Uint128> {
+    let next_epoch = next_epoch_start(current_ts);
+
+    let mut gauge_point = gauge_point_at(storage, gauge, next_epoch)?;
+    let mut total_point = total_point_at(storage, next_epoch)?;
+
+    if let Some(old_point) = USER_GAUGE_VOTE_POINTS.may_load(storage, (account, gauge))? {
+        let old_point = advance_vote_point(storage, account, gauge, old_point, next_epoch)?;
+        for point in [&mut gauge_point, &mut total_point] {
+            point.bias = point.bias.saturating_sub(old_point.bias);
+            point.slope = point.slope.saturating_sub(old_point.slope);
+        }
+
+        // Slope changes up to the next epoch are history of the gauge, the later ones go
+        let old_slope_changes = USER_GAUGE_VOTE_SLOPE_CHANGES.prefix((account, gauge))
+            .range(storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<(u64, Uint256)>>>()?;
+        for (change_ts, slope_change) in old_slope_changes {
+            if change_ts > next_epoch.u64() {
+                let key = (gauge, change_ts);
+                update_slope_change(storage, &GAUGE_SLOPE_CHANGES, key, |slope| {
+                    slope.saturating_sub(slope_change)
+                })?;
+                update_slope_change(storage, &TOTAL_GAUGE_SLOPE_CHANGES, change_ts, |slope| {
+                    slope.saturating_sub(slope_change)
+                })?;
+            }
+            USER_GAUGE_VOTE_SLOPE_CHANGES.remove(storage, (account, gauge, change_ts));
+        }
+    }
+
+    let mut new_point = Point { ts: next_epoch, ..Point::default() };
+    if weight > 0 {
+        let user_slope_changes = USER_SLOPE_CHANGES.prefix(account)
+            .range(storage, Some(Bound::exclusive(next_epoch.u64())), None, Order::Ascending)
+            .collect::<StdResult<Vec<(u64, Uint256)>>>()?;
+        for (change_ts, user_slope_change) in user_slope_changes {
+            let slope_change = user_slope_change.multiply_ratio(weight, MAX_GAUGE_VOTE_WEIGHT);
+            if slope_change.is_zero() {
+                continue;
+            }
+            // Bias is exactly what the slope changes take away, so it ends at zero
+            new_point.bias += slope_change * Uint256::from(change_ts - next_epoch.u64());
+            new_point.slope += slope_change;
+
+            USER_GAUGE_VOTE_SLOPE_CHANGES.save(
+                storage,
+                (account, gauge, change_ts),
+                &slope_change
+            )?;
+            update_slope_change(storage, &GAUGE_SLOPE_CHANGES, (gauge, change_ts), |slope| {
+                slope + slope_change
+            })?;
+            update_slope_change(storage, &TOTAL_GAUGE_SLOPE_CHANGES, change_ts, |slope| {
+                slope + slope_change
+            })?;
+        }
+    }
+    for point in [&mut gauge_point, &mut total_point] {
+        point.bias += new_point.bias;
+        point.slope += new_point.slope;
+    }
+    GAUGE_POINTS.save(storage, (gauge, next_epoch.u64()), &gauge_point)?;
+    TOTAL_GAUGE_POINTS.save(storage, next_epoch.u64(), &total_point)?;
+
+    if weight == 0 {
+        USER_GAUGE_VOTES.remove(storage, (account, gauge));
+        USER_GAUGE_VOTE_POINTS.remove(storage, (account, gauge));
+    } else {
+        USER_GAUGE_VOTES.save(storage, (account, gauge), &weight)?;
+        USER_GAUGE_VOTE_POINTS.save(storage, (account, gauge), &new_point)?;
+    }
+    checkpoints::to_balance(new_point.bias)
+}
+
+/// Revotes every gauge vote of the account with its current slope changes
+pub fn update_user_power(
+    storage: &mut dyn Storage,
+    account: &Addr,
+    current_ts: Uint64
+) -> StdResult<()> {
+    let votes = USER_GAUGE_VOTES.prefix(account)
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(Addr, u16)>>>()?;
+
+    for (gauge, weight) in votes {
+        set_vote(storage, account, &gauge, weight, current_ts)?;
+    }
+    Ok(())
+}
+
+/// Power the account's vote adds to the gauge weight at ts,
+/// its power at the epoch it takes effect for earlier ts
+pub fn vote_power_at(
+    storage: &dyn Storage,
+    account: &Addr,
+    gauge: &Addr,
+    ts: Uint64
+) -> StdResult<Uint128> {
+    match USER_GAUGE_VOTE_POINTS.may_load(storage, (account, gauge))? {
+        Some(point) => {
+            let ts = ts.max(point.ts);
+            checkpoints::to_balance(advance_vote_point(storage, account, gauge, point, ts)?.bias)
+        }
+        None => Ok(Uint128::zero()),
+    }
+}
+
+/// Weight of the gauge in the epoch containing ts
+pub fn gauge_weight_at(storage: &dyn Storage, gauge: &Addr, ts: Uint64) -> StdResult<Uint128> {
+    checkpoints::to_balance(gauge_point_at(storage, gauge, epoch_start(ts))?.bias)
+}
+
+/// Sum of all gauge weights in the epoch containing ts
+pub fn total_weight_at(storage: &dyn Storage, ts: Uint64) -> StdResult<Uint128> {
+    checkpoints::to_balance(total_point_at(storage, epoch_start(ts))?.bias)
+}
+
+/// Share of the emissions directed to the gauge in the epoch containing ts
+pub fn relative_weight_at(storage: &dyn Storage, gauge: &Addr, ts: Uint64) -> StdResult<Decimal> {
+    let total_weight = total_weight_at(storage, ts)?;
+    if total_weight.is_zero() {
+        return Ok(Decimal::zero());
+    }
+    Ok(Decimal::from_ratio(gauge_weight_at(storage, gauge, ts)?, total_weight))
+}
+
+/// Last weight point of the gauge before ts, advanced to ts over its slope changes
+fn gauge_point_at(storage: &dyn Storage, gauge: &Addr, ts: Uint64) -> StdResult<Point> {
+    let last_point = GAUGE_POINTS.prefix(gauge)
+        .range(storage, None, Some(Bound::inclusive(ts.u64())), Order::Descending)
+        .next();
+    let point = match last_point {
+        Some(item) => item?.1,
+        None => {
+            return Ok(Point { ts, ..Point::default() });
+        }
+    };
+
+    let slope_changes = GAUGE_SLOPE_CHANGES.prefix(gauge).range(
+        storage,
+        Some(Bound::exclusive(point.ts.u64())),
+        Some(Bound::inclusive(ts.u64())),
+        Order::Ascending
+    );
+    checkpoints::advance_point(point, slope_changes, ts)
+}
+
+/// Last total weight point before ts, advanced to ts over the slope changes of all gauges
+fn total_point_at(storage: &dyn Storage, ts: Uint64) -> StdResult<Point> {
+    let last_point = TOTAL_GAUGE_POINTS.range(
+        storage,
+        None,
+        Some(Bound::inclusive(ts.u64())),
+        Order::Descending
+    ).next();
+    let point = match last_point {
+        Some(item) => item?.1,
+        None => {
+            return Ok(Point { ts, ..Point::default() });
+        }
+    };
+
+    let slope_changes = TOTAL_GAUGE_SLOPE_CHANGES.range(
+        storage,
+        Some(Bound::exclusive(point.ts.u64())),
+        Some(Bound::inclusive(ts.u64())),
+        Order::Ascending
+    );
+    checkpoints::advance_point(point, slope_changes, ts)
+}
+
+fn advance_vote_point(
+    storage: &dyn Storage,
+    account: &Addr,
+    gauge: &Addr,
+    point: Point,
+    ts: Uint64
+) -> StdResult<Point> {
+    let slope_changes = USER_GAUGE_VOTE_SLOPE_CHANGES.prefix((account, gauge)).range(
+        storage,
+        Some(Bound::exclusive(point.ts.u64())),
+        Some(Bound::inclusive(ts.u64())),
+        Order::Ascending
+    );
+    checkpoints::advance_point(point, slope_changes, ts)
+}
+
+fn update_slope_change<'a, K: PrimaryKey<'a> + Copy>(
+    storage: &mut dyn Storage,
+    slope_changes: &Map<'a, K, Uint256>,
+    key: K,
+    action: impl FnOnce(Uint256) -> Uint256
+) -> StdResult<()> {
+    let slope = action(slope_changes.may_load(storage, key)?.unwrap_or_default());
+    if slope.is_zero() {
+        slope_changes.remove(storage, key);
+    } else {
+        slope_changes.save(storage, key, &slope)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod gauges_tests {
+    use cosmwasm_std::testing::mock_dependencies;
+
+    use super::*;
+
+    const EPOCH: u64 = GAUGE_EPOCH * 100;
+    const MAX_LOCK: u64 = GAUGE_EPOCH * 10;
+
+    /// Slope of 1000 per second for the locks below
+    fn lock(
+        storage: &mut dyn Storage,
+        account: &Addr,
+        old_point: &Point,
+        until: u64,
+        at: u64
+    ) -> Point {
+        checkpoints::checkpoint(
+            storage,
+            account,
+            old_point,
+            Uint128::from(MAX_LOCK * 1000),
+            Uint64::from(until),
+            Uint64::from(at),
+            Uint64::from(MAX_LOCK)
+        ).unwrap()
+    }
+
+    #[test]
+    fn test_weights_apply_from_next_epoch() {
+        let mut deps = mock_dependencies();
+        let alice = Addr::unchecked("alice");
+        let gauge_a = Addr::unchecked("gauge_a");
+        let gauge_b = Addr::unchecked("gauge_b");
+
+        let epoch = Uint64::from(EPOCH);
+        let voted_at = epoch + Uint64::from(10u8);
+        let next_epoch = epoch + Uint64::from(GAUGE_EPOCH);
+        assert_eq!(epoch_start(voted_at), epoch);
+        assert_eq!(next_epoch_start(voted_at), next_epoch);
+
+        // alice: locked until 4 epochs later, 60% to gauge_a and 40% to gauge_b
+        let until = EPOCH + GAUGE_EPOCH * 4;
+        let point = lock(deps.as_mut().storage, &alice, &Point::default(), until, EPOCH);
+        for (gauge, weight) in [(&gauge_a, 6000u16), (&gauge_b, 4000u16)] {
+            set_vote(deps.as_mut().storage, &alice, gauge, weight, voted_at).unwrap();
+        }
+
+        let storage = deps.as_ref().storage;
+        let balance = checkpoints::balance_of(storage, &alice, next_epoch).unwrap();
+        assert_eq!(balance, Uint128::from(GAUGE_EPOCH * 3000));
+        assert_eq!(relative_weight_at(storage, &gauge_a, voted_at).unwrap(), Decimal::zero());
+        assert_eq!(
+            gauge_weight_at(storage, &gauge_a, next_epoch).unwrap(),
+            Uint128::from(GAUGE_EPOCH * 1800)
+        );
+        assert_eq!(
+            vote_power_at(storage, &alice, &gauge_a, voted_at).unwrap(),
+            Uint128::from(GAUGE_EPOCH * 1800)
+        );
+        assert_eq!(total_weight_at(storage, next_epoch).unwrap(), balance);
+        assert_eq!(
+            relative_weight_at(storage, &gauge_b, next_epoch).unwrap(),
+            Decimal::percent(40)
+        );
+
+        // Weights decay with the lock while nobody touches it
+        let epoch_after = next_epoch + Uint64::from(GAUGE_EPOCH);
+        assert_eq!(
+            gauge_weight_at(storage, &gauge_a, epoch_after).unwrap(),
+            Uint128::from(GAUGE_EPOCH * 1200)
+        );
+        assert_eq!(
+            total_weight_at(storage, epoch_after).unwrap(),
+            Uint128::from(GAUGE_EPOCH * 2000)
+        );
+
+        // Extending the lock during the next epoch reweights the epoch after it
+        let changed_at = next_epoch + Uint64::from(10u8);
+        let until = EPOCH + GAUGE_EPOCH * 6;
+        lock(deps.as_mut().storage, &alice, &point, until, changed_at.u64());
+        update_user_power(deps.as_mut().storage, &alice, changed_at).unwrap();
+
+        let storage = deps.as_ref().storage;
+        assert_eq!(
+            gauge_weight_at(storage, &gauge_a, changed_at).unwrap(),
+            Uint128::from(GAUGE_EPOCH * 1800)
+        );
+        assert_eq!(
+            gauge_weight_at(storage, &gauge_a, epoch_after).unwrap(),
+            Uint128::from(GAUGE_EPOCH * 2400)
+        );
+        assert_eq!(
+            gauge_weight_at(storage, &gauge_b, epoch_after).unwrap(),
+            Uint128::from(GAUGE_EPOCH * 1600)
+        );
+        assert_eq!(
+            total_weight_at(storage, epoch_after).unwrap(),
+            Uint128::from(GAUGE_EPOCH * 4000)
+        );
+        assert_eq!(
+            total_weight_at(storage, Uint64::from(until)).unwrap(),
+            Uint128::zero()
+        );
+    }
+
+    #[test]
+    fn test_weight_falls_to_zero_at_unlock() {
+        let mut deps = mock_dependencies();
+        let alice = Addr::unchecked("alice");
+        let bob = Addr::unchecked("bob");
+        let gauge_a = Addr::unchecked("gauge_a");
+        let gauge_b = Addr::unchecked("gauge_b");
+        let epoch = |n: u64| Uint64::from(EPOCH + GAUGE_EPOCH * n);
+
+        // alice unlocks in the middle of the third epoch, bob later
+        let alice_until = EPOCH + GAUGE_EPOCH * 2 + GAUGE_EPOCH / 2;
+        lock(deps.as_mut().storage, &alice, &Point::default(), alice_until, EPOCH);
+        lock(deps.as_mut().storage, &bob, &Point::default(), EPOCH + GAUGE_EPOCH * 8, EPOCH);
+        let voted_at = epoch(0) + Uint64::from(10u8);
+        set_vote(deps.as_mut().storage, &alice, &gauge_a, 10000, voted_at).unwrap();
+        set_vote(deps.as_mut().storage, &bob, &gauge_b, 10000, voted_at).unwrap();
+
+        let storage = deps.as_ref().storage;
+        assert_eq!(
+            gauge_weight_at(storage, &gauge_a, epoch(1)).unwrap(),
+            Uint128::from(GAUGE_EPOCH * 1500)
+        );
+        assert_eq!(
+            gauge_weight_at(storage, &gauge_a, epoch(2)).unwrap(),
+            Uint128::from(GAUGE_EPOCH * 500)
+        );
+
+        // Nobody touched alice's lock or vote since
+        assert_eq!(gauge_weight_at(storage, &gauge_a, epoch(3)).unwrap(), Uint128::zero());
+        assert_eq!(vote_power_at(storage, &alice, &gauge_a, epoch(3)).unwrap(), Uint128::zero());
+        assert_eq!(
+            total_weight_at(storage, epoch(3)).unwrap(),
+            gauge_weight_at(storage, &gauge_b, epoch(3)).unwrap()
+        );
+        assert_eq!(relative_weight_at(storage, &gauge_a, epoch(3)).unwrap(), Decimal::zero());
+        assert_eq!(relative_weight_at(storage, &gauge_b, epoch(3)).unwrap(), Decimal::one());
+
+        // Removing the expired vote takes nothing more away
+        let total_weight = total_weight_at(storage, epoch(4)).unwrap();
+        set_vote(deps.as_mut().storage, &alice, &gauge_a, 0, epoch(3)).unwrap();
+        let storage = deps.as_ref().storage;
+        assert_eq!(gauge_weight_at(storage, &gauge_a, epoch(4)).unwrap(), Uint128::zero());
+        assert_eq!(total_weight_at(storage, epoch(4)).unwrap(), total_weight);
+        assert_eq!(total_weight_at(storage, epoch(8)).unwrap(), Uint128::zero());
+    }
+}
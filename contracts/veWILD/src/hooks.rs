@@ -0,0 +1,61 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{ to_binary, Addr, Binary, CosmosMsg, Order, StdResult, Storage, SubMsg, WasmMsg };
+use cosmwasm_std::{ Uint128, Uint64 };
+
+use crate::consts::BALANCE_HOOK_REPLY_ID;
+use crate::state::{ HookErrorPolicy, BALANCE_HOOKS, SHUTDOWN };
+
+/// Sent to every balance hook when an account's ve balance changes,
+/// modelled on cw4 MemberChangedHookMsg
+#[cw_serde]
+pub struct VeBalanceChangedHookMsg {
+    pub account: String,
+    pub old_balance: Uint128,
+    pub new_balance: Uint128,
+    pub locked_until: Uint64,
+}
+
+impl VeBalanceChangedHookMsg {
+    /// serializes the message
+    pub fn into_binary(self) -> StdResult<Binary> {
+        let msg = VeBalanceChangedExecuteMsg::VeBalanceChangedHook(self);
+        to_binary(&msg)
+    }
+
+    /// creates a cosmos_msg sending this struct to the named contract
+    pub fn into_cosmos_msg<T: Into<String>>(self, contract_addr: T) -> StdResult<CosmosMsg> {
+        let msg = self.into_binary()?;
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg,
+            funds: vec![],
+        };
+        Ok(execute.into())
+    }
+}
+
+/// The message subscribers must accept in their ExecuteMsg
+#[cw_serde]
+pub enum VeBalanceChangedExecuteMsg {
+    VeBalanceChangedHook(VeBalanceChangedHookMsg),
+}
+
+/// One submessage per registered hook. Hooks registered with HookErrorPolicy::Catch
+/// report failures to reply instead of reverting the balance change, and so does every
+/// hook after emergency shutdown, so that no hook can keep accounts from withdrawing
+pub fn balance_changed_hook_msgs(
+    storage: &dyn Storage,
+    msg: VeBalanceChangedHookMsg
+) -> StdResult<Vec<SubMsg>> {
+    let shutdown = SHUTDOWN.may_load(storage)?.is_some();
+    BALANCE_HOOKS.range(storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (hook, on_error): (Addr, HookErrorPolicy) = item?;
+            let cosmos_msg = msg.clone().into_cosmos_msg(hook)?;
+            Ok(match on_error {
+                HookErrorPolicy::Revert if !shutdown => SubMsg::new(cosmos_msg),
+                _ => SubMsg::reply_on_error(cosmos_msg, BALANCE_HOOK_REPLY_ID),
+            })
+        })
+        .collect()
+}
@@ -1,24 +1,94 @@
-use cosmwasm_std::{ WasmMsg, Deps, Uint64, Uint128, DepsMut, MessageInfo, Env, Response, Addr };
+use cosmwasm_std::{
+    Api,
+    Empty,
+    CosmosMsg,
+    Order,
+    StdResult,
+    Deps,
+    Uint64,
+    Uint128,
+    DepsMut,
+    MessageInfo,
+    Env,
+    Response,
+    Addr,
+    Event,
+    Storage,
+};
+use cw20::Denom;
 use cw20_base::contract::{ query_balance, execute_mint, execute_burn, query_token_info };
 
 use crate::{
+    hooks::{ self, VeBalanceChangedHookMsg },
+    gauges,
+    delegation,
+    consts::MAX_POSITIONS_PER_ACCOUNT,
     error::ContractError,
-    state::{ TOKEN_STATE, USER_STATE, UserState },
+    state::{
+        TOKEN_STATE,
+        USER_STATE,
+        OWNERSHIP,
+        CONFIG,
+        REWARD_ASSETS,
+        USER_REWARD_SNAPSHOTS,
+        BALANCE_SNAPSHOTS,
+        TOTAL_SUPPLY_SNAPSHOTS,
+        UNCLAIMED_EPOCH_FEES,
+        FORFEITED_REWARDS,
+        LOCK_FOR_ALLOWANCES,
+        POSITIONS,
+        OWNER_POSITIONS,
+        POSITION_COUNT,
+        NUM_POSITIONS,
+        Point,
+        Position,
+        UserState,
+        RewardAssetState,
+        ForfeitedReward,
+        Role,
+    },
     events::ContractEvent,
-    consts::MAX_LOCK_PERIOD,
-    cw20_client::CW20Client,
+    asset_client::AssetClient,
+    checkpoints,
 };
 
 //  Internal functions
 pub mod internal_funcs {
     use super::*;
 
+    /// Owner implicitly holds every role
+    pub fn assert_role(
+        storage: &dyn Storage,
+        sender: &Addr,
+        role: Role
+    ) -> Result<(), ContractError> {
+        if OWNERSHIP.load(storage)?.assert_owner(sender).is_ok() {
+            return Ok(());
+        }
+        if role.is_granted(storage, sender) {
+            return Ok(());
+        }
+        Err(ContractError::Unauthorized {})
+    }
+
+    /// CW20 address must be valid, native denom must be set
+    pub fn validate_asset(api: &dyn Api, asset: Denom) -> Result<Denom, ContractError> {
+        match asset {
+            Denom::Cw20(addr) => Ok(Denom::Cw20(api.addr_validate(addr.as_str())?)),
+            Denom::Native(denom) if !denom.is_empty() => Ok(Denom::Native(denom)),
+            Denom::Native(_) => Err(ContractError::InvalidToken {}),
+        }
+    }
+
     /// unvested_income = reward_per_token * (distribution_period - blocks_elapsed)
     /// reserve_balance MUST BE  >= total_locked + unvested_income
+    /// #
+    /// Every reward asset is checked against its own balance:
+    /// reward_asset_balance MUST BE >= unvested_income of the asset
     pub fn check_reserves(deps: Deps, env: &Env) -> Result<(), ContractError> {
         let token_state = TOKEN_STATE.load(deps.storage)?;
 
-        let reserve_balance = CW20Client::new(
+        let reserve_balance = AssetClient::new(
             &deps.querier,
             token_state.locked_token.clone()
         ).balance(env.contract.address.clone())?;
@@ -32,16 +102,74 @@ pub mod internal_funcs {
             token_state.reward_per_token *
             Uint128::from(token_state.distribution_period - blocks_elapsed);
 
-        if reserve_balance < token_state.total_locked + unvested_income {
+        let unclaimed_fees = UNCLAIMED_EPOCH_FEES.may_load(deps.storage)?.unwrap_or_default();
+
+        if reserve_balance < token_state.total_locked + unvested_income + unclaimed_fees {
             return Err(ContractError::InsufficientReserves {});
         }
+
+        for item in REWARD_ASSETS.range(deps.storage, None, None, Order::Ascending) {
+            let (_, asset_state) = item?;
+            let asset_balance = AssetClient::new(
+                &deps.querier,
+                asset_state.asset.clone()
+            ).balance(env.contract.address.clone())?;
+
+            if asset_balance < asset_state.unvested_income(current_block) {
+                return Err(ContractError::InsufficientReserves {});
+            }
+        }
         Ok(())
     }
 
+    /// Accrues every reward asset and moves the account snapshots forward.
+    /// Returns the pending reward of each asset, which the caller pays out or forfeits
+    pub fn settle_reward_assets(
+        storage: &mut dyn Storage,
+        env: &Env,
+        account: &Addr
+    ) -> Result<Vec<(Denom, Uint128)>, ContractError> {
+        let total_supply = TOKEN_STATE.load(storage)?.total_supply;
+        let user_state = USER_STATE.load(storage, account).unwrap_or_default();
+        let current_block = Uint64::from(env.block.height);
+
+        let reward_assets = REWARD_ASSETS.range(storage, None, None, Order::Ascending).collect::<
+            StdResult<Vec<(String, RewardAssetState)>>
+        >()?;
+
+        let mut pending_rewards = vec![];
+        for (key, mut asset_state) in reward_assets {
+            asset_state.accrue(total_supply, current_block);
+            REWARD_ASSETS.save(storage, &key, &asset_state)?;
+
+            let reward_snapshot = USER_REWARD_SNAPSHOTS.may_load(storage, (account, &key))?;
+            let pending_reward = user_state.pending_asset_reward(
+                asset_state.reward_per_token,
+                reward_snapshot.unwrap_or_default()
+            );
+            USER_REWARD_SNAPSHOTS.save(storage, (account, &key), &asset_state.reward_per_token)?;
+
+            pending_rewards.push((asset_state.asset, pending_reward));
+        }
+
+        Ok(pending_rewards)
+    }
+
     pub fn claim(
-        mut deps: DepsMut,
+        deps: DepsMut,
         env: &Env,
         info: &MessageInfo
+    ) -> Result<Response, ContractError> {
+        claim_to(deps, env, info, &info.sender)
+    }
+
+    /// Settles the rewards of info.sender and refreshes its positions like claim,
+    /// paying the rewards to recipient
+    pub fn claim_to(
+        mut deps: DepsMut,
+        env: &Env,
+        info: &MessageInfo,
+        recipient: &Addr
     ) -> Result<Response, ContractError> {
         let mut token_state = TOKEN_STATE.load(deps.storage)?;
         let current_block = Uint64::from(env.block.height);
@@ -52,28 +180,55 @@ pub mod internal_funcs {
 
         let pending_reward = user_state.pending_reward(token_state.reward_per_token.clone());
 
-        let mut messages: Vec<WasmMsg> = vec![];
+        let mut messages: Vec<CosmosMsg> = vec![];
         if !pending_reward.is_zero() {
-            let msg = CW20Client::new(&deps.querier, token_state.locked_token.clone()).make_transfer_msg(
-                info.sender.to_owned(),
-                pending_reward
-            )?;
-            messages.push(msg.into());
+            let msg = AssetClient::new(
+                &deps.querier,
+                token_state.locked_token.clone()
+            ).make_transfer_msg(recipient.to_owned(), pending_reward)?;
+            messages.push(msg);
         }
 
         user_state.reward_snapshot = token_state.reward_per_token;
 
         let user_address = info.sender.clone().to_string();
 
+        let mut events: Vec<ContractEvent> = vec![];
+        for (asset, asset_reward) in settle_reward_assets(deps.storage, env, &info.sender)? {
+            if asset_reward.is_zero() {
+                continue;
+            }
+            let asset_key = RewardAssetState::key(&asset);
+            messages.push(
+                AssetClient::new(&deps.querier, asset).make_transfer_msg(
+                    recipient.to_owned(),
+                    asset_reward
+                )?
+            );
+            events.push(
+                ContractEvent::make_reward_asset_claim(
+                    user_address.clone(),
+                    recipient.to_string(),
+                    asset_key,
+                    asset_reward
+                )
+            );
+        }
+
         USER_STATE.save(deps.storage, &info.sender.to_owned(), &user_state)?;
         TOKEN_STATE.save(deps.storage, &token_state)?;
 
-        let mut response: Response = update_lock(
+        // ve balances shrink as unlock times get closer
+        for (id, mut position) in account_positions(deps.storage, &info.sender)? {
+            let locked_until = position.locked_until;
+            refresh_position(deps.storage, env, &mut position, locked_until)?;
+            POSITIONS.save(deps.storage, id, &position)?;
+        }
+        let mut response: Response = update_account_balance(
             deps.branch(),
             env,
             info,
-            &info.sender,
-            user_state.locked_until
+            &info.sender
         )?;
 
         response = response.add_messages(messages);
@@ -83,42 +238,323 @@ pub mod internal_funcs {
 
             let event = ContractEvent::Claim {
                 account: user_address,
+                recipient: recipient.to_string(),
                 claim_amount: pending_reward,
                 ve_balance: user_balance,
             };
             response = response.add_event(event.to_cosmos_event());
         }
+        response = response.add_events(events.iter().map(|event| event.to_cosmos_event()));
 
         Ok(response)
     }
 
-    pub fn update_lock(
+    /// Moves the account reward snapshots forward without paying the pending rewards,
+    /// which are added to FORFEITED_REWARDS. Returns the forfeited locked token reward
+    pub fn forfeit_rewards(
         deps: DepsMut,
         env: &Env,
-        info: &MessageInfo,
-        account: &Addr,
-        new_locked_until: Uint64
+        account: &Addr
+    ) -> Result<Uint128, ContractError> {
+        let forfeited_reward = take_pending_reward(deps.storage, env, account)?;
+
+        let locked_token = TOKEN_STATE.load(deps.storage)?.locked_token;
+        let mut forfeited = vec![(locked_token, forfeited_reward)];
+        forfeited.extend(settle_reward_assets(deps.storage, env, account)?);
+        for (asset, amount) in forfeited {
+            add_forfeited_reward(deps.storage, asset, amount)?;
+        }
+
+        Ok(forfeited_reward)
+    }
+
+    /// Adds amount of asset to what the owner can sweep with SweepForfeitedRewards
+    pub fn add_forfeited_reward(
+        storage: &mut dyn Storage,
+        asset: Denom,
+        amount: Uint128
+    ) -> StdResult<()> {
+        if amount.is_zero() {
+            return Ok(());
+        }
+        let key = RewardAssetState::key(&asset);
+        let mut reward = FORFEITED_REWARDS.may_load(storage, &key)?.unwrap_or(ForfeitedReward {
+            asset,
+            amount: Uint128::zero(),
+        });
+        reward.amount += amount;
+        FORFEITED_REWARDS.save(storage, &key, &reward)
+    }
+
+    /// Moves the account locked token reward snapshot forward and returns the pending
+    /// reward, which stays in the contract for the caller to account for
+    pub fn take_pending_reward(
+        storage: &mut dyn Storage,
+        env: &Env,
+        account: &Addr
+    ) -> Result<Uint128, ContractError> {
+        let mut token_state = TOKEN_STATE.load(storage)?;
+        token_state.accrue(storage, Uint64::from(env.block.height))?;
+
+        let mut user_state = USER_STATE.load(storage, account)?;
+        let pending_reward = user_state.pending_reward(token_state.reward_per_token);
+
+        user_state.reward_snapshot = token_state.reward_per_token;
+        USER_STATE.save(storage, account, &user_state)?;
+
+        Ok(pending_reward)
+    }
+
+    /// Fails unless the position exists and belongs to account
+    pub fn load_position(
+        storage: &dyn Storage,
+        id: u64,
+        account: &Addr
+    ) -> Result<Position, ContractError> {
+        let position = POSITIONS.may_load(storage, id)?.ok_or(ContractError::PositionNotFound {
+            id,
+        })?;
+        if position.owner != *account {
+            return Err(ContractError::Unauthorized {});
+        }
+        Ok(position)
+    }
+
+    /// Positions of the account by id, at most MAX_POSITIONS_PER_ACCOUNT
+    pub fn account_positions(
+        storage: &dyn Storage,
+        account: &Addr
+    ) -> StdResult<Vec<(u64, Position)>> {
+        OWNER_POSITIONS.prefix(account)
+            .keys(storage, None, None, Order::Ascending)
+            .map(|id| {
+                let id = id?;
+                Ok((id, POSITIONS.load(storage, id)?))
+            })
+            .collect()
+    }
+
+    fn assert_can_own_position(storage: &dyn Storage, account: &Addr) -> Result<(), ContractError> {
+        let positions_count = OWNER_POSITIONS.prefix(account)
+            .keys(storage, None, None, Order::Ascending)
+            .count();
+        if positions_count >= MAX_POSITIONS_PER_ACCOUNT {
+            return Err(ContractError::TooManyPositions {});
+        }
+        Ok(())
+    }
+
+    /// Uses up one position of what beneficiary allowed locker to give it, for a position
+    /// unlocking at locked_until. Returns the allowance event once it is used up
+    pub fn use_lock_for_allowance(
+        storage: &mut dyn Storage,
+        beneficiary: &Addr,
+        locker: &Addr,
+        locked_until: Uint64
+    ) -> Result<Vec<Event>, ContractError> {
+        let key = (beneficiary, locker);
+        let mut allowance = LOCK_FOR_ALLOWANCES.may_load(storage, key)?.ok_or(
+            ContractError::LockForNotAllowed {}
+        )?;
+        if locked_until > allowance.max_locked_until {
+            return Err(ContractError::LockForTooLong {});
+        }
+
+        // Each position uses up one, so a locker cannot fill all of the beneficiary's
+        // MAX_POSITIONS_PER_ACCOUNT slots unless allowed to
+        allowance.positions -= 1;
+        if allowance.positions == 0 {
+            LOCK_FOR_ALLOWANCES.remove(storage, key);
+            let event = ContractEvent::make_lock_for_allowance(
+                beneficiary.to_string(),
+                locker.to_string(),
+                Uint64::zero(),
+                0
+            );
+            return Ok(vec![event.to_cosmos_event()]);
+        }
+        LOCK_FOR_ALLOWANCES.save(storage, key, &allowance)?;
+        Ok(vec![])
+    }
+
+    /// Opens an empty position owned by account, minting its NFT
+    pub fn create_position(storage: &mut dyn Storage, account: &Addr) -> Result<u64, ContractError> {
+        assert_can_own_position(storage, account)?;
+
+        let id = POSITION_COUNT.may_load(storage)?.unwrap_or_default() + 1;
+        POSITION_COUNT.save(storage, &id)?;
+        POSITIONS.save(storage, id, &Position::new(account.clone()))?;
+        OWNER_POSITIONS.save(storage, (account, id), &Empty {})?;
+        let num_positions = NUM_POSITIONS.may_load(storage)?.unwrap_or_default();
+        NUM_POSITIONS.save(storage, &(num_positions + 1))?;
+
+        Ok(id)
+    }
+
+    /// Deletes a withdrawn position and burns its NFT, the id is not reused
+    pub fn remove_position(storage: &mut dyn Storage, id: u64, owner: &Addr) -> StdResult<()> {
+        POSITIONS.remove(storage, id);
+        OWNER_POSITIONS.remove(storage, (owner, id));
+        let num_positions = NUM_POSITIONS.may_load(storage)?.unwrap_or_default();
+        NUM_POSITIONS.save(storage, &num_positions.saturating_sub(1))
+    }
+
+    /// Moves the position with its ve balance, voting power and withdraw request to recipient.
+    /// Both accounts claim first, so rewards accrued until now stay with the previous owner
+    pub fn transfer_position(
+        mut deps: DepsMut,
+        env: &Env,
+        id: u64,
+        recipient: &Addr
     ) -> Result<Response, ContractError> {
+        let owner = POSITIONS.load(deps.storage, id)?.owner;
+        if owner != *recipient {
+            assert_can_own_position(deps.storage, recipient)?;
+        }
+        if !USER_STATE.has(deps.storage, recipient) {
+            USER_STATE.save(deps.storage, recipient, &UserState::default())?;
+        }
+
+        let owner_info = MessageInfo { sender: owner.clone(), funds: vec![] };
+        let recipient_info = MessageInfo { sender: recipient.clone(), funds: vec![] };
+        let mut response = Response::new();
+        for info in [&owner_info, &recipient_info] {
+            let claim_response = claim(deps.branch(), env, info)?;
+            response = response
+                .add_submessages(claim_response.messages)
+                .add_events(claim_response.events)
+                .add_attributes(claim_response.attributes);
+        }
+
+        // claim has refreshed the position
+        let mut position = POSITIONS.load(deps.storage, id)?;
+        let current_ts = Uint64::from(env.block.time.seconds());
+        let max_lock_period = CONFIG.load(deps.storage)?.max_lock_period;
+        checkpoints::checkpoint(
+            deps.storage,
+            &owner,
+            &position.point,
+            Uint128::zero(),
+            position.locked_until,
+            current_ts,
+            max_lock_period
+        )?;
+        position.point = checkpoints::checkpoint(
+            deps.storage,
+            recipient,
+            &Point::default(),
+            position.locked_balance,
+            position.locked_until,
+            current_ts,
+            max_lock_period
+        )?;
+        position.owner = recipient.clone();
+        position.approvals = vec![];
+        POSITIONS.save(deps.storage, id, &position)?;
+        OWNER_POSITIONS.remove(deps.storage, (&owner, id));
+        OWNER_POSITIONS.save(deps.storage, (recipient, id), &Empty {})?;
+
+        USER_STATE.update(deps.storage, &owner, |state| -> StdResult<_> {
+            let mut state = state.unwrap_or_default();
+            state.locked_balance -= position.locked_balance;
+            Ok(state)
+        })?;
+        USER_STATE.update(deps.storage, recipient, |state| -> StdResult<_> {
+            let mut state = state.unwrap_or_default();
+            state.locked_balance += position.locked_balance;
+            Ok(state)
+        })?;
+
+        for (info, account) in [(&owner_info, &owner), (&recipient_info, recipient)] {
+            let set_balance_resp = update_account_balance(deps.branch(), env, info, account)?;
+            response = response
+                .add_submessages(set_balance_resp.messages)
+                .add_events(set_balance_resp.events)
+                .add_attributes(set_balance_resp.attributes);
+        }
+
+        Ok(response)
+    }
+
+    /// Recomputes the position ve balance for new_locked_until and checkpoints its voting power.
+    /// The owner's ve balance is left to update_account_balance
+    pub fn refresh_position(
+        storage: &mut dyn Storage,
+        env: &Env,
+        position: &mut Position,
+        new_locked_until: Uint64
+    ) -> StdResult<()> {
         let current_ts = Uint64::from(env.block.time.seconds());
+        let max_lock_period = CONFIG.load(storage)?.max_lock_period;
+
+        position.balance = position_balance(
+            position.locked_balance,
+            new_locked_until,
+            current_ts,
+            max_lock_period
+        );
+        position.locked_until = new_locked_until;
+        position.point = checkpoints::checkpoint(
+            storage,
+            &position.owner,
+            &position.point,
+            position.locked_balance,
+            new_locked_until,
+            current_ts,
+            max_lock_period
+        )?;
+
+        Ok(())
+    }
 
-        let lock_seconds = if new_locked_until > current_ts {
-            Uint128::from(new_locked_until - current_ts)
+    /// The ve balance of a position at current_ts
+    pub fn position_balance(
+        locked_balance: Uint128,
+        locked_until: Uint64,
+        current_ts: Uint64,
+        max_lock_period: Uint64
+    ) -> Uint128 {
+        // Locks made before max_lock_period was reduced can't give more than 1 veToken per token
+        let lock_seconds = if locked_until > current_ts {
+            Uint128::from((locked_until - current_ts).min(max_lock_period))
         } else {
             Uint128::zero()
         };
 
-        let mut user_state = USER_STATE.load(deps.storage, &account).unwrap_or_default();
+        (locked_balance * lock_seconds) / Uint128::from(max_lock_period)
+    }
 
-        let new_balance =
-            (user_state.locked_balance * lock_seconds) / Uint128::from(MAX_LOCK_PERIOD);
+    pub fn update_lock(
+        deps: DepsMut,
+        env: &Env,
+        info: &MessageInfo,
+        position_id: u64,
+        new_locked_until: Uint64
+    ) -> Result<Response, ContractError> {
+        let mut position = POSITIONS.load(deps.storage, position_id)?;
+        refresh_position(deps.storage, env, &mut position, new_locked_until)?;
+        POSITIONS.save(deps.storage, position_id, &position)?;
 
-        user_state.locked_until = new_locked_until;
+        update_account_balance(deps, env, info, &position.owner)
+    }
 
-        USER_STATE.save(deps.storage, &account, &user_state)?;
+    /// Sets the account ve balance to the sum of its position balances
+    pub fn update_account_balance(
+        deps: DepsMut,
+        env: &Env,
+        info: &MessageInfo,
+        account: &Addr
+    ) -> Result<Response, ContractError> {
+        let new_balance = account_positions(deps.storage, account)?
+            .iter()
+            .map(|(_, position)| position.balance)
+            .sum();
 
-        return set_balance(deps, &env, &info, &account, new_balance);
+        set_balance(deps, env, info, account, new_balance)
     }
 
+    /// Mints or burns the ve balance of the account, and follows the change in the
+    /// snapshots, gauge votes, delegated voting power and balance hooks
     pub fn set_balance(
         mut deps: DepsMut,
         env: &Env,
@@ -132,6 +568,13 @@ pub mod internal_funcs {
         if !user_state.reward_snapshot.eq(&token_state.reward_per_token) {
             return Result::Err(ContractError::ClaimFirst {});
         }
+        for item in REWARD_ASSETS.range(deps.storage, None, None, Order::Ascending) {
+            let (key, asset_state) = item?;
+            let reward_snapshot = USER_REWARD_SNAPSHOTS.may_load(deps.storage, (account, &key))?;
+            if !reward_snapshot.unwrap_or_default().eq(&asset_state.reward_per_token) {
+                return Result::Err(ContractError::ClaimFirst {});
+            }
+        }
 
         // TODO: check if this is correct way for internal transactions
         let mut cw_info = info.clone();
@@ -166,12 +609,40 @@ pub mod internal_funcs {
 
         let user_balance = query_balance(deps.as_ref(), account.to_string())?.balance;
 
+        // Votes follow the slope changes of the positions, which change with the balance or not
+        gauges::update_user_power(deps.storage, account, Uint64::from(env.block.time.seconds()))?;
+
+        let mut hook_msgs = vec![];
+        if user_balance != user_state.balance {
+            BALANCE_SNAPSHOTS.save(deps.storage, account, &user_balance, env.block.height)?;
+            TOTAL_SUPPLY_SNAPSHOTS.save(deps.storage, &total_supply, env.block.height)?;
+            delegation::update_voting_power(
+                deps.storage,
+                account,
+                user_state.balance,
+                user_balance,
+                env.block.height
+            )?;
+
+            let locked_until = account_positions(deps.storage, account)?
+                .iter()
+                .map(|(_, position)| position.locked_until)
+                .max()
+                .unwrap_or_default();
+            hook_msgs = hooks::balance_changed_hook_msgs(deps.storage, VeBalanceChangedHookMsg {
+                account: account.to_string(),
+                old_balance: user_state.balance,
+                new_balance: user_balance,
+                locked_until,
+            })?;
+        }
+
         user_state.balance = user_balance;
         USER_STATE.save(deps.storage, account, &user_state)?;
 
         match cw20_result {
             Ok(resp) => {
-                return Ok(resp);
+                return Ok(resp.add_submessages(hook_msgs));
             }
             Err(err) => {
                 return Err(ContractError::CW20BaseError(err.to_string()));
@@ -189,11 +660,19 @@ mod internal_tests {
         attr,
         Empty,
         to_binary,
+        WasmMsg,
     };
     use cw20::Cw20ExecuteMsg;
     use cw20_base::state::{ BALANCES, TOKEN_INFO };
 
-    use crate::test_helpers::{ mock_instantiate, apply_decimals, cw20_mock_querier };
+    use crate::test_helpers::{
+        mock_instantiate,
+        apply_decimals,
+        cw20_mock_querier,
+        MOCK_LOCKED_TOKEN,
+    };
+    use crate::consts::MAX_LOCK_PERIOD;
+    use crate::state::VOTING_POWER;
 
     use super::*;
     use internal_funcs::*;
@@ -229,12 +708,22 @@ mod internal_tests {
             |state| -> StdResult<_> {
                 let mut user = state.unwrap_or_default();
                 user.locked_balance = user_locked_balance.clone();
-                user.locked_until = Uint64::from(
-                    env.block.time.plus_seconds(user_locked_until_delta.clone().u64()).seconds()
-                );
                 Ok(user)
             }
         ).unwrap();
+        let position_id = create_position(deps.as_mut().storage, &user_addr).unwrap();
+        POSITIONS.update(
+            deps.as_mut().storage,
+            position_id,
+            |position| -> StdResult<_> {
+                let mut position = position.unwrap();
+                position.locked_balance = user_locked_balance;
+                position.locked_until = Uint64::from(
+                    env.block.time.plus_seconds(user_locked_until_delta.u64()).seconds()
+                );
+                Ok(position)
+            }
+        ).unwrap();
 
         let token_state = TOKEN_STATE.load(deps.as_mut().storage).unwrap();
         let user_state = USER_STATE.load(deps.as_mut().storage, &user_addr).unwrap();
@@ -270,7 +759,7 @@ mod internal_tests {
         );
 
         let expected_message = CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: token_state.locked_token.to_string(),
+            contract_addr: MOCK_LOCKED_TOKEN.to_string(),
             msg: to_binary(
                 &(Cw20ExecuteMsg::Transfer {
                     recipient: user_addr.clone().into(),
@@ -284,6 +773,7 @@ mod internal_tests {
             .add_message(expected_message)
             .add_event(
                 ContractEvent::make_claim(
+                    user_addr.to_string(),
                     user_addr.to_string(),
                     expected_pending_reward,
                     expected_balance.clone()
@@ -311,6 +801,10 @@ mod internal_tests {
         let mut user_state = UserState::default();
         user_state.locked_balance = Uint128::from(1000u16);
         USER_STATE.save(deps_binding.as_mut().storage, &user_addr, &user_state).unwrap();
+        let position_id = create_position(deps_binding.as_mut().storage, &user_addr).unwrap();
+        let mut position = POSITIONS.load(deps_binding.as_ref().storage, position_id).unwrap();
+        position.locked_balance = Uint128::from(1000u16);
+        POSITIONS.save(deps_binding.as_mut().storage, position_id, &position).unwrap();
 
         // 1. Set non-zero balance
 
@@ -320,7 +814,7 @@ mod internal_tests {
             deps_binding.as_mut(),
             &env,
             &info,
-            &user_addr,
+            position_id,
             new_locked_until
         ).unwrap();
 
@@ -340,7 +834,7 @@ mod internal_tests {
             deps_binding.as_mut(),
             &env,
             &info,
-            &user_addr,
+            position_id,
             Uint64::from(env.block.time.seconds().to_owned())
         ).unwrap();
 
@@ -355,6 +849,47 @@ mod internal_tests {
         assert_eq!(resp, expected_response);
     }
 
+    #[test]
+    fn test_update_lock_after_max_lock_period_reduced() {
+        let mut deps_binding = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("creator", &[]);
+
+        mock_instantiate(deps_binding.as_mut(), env.to_owned(), info.to_owned());
+
+        let user_addr = Addr::unchecked("user");
+        let mut user_state = UserState::default();
+        user_state.locked_balance = Uint128::from(1000u16);
+        USER_STATE.save(deps_binding.as_mut().storage, &user_addr, &user_state).unwrap();
+        let position_id = create_position(deps_binding.as_mut().storage, &user_addr).unwrap();
+        let mut position = POSITIONS.load(deps_binding.as_ref().storage, position_id).unwrap();
+        position.locked_balance = Uint128::from(1000u16);
+        POSITIONS.save(deps_binding.as_mut().storage, position_id, &position).unwrap();
+
+        let new_locked_until = Uint64::from(env.block.time.plus_seconds(MAX_LOCK_PERIOD).seconds());
+
+        update_lock(deps_binding.as_mut(), &env, &info, position_id, new_locked_until).unwrap();
+        assert_eq!(
+            BALANCES.load(deps_binding.as_ref().storage, &user_addr).unwrap(),
+            Uint128::from(1000u16)
+        );
+
+        // Lock remaining time is now longer than max lock period
+        CONFIG.update(
+            deps_binding.as_mut().storage,
+            |mut config| -> StdResult<_> {
+                config.max_lock_period = Uint64::from(MAX_LOCK_PERIOD / 2);
+                Ok(config)
+            }
+        ).unwrap();
+
+        update_lock(deps_binding.as_mut(), &env, &info, position_id, new_locked_until).unwrap();
+        assert_eq!(
+            BALANCES.load(deps_binding.as_ref().storage, &user_addr).unwrap(),
+            Uint128::from(1000u16)
+        ); // capped at 1 veToken per token
+    }
+
     #[test]
     fn test_set_balance_burn() {
         let mut deps_binding = mock_dependencies();
@@ -377,6 +912,12 @@ mod internal_tests {
         user_state.reward_snapshot = Uint128::from(10u16);
         user_state.balance = Uint128::from(100u16);
         USER_STATE.save(deps_binding.as_mut().storage, &user_addr, &user_state).unwrap();
+        VOTING_POWER.save(
+            deps_binding.as_mut().storage,
+            &user_addr,
+            &user_state.balance,
+            env.block.height
+        ).unwrap();
 
         let mut cw20_info = info.clone();
         cw20_info.sender = env.clone().contract.address;
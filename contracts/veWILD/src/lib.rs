@@ -1,4 +1,14 @@
-use cosmwasm_std::{ entry_point, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult };
+use cosmwasm_std::{
+    entry_point,
+    Binary,
+    Deps,
+    DepsMut,
+    Env,
+    MessageInfo,
+    Reply,
+    Response,
+    StdResult,
+};
 use error::ContractError;
 
 pub mod contract;
@@ -8,9 +18,17 @@ pub mod state;
 pub mod events;
 pub mod consts;
 pub mod cw20_client;
+pub mod asset_client;
 pub mod tests;
 pub mod test_helpers;
 pub mod internal;
+pub mod migrations;
+pub mod checkpoints;
+pub mod hooks;
+pub mod gauges;
+pub mod fee_distributor;
+pub mod nft;
+pub mod delegation;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -36,3 +54,13 @@ pub fn execute(
 ) -> Result<Response, ContractError> {
     contract::execute(deps, env, info, msg)
 }
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, msg: msg::MigrateMsg) -> Result<Response, ContractError> {
+    contract::migrate(deps, env, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    contract::reply(deps, env, msg)
+}
@@ -0,0 +1,166 @@
+use cosmwasm_std::{ Addr, DepsMut, Empty, Env, Order, StdResult, Uint128, Uint64 };
+use cw20::Denom;
+use semver::Version;
+
+use crate::{
+    error::ContractError,
+    msg::MigrateMsg,
+    checkpoints,
+    state::{
+        TOKEN_STATE,
+        USER_STATE,
+        BALANCE_SNAPSHOTS,
+        VOTING_POWER,
+        TOTAL_SUPPLY_SNAPSHOTS,
+        OWNERSHIP,
+        DAO,
+        PAUSE_STATE,
+        CONFIG,
+        POSITIONS,
+        OWNER_POSITIONS,
+        POSITION_COUNT,
+        NUM_POSITIONS,
+        Point,
+        Position,
+        TokenState,
+        Ownership,
+        PauseState,
+        Config,
+        UserState,
+    },
+};
+
+/// Storage layout of 0.1.x deployments
+pub mod v0_1 {
+    use cosmwasm_schema::cw_serde;
+    use cosmwasm_std::{ Addr, Uint128, Uint64 };
+    use cw_storage_plus::{ Item, Map };
+
+    pub const TOKEN_STATE: Item<TokenState> = Item::new("token_state");
+    pub const USER_STATE: Map<&Addr, UserState> = Map::new("user_state");
+
+    #[cw_serde]
+    pub struct TokenState {
+        pub total_supply: Uint128,
+        pub total_locked: Uint128,
+        pub distribution_period: Uint64,
+        pub locked_token: Addr,
+        pub last_accrue_block: Uint64,
+        pub last_income_block: Uint64,
+        pub reward_per_token: Uint128,
+        pub reward_rate_stored: Uint128,
+    }
+
+    /// A single lock per account
+    #[cw_serde]
+    pub struct UserState {
+        pub balance: Uint128,
+        pub locked_balance: Uint128,
+        pub locked_until: Uint64,
+        pub reward_snapshot: Uint128,
+        pub withdraw_at: Uint64,
+    }
+}
+
+/// Runs every step newer than previous_version, oldest first.
+/// Each storage layout change of a released version bumps the minor version and adds a
+/// migrate_to_vX step here. Only layouts a step reads are accepted: 0.1.x, and 0.2.x
+/// releases, which need no step
+pub fn migrate_storage(
+    mut deps: DepsMut,
+    env: &Env,
+    previous_version: &Version,
+    msg: &MigrateMsg
+) -> Result<(), ContractError> {
+    if previous_version.major == 0 && previous_version.minor == 1 {
+        migrate_to_v0_2(deps.branch(), env, msg)?;
+    } else if *previous_version < Version::new(0, 2, 0) {
+        // Older versions and 0.2 pre-releases have layouts no step reads
+        return Err(ContractError::UnsupportedMigration {
+            previous_version: previous_version.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// 0.1 -> 0.2: owner (also the DAO), pause state, config with the former compile-time limits,
+/// locked token becomes a CW20 denom, voting power checkpoints and balance snapshots
+/// for existing locks.
+/// Each existing lock becomes a position, UserState keeps the account totals.
+/// Nobody has delegated yet, so voting power starts as the ve balance.
+/// Writes the whole 0.2 layout, which was not released before these changes
+fn migrate_to_v0_2(deps: DepsMut, env: &Env, msg: &MigrateMsg) -> Result<(), ContractError> {
+    let legacy_state = v0_1::TOKEN_STATE.load(deps.storage)?;
+    let token_state = TokenState {
+        total_supply: legacy_state.total_supply,
+        total_locked: legacy_state.total_locked,
+        distribution_period: legacy_state.distribution_period,
+        locked_token: Denom::Cw20(legacy_state.locked_token),
+        last_accrue_block: legacy_state.last_accrue_block,
+        last_income_block: legacy_state.last_income_block,
+        reward_per_token: legacy_state.reward_per_token,
+        reward_rate_stored: legacy_state.reward_rate_stored,
+    };
+    TOKEN_STATE.save(deps.storage, &token_state)?;
+
+    // 0.1 had no owner at all
+    let owner = msg.owner.as_ref().ok_or(ContractError::MigrationOwnerRequired {})?;
+    let owner = deps.api.addr_validate(owner)?;
+    DAO.save(deps.storage, &owner)?;
+    OWNERSHIP.save(deps.storage, &Ownership::new(owner))?;
+
+    PAUSE_STATE.save(deps.storage, &PauseState::default())?;
+    let config = Config::default();
+    CONFIG.save(deps.storage, &config)?;
+
+    let current_ts = Uint64::from(env.block.time.seconds());
+    let users = v0_1::USER_STATE.range(deps.storage, None, None, Order::Ascending).collect::<
+        StdResult<Vec<(Addr, v0_1::UserState)>>
+    >()?;
+    // History starts at the migration height
+    TOTAL_SUPPLY_SNAPSHOTS.save(deps.storage, &token_state.total_supply, env.block.height)?;
+    let mut position_count = 0u64;
+    for (account, legacy_user) in users {
+        BALANCE_SNAPSHOTS.save(deps.storage, &account, &legacy_user.balance, env.block.height)?;
+        VOTING_POWER.save(deps.storage, &account, &legacy_user.balance, env.block.height)?;
+        USER_STATE.save(deps.storage, &account, &UserState {
+            balance: legacy_user.balance,
+            locked_balance: legacy_user.locked_balance,
+            reward_snapshot: legacy_user.reward_snapshot,
+        })?;
+        if legacy_user.locked_balance.is_zero() {
+            continue;
+        }
+
+        let point = checkpoints::checkpoint(
+            deps.storage,
+            &account,
+            &Point::default(),
+            legacy_user.locked_balance,
+            legacy_user.locked_until,
+            current_ts,
+            config.max_lock_period
+        )?;
+        position_count += 1;
+        POSITIONS.save(deps.storage, position_count, &Position {
+            owner: account.clone(),
+            locked_balance: legacy_user.locked_balance,
+            locked_until: legacy_user.locked_until,
+            withdraw_at: legacy_user.withdraw_at,
+            // v0.1 requests always covered the whole lock
+            withdraw_amount: if legacy_user.withdraw_at.is_zero() {
+                Uint128::zero()
+            } else {
+                legacy_user.locked_balance
+            },
+            balance: legacy_user.balance,
+            point,
+            approvals: vec![],
+        })?;
+        OWNER_POSITIONS.save(deps.storage, (&account, position_count), &Empty {})?;
+    }
+    POSITION_COUNT.save(deps.storage, &position_count)?;
+    NUM_POSITIONS.save(deps.storage, &position_count)?;
+
+    Ok(())
+}
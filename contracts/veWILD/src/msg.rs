@@ -1,31 +1,309 @@
 use cosmwasm_schema::{ cw_serde, QueryResponses };
-use cosmwasm_std::{ Addr, Uint64, Uint128 };
-use cw20::{ BalanceResponse, TokenInfoResponse };
+use cosmwasm_std::{ Addr, Binary, Decimal, Uint64, Uint128 };
+use cw2::ContractVersion;
+use cw20::{ BalanceResponse, TokenInfoResponse, Cw20ReceiveMsg, Denom };
+use cw_utils::Expiration;
 
-use crate::state::{ UserState, TokenState };
+use crate::nft::PositionMetadata;
+
+use crate::state::{
+    Config,
+    EarlyWithdrawConfig,
+    UserState,
+    Position,
+    NftApproval,
+    TokenState,
+    RewardAssetState,
+    HookErrorPolicy,
+    Role,
+    PausableAction,
+    PauseState,
+    Shutdown,
+    ShutdownRewardPolicy,
+};
 
 #[cw_serde]
 pub struct InstantiateMsg {
-    pub locked_token: Addr,
+    /// CW20 contract or native (bank / tokenfactory) denom
+    pub locked_token: Denom,
     pub distribution_period: Uint64,
+    /// seconds, defaults to 1 week
+    pub min_lock_period: Option<Uint64>,
+    /// seconds, defaults to 4 years
+    pub max_lock_period: Option<Uint64>,
+    /// seconds, defaults to 1 day
+    pub withdraw_delay: Option<Uint64>,
+    /// DAO core using this contract as its voting module, defaults to the instantiator
+    /// as DAO core instantiates its voting module itself
+    pub dao: Option<String>,
+}
+
+#[cw_serde]
+pub struct MigrateMsg {
+    /// Required when migrating from 0.1.x, which had no owner
+    pub owner: Option<String>,
 }
 
 // This is for differentiating the messages in execute()
 #[cw_serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
+    /// Opens a new position. CW20 locked token is received through Send with a ReceiveMsg
+    /// instead. Native locked token is attached as funds matching amount
     Lock {
         amount: Uint128,
         new_locked_until: Uint64,
     },
-    RequestWithdraw,
-    Withdraw,
-    Claim,
+    /// Adds amount to a position that has not expired yet, keeping its unlock time.
+    /// Paid like Lock
+    IncreaseAmount {
+        position: u64,
+        amount: Uint128,
+    },
+    /// Moves the unlock time of a position later, expired positions are relocked from now
+    ExtendLock {
+        position: u64,
+        new_locked_until: Uint64,
+    },
+    /// Opens a position owned by beneficiary, paid by the sender like Lock. Existing
+    /// positions are never extended, and a beneficiary other than the sender must have
+    /// allowed the sender with AllowLockFor up to locked_until, using up one of its positions
+    LockFor {
+        beneficiary: String,
+        amount: Uint128,
+        locked_until: Uint64,
+    },
+    /// Lets locker open up to max_positions positions for the sender, or transfer them to it,
+    /// that unlock no later than max_locked_until. Replaces the previous allowance of locker
+    AllowLockFor {
+        locker: String,
+        max_locked_until: Uint64,
+        max_positions: u32,
+    },
+    DisallowLockFor {
+        locker: String,
+    },
+    /// Starts the withdraw delay for amount of an expired position, the whole position if unset.
+    /// A new request replaces the pending one
+    RequestWithdraw {
+        position: u64,
+        amount: Option<Uint128>,
+    },
+    /// Pays out the requested amount, the rest of the position stays
+    Withdraw {
+        position: u64,
+    },
+    /// Drops the pending withdraw request, relocking the position until relock_until if set
+    CancelWithdrawRequest {
+        position: u64,
+        relock_until: Option<Uint64>,
+    },
+    /// Withdraws a position before it unlocks, minus a penalty that shrinks linearly
+    /// with the time left, see EarlyWithdrawConfig
+    EarlyWithdraw {
+        position: u64,
+    },
+    /// Moves the locked tokens of from into into, which keeps the later unlock time
+    Merge {
+        from: u64,
+        into: u64,
+    },
+    /// Opens a position with the same unlock time for each amount taken out of position
+    Split {
+        position: u64,
+        amounts: Vec<Uint128>,
+    },
+    /// Implements CW721. Moves the position with its ve balance and withdraw rights,
+    /// rewards accrued so far are paid to the previous owner. Uses up a position of the
+    /// recipient's AllowLockFor for the sender, unless sent to the sender or the owner
+    TransferNft {
+        recipient: String,
+        token_id: String,
+    },
+    /// Implements CW721. TransferNft to a contract, which is then called with
+    /// Cw721ReceiveMsg
+    SendNft {
+        contract: String,
+        token_id: String,
+        msg: Binary,
+    },
+    /// Implements CW721. Allows spender to transfer the position until expires
+    /// (default never) or the next transfer
+    Approve {
+        spender: String,
+        token_id: String,
+        expires: Option<Expiration>,
+    },
+    /// Implements CW721
+    Revoke {
+        spender: String,
+        token_id: String,
+    },
+    /// Implements CW721. Allows operator to transfer and approve every position of the sender
+    ApproveAll {
+        operator: String,
+        expires: Option<Expiration>,
+    },
+    /// Implements CW721
+    RevokeAll {
+        operator: String,
+    },
+    /// Pays the sender's rewards to recipient, default the sender
+    Claim {
+        recipient: Option<String>,
+    },
+    /// Claim operators only. Claims the rewards of account, always paid to account
+    ClaimFor {
+        account: String,
+    },
+    /// Callable by anyone. Brings the stored ve balance of account down to its decayed value,
+    /// claiming its rewards to account. Fails if the stored balance isn't stale
+    Kick {
+        account: String,
+    },
+    /// Locks the sender's pending locked token rewards into its unexpired position with the
+    /// latest unlock time. Reward assets are paid out like Claim
+    Compound {},
+    /// Compound for an account that enabled auto-compounding, callable by anyone
+    CompoundFor {
+        account: String,
+    },
+    SetAutoCompound {
+        enabled: bool,
+    },
+    /// Lets operator claim the sender's rewards with ClaimFor
+    ApproveClaimOperator {
+        operator: String,
+    },
+    RevokeClaimOperator {
+        operator: String,
+    },
+    /// CW20 income is received through Send with ReceiveMsg::AddIncome,
+    /// native income is attached as funds matching add_amount
     AddIncome {
         add_amount: Uint128,
     },
+    /// Income manager only. Locked token income for the weekly fee distribution,
+    /// sent like AddIncome (CW20 through Send with ReceiveMsg::AddEpochIncome)
+    AddEpochIncome {
+        add_amount: Uint128,
+    },
+    /// Pays the sender's share of the finished fee epochs, going over at most limit
+    /// epochs with income. Call again to resume from where it stopped.
+    /// Rejected after a shutdown that forfeits rewards
+    ClaimEpochFees {
+        limit: Option<u32>,
+    },
+    /// Config admin only. CW20 address or native denom other than the locked token
+    AddRewardAsset {
+        asset: Denom,
+        distribution_period: Uint64,
+    },
+    /// Income manager only. CW20 reward assets are received through Send with
+    /// ReceiveMsg::AddRewardIncome, native ones are attached as funds matching add_amount
+    AddRewardIncome {
+        asset: Denom,
+        add_amount: Uint128,
+    },
+    /// Config admin only. address is called with VeBalanceChangedHookMsg on every
+    /// ve balance change, on_error decides whether its failures revert the change
+    AddBalanceHook {
+        address: String,
+        on_error: HookErrorPolicy,
+    },
+    /// Config admin only.
+    RemoveBalanceHook {
+        address: String,
+    },
+    /// Config admin only.
+    AddGauge {
+        address: String,
+    },
+    /// Votes weight basis points of the sender's decaying BalanceOf for the gauge, from the
+    /// next epoch on. The vote decays with the sender's positions and ends when they unlock.
+    /// Weight 0 removes the vote. Each gauge vote has a cooldown
+    VoteGauge {
+        gauge: String,
+        weight: u16,
+    },
+    /// Gives the governance voting power of the sender's ve balance, now and as it
+    /// changes, to another address. Replaces a previous delegation
+    Delegate {
+        to: String,
+    },
+    Undelegate {},
+    /// CW20 Send hook, accepted from the locked token and CW20 reward assets
+    Receive(Cw20ReceiveMsg),
     SetDistributionPeriod {
         blocks: Uint64,
     },
+    /// Owner only. Starts a transfer that completes once new_owner calls AcceptOwnership.
+    ProposeOwner {
+        new_owner: String,
+    },
+    AcceptOwnership {},
+    /// Owner only. Leaves the contract without an owner, irreversibly.
+    RenounceOwnership {},
+    /// Owner only.
+    GrantRole {
+        role: Role,
+        address: String,
+    },
+    /// Owner only.
+    RevokeRole {
+        role: Role,
+        address: String,
+    },
+    /// Pauser only.
+    Pause {
+        actions: Vec<PausableAction>,
+    },
+    /// Pauser only.
+    Unpause {
+        actions: Vec<PausableAction>,
+    },
+    /// Owner only. Irreversible: every account can withdraw immediately,
+    /// new locks and income are rejected.
+    EmergencyShutdown {
+        reward_policy: ShutdownRewardPolicy,
+    },
+    /// Owner only. Sends the rewards forfeited by exits after shutdown, and the epoch fees
+    /// unclaimed at a shutdown that forfeits rewards, to recipient
+    SweepForfeitedRewards {
+        recipient: String,
+    },
+    /// Config admin only. Unset fields are left unchanged.
+    /// Existing ve balances are not rescaled when max_lock_period changes: each account is
+    /// recomputed against the new value the next time its lock is updated (lock, claim, withdraw).
+    UpdateConfig {
+        min_lock_period: Option<Uint64>,
+        max_lock_period: Option<Uint64>,
+        withdraw_delay: Option<Uint64>,
+    },
+    /// Config admin only. None disables early withdraw
+    SetEarlyWithdrawConfig {
+        config: Option<EarlyWithdrawConfig>,
+    },
+}
+
+/// Hook messages of ExecuteMsg::Receive, sent amount is credited to the CW20 sender
+#[cw_serde(rename_all = "snake_case")]
+pub enum ReceiveMsg {
+    Lock {
+        new_locked_until: Uint64,
+    },
+    IncreaseAmount {
+        position: u64,
+    },
+    LockFor {
+        beneficiary: String,
+        locked_until: Uint64,
+    },
+    /// Income manager only
+    AddIncome {},
+    /// Income manager only
+    AddEpochIncome {},
+    /// Income manager only, sent by a registered CW20 reward asset
+    AddRewardIncome {},
 }
 
 // More queries based on the contract ...
@@ -35,6 +313,7 @@ pub enum ExecuteMsg {
 #[derive(QueryResponses)]
 pub enum QueryMsg {
     /// Implements CW20. Returns the current balance of the given address, 0 if unset.
+    /// This is the reward share, set when a position changes or the account is kicked
     #[returns(BalanceResponse)]
     Balance {
         address: String,
@@ -44,15 +323,202 @@ pub enum QueryMsg {
     TokenInfo {},
 
     #[returns(RewardRateResponse)] RewardRate {},
+    /// Locked token reward first, then every reward asset
     #[returns(PendingAccountRewardResponse)] PendingAccountReward {
         address: Addr,
     },
 
+    #[returns(RewardAssetsResponse)] RewardAssets {},
+
+    #[returns(BalanceHooksResponse)] BalanceHooks {},
+
+    /// Fee distribution income of the epoch containing time
+    #[returns(EpochFeesResponse)] EpochFees {
+        time: Uint64,
+    },
+
+    /// What ClaimEpochFees with the max limit would pay now
+    #[returns(PendingEpochFeesResponse)] PendingEpochFees {
+        address: String,
+    },
+
+    #[returns(GaugesResponse)] Gauges {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Weights of the epoch containing time, default current one
+    #[returns(GaugeWeightResponse)] GaugeWeight {
+        gauge: String,
+        time: Option<Uint64>,
+    },
+
+    /// Current votes, with the part of the decaying ve balance they add to the gauge weights
+    #[returns(UserVotesResponse)] UserVotes {
+        address: String,
+    },
+
+    /// Governance voting power, the ve balance unless delegated plus delegated balances
+    #[returns(VotingPowerResponse)] VotingPower {
+        address: String,
+    },
+
+    /// Accounts delegating to the delegate, ordered by address
+    #[returns(DelegationsResponse)] Delegations {
+        delegate: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Accounts keepers may CompoundFor, ordered by address
+    #[returns(AutoCompoundAccountsResponse)] AutoCompoundAccounts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Operators allowed to claim for account, ordered by address
+    #[returns(ClaimOperatorsResponse)] ClaimOperators {
+        account: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
     #[returns(UserInfoResponse)] UserInfo {
         address: Addr,
     },
 
+    /// Lock positions of the owner, ordered by id
+    #[returns(PositionsResponse)] Positions {
+        owner: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    #[returns(PositionResponse)] Position {
+        id: u64,
+    },
+
+    /// Latest unlock time and number of positions left for locker's LockFor for beneficiary
+    #[returns(LockForAllowanceResponse)] LockForAllowance {
+        beneficiary: String,
+        locker: String,
+    },
+
+    /// Implements CW721, expired approvals are left out unless include_expired
+    #[returns(OwnerOfResponse)] OwnerOf {
+        token_id: String,
+        include_expired: Option<bool>,
+    },
+    /// Implements CW721
+    #[returns(ApprovalResponse)] Approval {
+        token_id: String,
+        spender: String,
+        include_expired: Option<bool>,
+    },
+    /// Implements CW721
+    #[returns(ApprovalsResponse)] Approvals {
+        token_id: String,
+        include_expired: Option<bool>,
+    },
+    /// Implements CW721
+    #[returns(OperatorsResponse)] AllOperators {
+        owner: String,
+        include_expired: Option<bool>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Implements CW721. Number of positions not withdrawn yet
+    #[returns(NumTokensResponse)] NumTokens {},
+    /// Implements CW721, same name and symbol as the ve token
+    #[returns(NftContractInfoResponse)] ContractInfo {},
+    /// Implements CW721
+    #[returns(NftInfoResponse)] NftInfo {
+        token_id: String,
+    },
+    /// Implements CW721
+    #[returns(AllNftInfoResponse)] AllNftInfo {
+        token_id: String,
+        include_expired: Option<bool>,
+    },
+    /// Implements CW721
+    #[returns(TokensResponse)] Tokens {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Implements CW721
+    #[returns(TokensResponse)] AllTokens {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
     #[returns(VeTokenInfoResponse)] VeTokenInfo {},
+
+    /// Voting power decaying every second until the unlock time, at the given
+    /// timestamp (default now). Unlike Balance, which is the reward share.
+    #[returns(BalanceOfResponse)] BalanceOf {
+        address: Addr,
+        timestamp: Option<Uint64>,
+    },
+
+    /// Sum of BalanceOf over all accounts
+    #[returns(TotalSupplyAtResponse)] TotalSupplyAt {
+        timestamp: Option<Uint64>,
+    },
+
+    /// ve balance (Balance) at the start of the given block
+    #[returns(BalanceAtHeightResponse)] BalanceAtHeight {
+        address: Addr,
+        height: Uint64,
+    },
+
+    /// ve total supply at the start of the given block
+    #[returns(TotalSupplyAtHeightResponse)] TotalSupplyAtHeight {
+        height: Uint64,
+    },
+
+    #[returns(OwnerResponse)] Owner {},
+
+    /// DAO DAO voting module. Voting power at the start of the block, default current one.
+    /// Follows Balance, so it only drops when the positions are touched or the account is
+    /// kicked, not every second. An expired lock keeps its power until then
+    #[returns(VotingPowerAtHeightResponse)] VotingPowerAtHeight {
+        address: String,
+        height: Option<u64>,
+    },
+
+    /// DAO DAO voting module. ve total supply at the start of the block, the sum of the
+    /// stored balances, so it includes the power of expired locks nobody kicked yet
+    #[returns(TotalPowerAtHeightResponse)] TotalPowerAtHeight {
+        height: Option<u64>,
+    },
+
+    /// DAO DAO voting module. cw2 contract version
+    #[returns(InfoResponse)] Info {},
+
+    /// DAO DAO voting module. DAO core address
+    #[returns(Addr)] Dao {},
+
+    /// Returns accounts explicitly granted the role, ordered by address.
+    /// The owner holds every role implicitly and is not listed.
+    #[returns(RoleMembersResponse)] RoleMembers {
+        role: Role,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    #[returns(PauseStatusResponse)] PauseStatus {},
+
+    #[returns(ShutdownStatusResponse)] ShutdownStatus {},
+
+    #[returns(ConfigResponse)] Config {},
+
+    #[returns(EarlyWithdrawConfigResponse)] EarlyWithdrawConfig {},
+
+    /// What EarlyWithdraw of each still locked position of address would pay out now
+    #[returns(PreviewEarlyWithdrawResponse)] PreviewEarlyWithdraw {
+        address: String,
+    },
 }
 
 #[cw_serde(rename_all = "snake_case")]
@@ -60,16 +526,27 @@ pub struct RewardRateResponse {
     pub reward_rate: Uint128,
 }
 
+#[cw_serde(rename_all = "snake_case")]
+pub struct PendingReward {
+    pub asset: Denom,
+    pub amount: Uint128,
+}
+
 #[cw_serde(rename_all = "snake_case")]
 pub struct PendingAccountRewardResponse {
-    pub pending_account_reward: Uint128,
+    pub pending_account_rewards: Vec<PendingReward>,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct RewardAssetsResponse {
+    pub reward_assets: Vec<RewardAssetState>,
 }
 
 #[cw_serde(rename_all = "snake_case")]
 pub struct VeTokenInfoResponse {
     pub total_locked: Uint128,
     pub distribution_period: Uint64,
-    pub locked_token: Addr, // address of the token contract
+    pub locked_token: Denom, // CW20 contract or native denom
     pub last_accrue_block: Uint64,
     pub last_income_block: Uint64,
     pub reward_per_token: Uint128,
@@ -88,21 +565,313 @@ impl VeTokenInfoResponse {
     }
 }
 
+#[cw_serde(rename_all = "snake_case")]
+pub struct BalanceOfResponse {
+    pub balance: Uint128,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct TotalSupplyAtResponse {
+    pub total_supply: Uint128,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct BalanceAtHeightResponse {
+    pub balance: Uint128,
+    pub height: Uint64,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct TotalSupplyAtHeightResponse {
+    pub total_supply: Uint128,
+    pub height: Uint64,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct BalanceHook {
+    pub address: Addr,
+    pub on_error: HookErrorPolicy,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct BalanceHooksResponse {
+    pub hooks: Vec<BalanceHook>,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct EpochFeesResponse {
+    pub epoch_start: Uint64,
+    pub fees: Uint128,
+    /// ve supply at the epoch start the fees are split by
+    pub total_supply: Uint128,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct PendingEpochFeesResponse {
+    pub amount: Uint128,
+    /// Epoch start the next claim resumes from
+    pub next_epoch: Uint64,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct GaugesResponse {
+    pub gauges: Vec<Addr>,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct GaugeWeightResponse {
+    pub gauge: Addr,
+    pub epoch_start: Uint64,
+    pub weight: Uint128,
+    pub total_weight: Uint128,
+    pub relative_weight: Decimal,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct UserVote {
+    pub gauge: Addr,
+    /// basis points of the ve balance
+    pub weight: u16,
+    pub power: Uint128,
+    pub last_vote: Uint64,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct UserVotesResponse {
+    pub votes: Vec<UserVote>,
+    pub used_weight: u16,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct AutoCompoundAccountsResponse {
+    pub accounts: Vec<Addr>,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct ClaimOperatorsResponse {
+    pub operators: Vec<Addr>,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct LockForAllowanceResponse {
+    /// None if not allowed
+    pub max_locked_until: Option<Uint64>,
+    pub positions: u32,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct VotingPowerResponse {
+    pub power: Uint128,
+    pub delegated_to: Option<Addr>,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct Delegation {
+    pub delegator: Addr,
+    /// ve balance of the delegator
+    pub power: Uint128,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct DelegationsResponse {
+    pub delegations: Vec<Delegation>,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct VotingPowerAtHeightResponse {
+    pub power: Uint128,
+    pub height: u64,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct TotalPowerAtHeightResponse {
+    pub power: Uint128,
+    pub height: u64,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct InfoResponse {
+    pub info: ContractVersion,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct OwnerResponse {
+    pub owner: Option<Addr>,
+    pub pending_owner: Option<Addr>,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct RoleMembersResponse {
+    pub members: Vec<Addr>,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct PauseStatusResponse {
+    pub lock: bool,
+    pub claim: bool,
+    pub early_withdraw: bool,
+    pub add_income: bool,
+}
+
+impl PauseStatusResponse {
+    pub fn from_pause_state(pause_state: PauseState) -> Self {
+        PauseStatusResponse {
+            lock: pause_state.lock,
+            claim: pause_state.claim,
+            early_withdraw: pause_state.early_withdraw,
+            add_income: pause_state.add_income,
+        }
+    }
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct ShutdownStatusResponse {
+    pub shutdown: Option<Shutdown>,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct ConfigResponse {
+    pub min_lock_period: Uint64,
+    pub max_lock_period: Uint64,
+    pub withdraw_delay: Uint64,
+}
+
+impl ConfigResponse {
+    pub fn from_config(config: Config) -> Self {
+        ConfigResponse {
+            min_lock_period: config.min_lock_period,
+            max_lock_period: config.max_lock_period,
+            withdraw_delay: config.withdraw_delay,
+        }
+    }
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct EarlyWithdrawConfigResponse {
+    pub config: Option<EarlyWithdrawConfig>,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct EarlyWithdrawPreview {
+    pub position: u64,
+    pub locked_balance: Uint128,
+    pub penalty: Uint128,
+    /// locked_balance minus penalty
+    pub amount: Uint128,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct PreviewEarlyWithdrawResponse {
+    pub positions: Vec<EarlyWithdrawPreview>,
+}
+
 #[cw_serde(rename_all = "snake_case")]
 pub struct UserInfoResponse {
+    /// Sum over the account positions
     pub locked_balance: Uint128,
-    pub locked_until: Uint64,
     pub reward_snapshot: Uint128,
-    pub withdraw_at: Uint64,
 }
 
 impl UserInfoResponse {
     pub fn from_user_state(user_state: UserState) -> Self {
         UserInfoResponse {
             locked_balance: user_state.locked_balance,
-            locked_until: user_state.locked_until,
             reward_snapshot: user_state.reward_snapshot,
-            withdraw_at: user_state.withdraw_at,
         }
     }
 }
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct PositionResponse {
+    pub id: u64,
+    pub owner: Addr,
+    pub locked_balance: Uint128,
+    pub locked_until: Uint64,
+    pub withdraw_at: Uint64,
+    pub withdraw_amount: Uint128,
+    /// Part of the owner's ve balance
+    pub balance: Uint128,
+}
+
+impl PositionResponse {
+    pub fn from_position(id: u64, position: Position) -> Self {
+        PositionResponse {
+            id,
+            owner: position.owner,
+            locked_balance: position.locked_balance,
+            locked_until: position.locked_until,
+            withdraw_at: position.withdraw_at,
+            withdraw_amount: position.withdraw_amount,
+            balance: position.balance,
+        }
+    }
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct PositionsResponse {
+    pub positions: Vec<PositionResponse>,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct Approval {
+    pub spender: String,
+    pub expires: Expiration,
+}
+
+impl Approval {
+    pub fn from_nft_approval(approval: NftApproval) -> Self {
+        Approval {
+            spender: approval.spender.into_string(),
+            expires: approval.expires,
+        }
+    }
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct OwnerOfResponse {
+    pub owner: String,
+    pub approvals: Vec<Approval>,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct ApprovalResponse {
+    pub approval: Approval,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct ApprovalsResponse {
+    pub approvals: Vec<Approval>,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct OperatorsResponse {
+    pub operators: Vec<Approval>,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct NumTokensResponse {
+    pub count: u64,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct NftContractInfoResponse {
+    pub name: String,
+    pub symbol: String,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct NftInfoResponse {
+    pub token_uri: Option<String>,
+    pub extension: PositionMetadata,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct AllNftInfoResponse {
+    pub access: OwnerOfResponse,
+    pub info: NftInfoResponse,
+}
+
+#[cw_serde(rename_all = "snake_case")]
+pub struct TokensResponse {
+    pub tokens: Vec<String>,
+}
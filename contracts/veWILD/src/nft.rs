@@ -0,0 +1,83 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{ to_binary, Addr, Binary, BlockInfo, CosmosMsg, StdError, StdResult, WasmMsg };
+use cosmwasm_std::{ Storage, Uint128, Uint64 };
+
+use crate::error::ContractError;
+use crate::state::{ Position, NFT_OPERATORS };
+
+/// Sent to the contract receiving a position with SendNft, same as cw721 Cw721ReceiveMsg
+#[cw_serde]
+pub struct Cw721ReceiveMsg {
+    pub sender: String,
+    pub token_id: String,
+    pub msg: Binary,
+}
+
+impl Cw721ReceiveMsg {
+    /// serializes the message
+    pub fn into_binary(self) -> StdResult<Binary> {
+        let msg = ReceiverExecuteMsg::ReceiveNft(self);
+        to_binary(&msg)
+    }
+
+    /// creates a cosmos_msg sending this struct to the named contract
+    pub fn into_cosmos_msg<T: Into<String>>(self, contract_addr: T) -> StdResult<CosmosMsg> {
+        let msg = self.into_binary()?;
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg,
+            funds: vec![],
+        };
+        Ok(execute.into())
+    }
+}
+
+/// The message receivers must accept in their ExecuteMsg
+#[cw_serde]
+enum ReceiverExecuteMsg {
+    ReceiveNft(Cw721ReceiveMsg),
+}
+
+/// NftInfo extension of a position
+#[cw_serde]
+pub struct PositionMetadata {
+    pub locked_balance: Uint128,
+    pub locked_until: Uint64,
+}
+
+/// Token ids are position ids in decimal
+pub fn parse_token_id(token_id: &str) -> StdResult<u64> {
+    token_id.parse().map_err(|_| StdError::parse_err("position id", token_id))
+}
+
+/// Owner or an unexpired operator of the owner
+pub fn assert_can_approve(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    position: &Position,
+    sender: &Addr
+) -> Result<(), ContractError> {
+    if position.owner == *sender {
+        return Ok(());
+    }
+    match NFT_OPERATORS.may_load(storage, (&position.owner, sender))? {
+        Some(expires) if !expires.is_expired(block) => Ok(()),
+        _ => Err(ContractError::Unauthorized {}),
+    }
+}
+
+/// Like assert_can_approve, also accepting unexpired approvals of this position
+pub fn assert_can_send(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    position: &Position,
+    sender: &Addr
+) -> Result<(), ContractError> {
+    let approved = position.approvals
+        .iter()
+        .any(|approval| approval.spender == *sender && !approval.expires.is_expired(block));
+    if approved {
+        return Ok(());
+    }
+    assert_can_approve(storage, block, position, sender)
+}
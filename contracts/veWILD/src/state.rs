@@ -1,8 +1,10 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{ Addr, Uint128, Uint64, Response, Storage };
-use cw_storage_plus::{ Item, Map };
+use cosmwasm_std::{ Addr, Decimal, Uint128, Uint256, Uint64, Response, Storage, Empty, StdResult };
+use cw20::Denom;
+use cw_storage_plus::{ Item, Map, SnapshotItem, SnapshotMap, Strategy };
+use cw_utils::Expiration;
 
-use crate::consts::TOKEN_DECIMALS;
+use crate::consts::{ TOKEN_DECIMALS, MIN_LOCK_PERIOD, MAX_LOCK_PERIOD, WITHDRAW_DELAY };
 use crate::error::ContractError;
 use crate::{ events::* };
 
@@ -10,7 +12,363 @@ use crate::{ events::* };
 // uint8  public constant decimals = 18;
 
 pub const TOKEN_STATE: Item<TokenState> = Item::new("token_state");
+pub const CONFIG: Item<Config> = Item::new("config");
+/// Not set while early withdraw is disabled
+pub const EARLY_WITHDRAW_CONFIG: Item<EarlyWithdrawConfig> = Item::new("early_withdraw_config");
+/// Account totals over its positions and reward snapshot
 pub const USER_STATE: Map<&Addr, UserState> = Map::new("user_state");
+/// position id -> lock, ids are never reused
+pub const POSITIONS: Map<u64, Position> = Map::new("positions");
+/// (owner, position id), for listing the positions of an account
+pub const OWNER_POSITIONS: Map<(&Addr, u64), Empty> = Map::new("owner_positions");
+/// Last position id handed out
+pub const POSITION_COUNT: Item<u64> = Item::new("position_count");
+/// Positions not withdrawn yet, the CW721 token count
+pub const NUM_POSITIONS: Item<u64> = Item::new("num_positions");
+/// (owner, operator) -> expiration, operators may transfer and approve any position of owner
+pub const NFT_OPERATORS: Map<(&Addr, &Addr), Expiration> = Map::new("nft_operators");
+/// (beneficiary, locker) -> what locker may still open with LockFor or transfer to the
+/// beneficiary, removed once used up
+pub const LOCK_FOR_ALLOWANCES: Map<(&Addr, &Addr), LockForAllowanceState> = Map::new(
+    "lock_for_allowances"
+);
+/// Accounts that let anyone compound their rewards
+pub const AUTO_COMPOUND: Map<&Addr, Empty> = Map::new("auto_compound");
+/// (account, operator), operators may claim the account's rewards to the account
+pub const CLAIM_OPERATORS: Map<(&Addr, &Addr), Empty> = Map::new("claim_operators");
+/// Reward assets by RewardAssetState::key
+pub const REWARD_ASSETS: Map<&str, RewardAssetState> = Map::new("reward_assets");
+/// (account, RewardAssetState::key) -> reward_per_token at last claim
+pub const USER_REWARD_SNAPSHOTS: Map<(&Addr, &str), Uint128> = Map::new("user_reward_snapshots");
+/// ve balance history by height, written by set_balance.
+/// Loading at height h gives the value at the start of block h
+pub const BALANCE_SNAPSHOTS: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
+    "balance_snapshots",
+    "balance_snapshots__checkpoints",
+    "balance_snapshots__changelog",
+    Strategy::EveryBlock
+);
+pub const TOTAL_SUPPLY_SNAPSHOTS: SnapshotItem<Uint128> = SnapshotItem::new(
+    "total_supply_snapshots",
+    "total_supply_snapshots__checkpoints",
+    "total_supply_snapshots__changelog",
+    Strategy::EveryBlock
+);
+/// Governance voting power by height, see delegation.rs
+pub const VOTING_POWER: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
+    "voting_power",
+    "voting_power__checkpoints",
+    "voting_power__changelog",
+    Strategy::EveryBlock
+);
+/// delegator -> delegate
+pub const DELEGATIONS: Map<&Addr, Addr> = Map::new("delegations");
+/// (delegate, delegator), for listing the delegators of a delegate
+pub const DELEGATORS: Map<(&Addr, &Addr), Empty> = Map::new("delegators");
+/// Decaying voting power, see checkpoints.rs
+/// timestamp -> sum of all user points at that time
+pub const GLOBAL_POINTS: Map<u64, Point> = Map::new("global_points");
+/// (account, timestamp) -> sum of the account's position points at that time
+pub const USER_POINTS: Map<(&Addr, u64), Point> = Map::new("user_points");
+/// unlock timestamp -> slope that stops decaying there
+pub const SLOPE_CHANGES: Map<u64, Uint256> = Map::new("slope_changes");
+/// (account, unlock timestamp) -> slope of the account's positions that stops decaying there
+pub const USER_SLOPE_CHANGES: Map<(&Addr, u64), Uint256> = Map::new("user_slope_changes");
+/// Contracts called with VeBalanceChangedHookMsg on every ve balance change
+pub const BALANCE_HOOKS: Map<&Addr, HookErrorPolicy> = Map::new("balance_hooks");
+/// RewardAssetState::key -> rewards forfeited by exits after shutdown, until swept
+pub const FORFEITED_REWARDS: Map<&str, ForfeitedReward> = Map::new("forfeited_rewards");
+/// Fee distributor, see fee_distributor.rs
+/// epoch start -> locked token income of that epoch
+pub const EPOCH_FEES: Map<u64, Uint128> = Map::new("epoch_fees");
+/// Epoch income not claimed yet, held back by check_reserves
+pub const UNCLAIMED_EPOCH_FEES: Item<Uint128> = Item::new("unclaimed_epoch_fees");
+/// Epoch start that got income before the epoch began, while it may still start without supply
+pub const FORWARDED_FEE_EPOCH: Item<u64> = Item::new("forwarded_fee_epoch");
+/// account -> first epoch start not claimed yet
+pub const USER_FEE_CURSORS: Map<&Addr, u64> = Map::new("user_fee_cursors");
+/// Gauge controller, see gauges.rs
+pub const GAUGES: Map<&Addr, Empty> = Map::new("gauges");
+/// (account, gauge) -> share of the account's ve balance voted, in basis points
+pub const USER_GAUGE_VOTES: Map<(&Addr, &Addr), u16> = Map::new("user_gauge_votes");
+/// (account, gauge) -> point the vote adds to the gauge weight from the epoch it was cast for
+pub const USER_GAUGE_VOTE_POINTS: Map<(&Addr, &Addr), Point> = Map::new(
+    "user_gauge_vote_points"
+);
+/// (account, gauge, ts) -> drop of the vote slope at ts
+pub const USER_GAUGE_VOTE_SLOPE_CHANGES: Map<(&Addr, &Addr, u64), Uint256> = Map::new(
+    "user_gauge_vote_slope_changes"
+);
+/// (account, gauge) -> time of the last vote, kept after the vote is removed
+pub const USER_GAUGE_LAST_VOTE: Map<(&Addr, &Addr), Uint64> = Map::new("user_gauge_last_vote");
+/// (gauge, epoch start) -> gauge weight point, decaying over GAUGE_SLOPE_CHANGES
+pub const GAUGE_POINTS: Map<(&Addr, u64), Point> = Map::new("gauge_points");
+/// (gauge, ts) -> drop of the gauge weight slope at ts
+pub const GAUGE_SLOPE_CHANGES: Map<(&Addr, u64), Uint256> = Map::new("gauge_slope_changes");
+/// epoch start -> point of the sum of all gauge weights
+pub const TOTAL_GAUGE_POINTS: Map<u64, Point> = Map::new("total_gauge_points");
+/// ts -> drop of the total gauge weight slope at ts
+pub const TOTAL_GAUGE_SLOPE_CHANGES: Map<u64, Uint256> = Map::new("total_gauge_slope_changes");
+pub const OWNERSHIP: Item<Ownership> = Item::new("ownership");
+/// DAO core this contract is the voting module of
+pub const DAO: Item<Addr> = Item::new("dao");
+/// (role, account) => granted
+pub const ROLES: Map<(&str, &Addr), Empty> = Map::new("roles");
+pub const PAUSE_STATE: Item<PauseState> = Item::new("pause_state");
+/// Set once by the owner, never removed
+pub const SHUTDOWN: Item<Shutdown> = Item::new("shutdown");
+
+#[cw_serde]
+pub struct Ownership {
+    pub owner: Option<Addr>,
+    pub pending_owner: Option<Addr>,
+}
+
+impl Ownership {
+    pub fn new(owner: Addr) -> Self {
+        Self {
+            owner: Some(owner),
+            pending_owner: None,
+        }
+    }
+
+    /// Fails with Unauthorized unless sender is the current owner
+    pub fn assert_owner(&self, sender: &Addr) -> Result<(), ContractError> {
+        match &self.owner {
+            Some(owner) if owner == sender => Ok(()),
+            _ => Err(ContractError::Unauthorized {}),
+        }
+    }
+}
+
+#[cw_serde]
+#[derive(Copy)]
+pub enum HookErrorPolicy {
+    /// A failing hook reverts the balance change, and with it the lock, claim or withdraw.
+    /// Caught like Catch after emergency shutdown
+    Revert,
+    /// A failing hook is caught through reply, the balance change goes through
+    Catch,
+}
+
+impl HookErrorPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HookErrorPolicy::Revert => "revert",
+            HookErrorPolicy::Catch => "catch",
+        }
+    }
+}
+
+#[cw_serde]
+#[derive(Copy)]
+pub enum Role {
+    /// Can call AddIncome
+    IncomeManager,
+    /// Can pause and unpause contract actions
+    Pauser,
+    /// Can change distribution period and other contract parameters
+    ConfigAdmin,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::IncomeManager => "income_manager",
+            Role::Pauser => "pauser",
+            Role::ConfigAdmin => "config_admin",
+        }
+    }
+
+    pub fn is_granted(&self, storage: &dyn Storage, account: &Addr) -> bool {
+        ROLES.has(storage, (self.as_str(), account))
+    }
+}
+
+#[cw_serde]
+pub struct Config {
+    /// seconds
+    pub min_lock_period: Uint64,
+    /// seconds, lock of that length gives 1 veToken per locked token
+    pub max_lock_period: Uint64,
+    /// seconds between RequestWithdraw and Withdraw
+    pub withdraw_delay: Uint64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            min_lock_period: Uint64::from(MIN_LOCK_PERIOD),
+            max_lock_period: Uint64::from(MAX_LOCK_PERIOD),
+            withdraw_delay: Uint64::from(WITHDRAW_DELAY),
+        }
+    }
+}
+
+impl Config {
+    pub fn validate(&self) -> Result<(), ContractError> {
+        if self.max_lock_period.is_zero() || self.min_lock_period > self.max_lock_period {
+            return Err(ContractError::InvalidLockPeriods {});
+        }
+        Ok(())
+    }
+}
+
+/// Where early withdraw penalties go
+#[cw_serde]
+pub enum PenaltyDestination {
+    /// Added to the reward stream like AddIncome
+    Lockers,
+    Treasury {
+        address: Addr,
+    },
+}
+
+impl PenaltyDestination {
+    pub fn as_str(&self) -> &str {
+        match self {
+            PenaltyDestination::Lockers => "lockers",
+            PenaltyDestination::Treasury { address } => address.as_str(),
+        }
+    }
+}
+
+#[cw_serde]
+pub struct EarlyWithdrawConfig {
+    /// share of the position taken with max_lock_period left, linearly less with less time left
+    pub penalty_rate: Decimal,
+    /// share of the position taken however little time is left
+    pub penalty_floor: Decimal,
+    pub penalty_destination: PenaltyDestination,
+}
+
+impl EarlyWithdrawConfig {
+    pub fn validate(&self) -> Result<(), ContractError> {
+        if self.penalty_rate > Decimal::one() || self.penalty_floor > Decimal::one() {
+            return Err(ContractError::InvalidPenalty {});
+        }
+        Ok(())
+    }
+
+    /// Penalty for withdrawing locked_balance with remaining seconds of lock left
+    pub fn penalty(
+        &self,
+        locked_balance: Uint128,
+        remaining: Uint64,
+        max_lock_period: Uint64
+    ) -> Uint128 {
+        let remaining_share = Decimal::from_ratio(remaining.min(max_lock_period), max_lock_period);
+        locked_balance * (self.penalty_rate * remaining_share).max(self.penalty_floor)
+    }
+}
+
+/// Actions that can be paused one by one.
+/// Unlocked funds can always be requested and withdrawn, so only exits of funds still
+/// locked can be paused.
+#[cw_serde]
+#[derive(Copy)]
+pub enum PausableAction {
+    Lock,
+    Claim,
+    EarlyWithdraw,
+    AddIncome,
+}
+
+impl PausableAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PausableAction::Lock => "lock",
+            PausableAction::Claim => "claim",
+            PausableAction::EarlyWithdraw => "early_withdraw",
+            PausableAction::AddIncome => "add_income",
+        }
+    }
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct PauseState {
+    pub lock: bool,
+    pub claim: bool,
+    pub early_withdraw: bool,
+    pub add_income: bool,
+}
+
+impl PauseState {
+    /// Nothing is paused until a pauser says otherwise
+    pub fn load(storage: &dyn Storage) -> StdResult<Self> {
+        Ok(PAUSE_STATE.may_load(storage)?.unwrap_or_default())
+    }
+
+    fn flag_mut(&mut self, action: PausableAction) -> &mut bool {
+        match action {
+            PausableAction::Lock => &mut self.lock,
+            PausableAction::Claim => &mut self.claim,
+            PausableAction::EarlyWithdraw => &mut self.early_withdraw,
+            PausableAction::AddIncome => &mut self.add_income,
+        }
+    }
+
+    pub fn set(&mut self, action: PausableAction, paused: bool) {
+        *self.flag_mut(action) = paused;
+    }
+
+    pub fn is_paused(&self, action: PausableAction) -> bool {
+        match action {
+            PausableAction::Lock => self.lock,
+            PausableAction::Claim => self.claim,
+            PausableAction::EarlyWithdraw => self.early_withdraw,
+            PausableAction::AddIncome => self.add_income,
+        }
+    }
+
+    pub fn assert_not_paused(&self, action: PausableAction) -> Result<(), ContractError> {
+        if self.is_paused(action) {
+            return Err(ContractError::Paused { action: action.as_str().to_string() });
+        }
+        Ok(())
+    }
+}
+
+/// What happens to pending rewards of accounts exiting after emergency shutdown
+#[cw_serde]
+#[derive(Copy)]
+pub enum ShutdownRewardPolicy {
+    /// Pending rewards are paid out together with the locked tokens
+    Settle,
+    /// Pending rewards and unclaimed epoch fees stay in the contract, for the owner to sweep
+    Forfeit,
+}
+
+impl ShutdownRewardPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ShutdownRewardPolicy::Settle => "settle",
+            ShutdownRewardPolicy::Forfeit => "forfeit",
+        }
+    }
+}
+
+#[cw_serde]
+pub struct ForfeitedReward {
+    pub asset: Denom,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct Shutdown {
+    pub activated_at: Uint64,
+    pub reward_policy: ShutdownRewardPolicy,
+}
+
+impl Shutdown {
+    pub fn assert_not_shutdown(storage: &dyn Storage) -> Result<(), ContractError> {
+        if SHUTDOWN.may_load(storage)?.is_some() {
+            return Err(ContractError::Shutdown {});
+        }
+        Ok(())
+    }
+}
 
 #[cw_serde]
 pub struct TokenState {
@@ -19,7 +377,7 @@ pub struct TokenState {
     pub distribution_period: Uint64,
 
     // utility values
-    pub locked_token: Addr, // address of the token contract
+    pub locked_token: Denom, // CW20 contract or native denom
     pub last_accrue_block: Uint64,
     pub last_income_block: Uint64,
     pub reward_per_token: Uint128,
@@ -38,7 +396,7 @@ impl TokenState {
             total_supply: Uint128::zero(),
             total_locked: Uint128::zero(),
             distribution_period: Uint64::zero(),
-            locked_token: Addr::unchecked(""),
+            locked_token: Denom::Cw20(Addr::unchecked("")),
             last_accrue_block: Uint64::zero(),
             last_income_block: Uint64::zero(),
             reward_per_token: Uint128::zero(),
@@ -160,14 +518,172 @@ impl TokenState {
     }
 }
 
+/// Additional reward stream paid to ve holders next to the locked token income.
+/// Vests per block like TokenState, over the same ve total_supply
+#[cw_serde]
+pub struct RewardAssetState {
+    pub asset: Denom,
+    pub distribution_period: Uint64,
+    pub last_accrue_block: Uint64,
+    pub last_income_block: Uint64,
+    pub reward_per_token: Uint128,
+    pub reward_rate_stored: Uint128,
+}
+
+impl RewardAssetState {
+    pub fn new(asset: Denom, distribution_period: Uint64, current_block: Uint64) -> Self {
+        Self {
+            asset,
+            distribution_period,
+            last_accrue_block: current_block,
+            last_income_block: current_block,
+            reward_per_token: Uint128::zero(),
+            reward_rate_stored: Uint128::zero(),
+        }
+    }
+
+    /// Storage key of the asset in REWARD_ASSETS and USER_REWARD_SNAPSHOTS
+    pub fn key(asset: &Denom) -> String {
+        match asset {
+            Denom::Cw20(addr) => format!("cw20:{}", addr),
+            Denom::Native(denom) => format!("native:{}", denom),
+        }
+    }
+
+    pub fn reward_rate(&self, current_block: Uint64) -> Uint128 {
+        let blocks_elapsed: Uint64 = current_block - self.last_income_block;
+        if blocks_elapsed < self.distribution_period {
+            self.reward_rate_stored
+        } else {
+            Uint128::zero()
+        }
+    }
+
+    /// reward_rate_stored(or 0) * blocks_since_last_accrue / total_supply
+    pub fn pending_reward_per_token(&self, total_supply: Uint128, current_block: Uint64) -> Uint128 {
+        if total_supply.is_zero() {
+            return Uint128::zero();
+        }
+
+        let blocks_since_last_accrue = Uint128::from(current_block - self.last_accrue_block);
+        let reward_per_token =
+            (self.reward_rate(current_block) * blocks_since_last_accrue) / total_supply;
+
+        reward_per_token * Uint128::from(10u8).pow(TOKEN_DECIMALS)
+    }
+
+    /// Caller saves the state
+    pub fn accrue(&mut self, total_supply: Uint128, current_block: Uint64) {
+        self.reward_per_token += self.pending_reward_per_token(total_supply, current_block);
+        self.last_accrue_block = current_block;
+    }
+
+    /// reward_rate_stored * blocks left in the current distribution period
+    pub fn unvested_income(&self, current_block: Uint64) -> Uint128 {
+        let blocks_elapsed = self.distribution_period.min(current_block - self.last_income_block);
+        self.reward_rate_stored * Uint128::from(self.distribution_period - blocks_elapsed)
+    }
+
+    /// Same as TokenState::update_reward_rate, keeps the distribution period.
+    /// Caller saves the state
+    pub fn update_reward_rate(
+        &mut self,
+        add_amount: Uint128,
+        current_block: Uint64
+    ) -> Result<Uint128, ContractError> {
+        if !current_block.eq(&self.last_accrue_block) {
+            return Result::Err(ContractError::AccrueFirst {});
+        }
+        let unvested_income = self.unvested_income(current_block);
+
+        self.reward_rate_stored =
+            (unvested_income + add_amount) / Uint128::from(self.distribution_period);
+        self.last_income_block = current_block;
+
+        Ok(unvested_income)
+    }
+}
+
+/// Voting power line: bias at ts, decreasing by slope every second until it reaches zero.
+/// Both are scaled up by POINT_PRECISION
 #[cw_serde]
 #[derive(Default)]
-pub struct UserState {
-    pub balance: Uint128, // veBalance
-    pub locked_balance: Uint128, // locked
+pub struct Point {
+    pub bias: Uint256,
+    pub slope: Uint256,
+    pub ts: Uint64,
+}
+
+impl Point {
+    /// Voting power at a later timestamp
+    pub fn bias_at(&self, ts: Uint64) -> Uint256 {
+        let elapsed = Uint256::from(ts.saturating_sub(self.ts).u64());
+        self.bias.saturating_sub(self.slope.saturating_mul(elapsed))
+    }
+
+    /// Unlock time of a position point, bias is always a multiple of slope
+    pub fn end(&self) -> Uint64 {
+        if self.slope.is_zero() {
+            return self.ts;
+        }
+        // at most max_lock_period
+        let lock_seconds = Uint128::try_from(self.bias / self.slope).unwrap_or_default();
+        self.ts + Uint64::from(lock_seconds.u128() as u64)
+    }
+}
+
+/// One lock of an account, also a CW721 token with the id as token id.
+/// Rewards are paid per account, on the sum of the position balances
+#[cw_serde]
+pub struct Position {
+    pub owner: Addr,
+    pub locked_balance: Uint128,
     pub locked_until: Uint64,
-    pub reward_snapshot: Uint128,
     pub withdraw_at: Uint64,
+    /// Part of locked_balance requested by RequestWithdraw, zero without a pending request
+    pub withdraw_amount: Uint128,
+    /// ve balance this position adds to its owner's
+    pub balance: Uint128,
+    /// Last voting power checkpoint, see checkpoints.rs
+    pub point: Point,
+    /// Spenders allowed to transfer this position, cleared on transfer
+    pub approvals: Vec<NftApproval>,
+}
+
+impl Position {
+    pub fn new(owner: Addr) -> Self {
+        Self {
+            owner,
+            locked_balance: Uint128::zero(),
+            locked_until: Uint64::zero(),
+            withdraw_at: Uint64::zero(),
+            withdraw_amount: Uint128::zero(),
+            balance: Uint128::zero(),
+            point: Point::default(),
+            approvals: vec![],
+        }
+    }
+}
+
+#[cw_serde]
+pub struct LockForAllowanceState {
+    pub max_locked_until: Uint64,
+    /// Positions the locker may still give, each LockFor or transfer uses one
+    pub positions: u32,
+}
+
+#[cw_serde]
+pub struct NftApproval {
+    pub spender: Addr,
+    pub expires: Expiration,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct UserState {
+    pub balance: Uint128, // veBalance, sum over positions
+    pub locked_balance: Uint128, // locked, sum over positions
+    pub reward_snapshot: Uint128,
 }
 
 impl UserState {
@@ -175,9 +691,7 @@ impl UserState {
         Self {
             balance: Uint128::zero(),
             locked_balance: Uint128::zero(),
-            locked_until: Uint64::zero(),
             reward_snapshot: Uint128::zero(),
-            withdraw_at: Uint64::zero(),
         }
     }
 
@@ -185,7 +699,16 @@ impl UserState {
     /// This allows the user to see if they have any pending rewards.
     /// (pending_reward_per_token - reward_snapshot) * balance / 10^TOKEN_DECIMALS
     pub fn pending_reward(&self, pending_reward_per_token: Uint128) -> Uint128 {
-        let reward_per_token_delta = pending_reward_per_token - self.reward_snapshot;
+        self.pending_asset_reward(pending_reward_per_token, self.reward_snapshot)
+    }
+
+    /// pending_reward of a reward asset, whose snapshot is kept in USER_REWARD_SNAPSHOTS
+    pub fn pending_asset_reward(
+        &self,
+        pending_reward_per_token: Uint128,
+        reward_snapshot: Uint128
+    ) -> Uint128 {
+        let reward_per_token_delta = pending_reward_per_token - reward_snapshot;
         let pending_reward = reward_per_token_delta * self.balance;
         pending_reward / Uint128::from(10u8).pow(TOKEN_DECIMALS as u32)
     }
@@ -209,6 +732,25 @@ mod state_tests {
         );
     }
 
+    #[test]
+    fn test_early_withdraw_penalty() {
+        let config = EarlyWithdrawConfig {
+            penalty_rate: Decimal::percent(50),
+            penalty_floor: Decimal::percent(10),
+            penalty_destination: PenaltyDestination::Lockers,
+        };
+        let locked_balance = Uint128::from(1000u128);
+        let max_lock_period = Uint64::from(400u64);
+
+        let penalty = |remaining: u64| {
+            config.penalty(locked_balance, Uint64::from(remaining), max_lock_period)
+        };
+        assert_eq!(penalty(400), Uint128::from(500u128));
+        assert_eq!(penalty(200), Uint128::from(250u128));
+        assert_eq!(penalty(40), Uint128::from(100u128));
+        assert_eq!(penalty(1), Uint128::from(100u128));
+    }
+
     #[test]
     fn test_set_distribution_period() {
         let mut binding = mock_dependencies();
@@ -341,7 +883,7 @@ mod state_tests {
                 total_supply: Uint128::from(1000u128),
                 total_locked: Uint128::from(1000u128),
                 distribution_period: Uint64::from(10u64),
-                locked_token: Addr::unchecked(""),
+                locked_token: Denom::Cw20(Addr::unchecked("")),
                 last_accrue_block: Uint64::from(100u64),
                 last_income_block: Uint64::from(100u64),
                 reward_per_token: Uint128::from(1u128),
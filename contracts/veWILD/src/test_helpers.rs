@@ -1,12 +1,15 @@
 use crate::consts::TOKEN_DECIMALS;
 use crate::contract::*;
+use crate::internal::internal_funcs;
 use crate::msg::*;
+use crate::state::POSITIONS;
 use cosmwasm_std::ContractResult;
 use cosmwasm_std::DepsMut;
 use cosmwasm_std::Env;
 use cosmwasm_std::MessageInfo;
 use cosmwasm_std::QuerierResult;
 use cosmwasm_std::SystemError;
+use cosmwasm_std::Storage;
 use cosmwasm_std::SystemResult;
 use cosmwasm_std::Uint128;
 use cosmwasm_std::WasmQuery;
@@ -15,23 +18,53 @@ use cosmwasm_std::to_binary;
 use cosmwasm_std::{ Addr, Uint64 };
 use cw20::BalanceResponse;
 use cw20::Cw20QueryMsg;
+use cw20::Cw20ReceiveMsg;
+use cw20::Denom;
 
 pub const MOCK_LOCKED_TOKEN: &str = "cw20";
+pub const MOCK_LOCKED_DENOM: &str = "factory/creator/wild";
 
 pub fn mock_instantiate(deps: DepsMut, env: Env, info: MessageInfo) {
+    mock_instantiate_with_token(deps, env, info, Denom::Cw20(Addr::unchecked(MOCK_LOCKED_TOKEN)));
+}
+
+/// Instantiates with MOCK_LOCKED_DENOM, balances come from the bank querier
+pub fn mock_instantiate_native(deps: DepsMut, env: Env, info: MessageInfo) {
+    mock_instantiate_with_token(deps, env, info, Denom::Native(MOCK_LOCKED_DENOM.to_string()));
+}
+
+fn mock_instantiate_with_token(deps: DepsMut, env: Env, info: MessageInfo, locked_token: Denom) {
     instantiate(deps, env, info, InstantiateMsg {
-        locked_token: Addr::unchecked(MOCK_LOCKED_TOKEN),
+        locked_token,
         distribution_period: Uint64::from(1000 as u16),
+        min_lock_period: None,
+        max_lock_period: None,
+        withdraw_delay: None,
+        dao: None,
     }).unwrap();
 }
 
 pub fn cw20_mock_querier(contract_balance: Uint128) -> Box<dyn Fn(&WasmQuery) -> QuerierResult> {
-    let expected_address = String::from(MOCK_LOCKED_TOKEN);
+    cw20_balances_mock_querier(vec![(MOCK_LOCKED_TOKEN, contract_balance)])
+}
+
+/// Contract balance of every listed CW20 token
+pub fn cw20_balances_mock_querier(
+    contract_balances: Vec<(&str, Uint128)>
+) -> Box<dyn Fn(&WasmQuery) -> QuerierResult> {
+    let contract_balances: Vec<(String, Uint128)> = contract_balances
+        .into_iter()
+        .map(|(token, balance)| (token.to_string(), balance))
+        .collect();
     Box::new(move |request| -> QuerierResult {
         match request {
             WasmQuery::Smart { contract_addr, msg } => {
-                match contract_addr {
-                    _ if contract_addr.eq(&expected_address) => {
+                let token_balance = contract_balances
+                    .iter()
+                    .find(|(token, _)| token.eq(contract_addr))
+                    .map(|(_, balance)| *balance);
+                match token_balance {
+                    Some(contract_balance) => {
                         let balance_msg_res = from_binary(&msg);
                         match balance_msg_res {
                             Ok(Cw20QueryMsg::Balance { address: _ }) => {
@@ -39,7 +72,7 @@ pub fn cw20_mock_querier(contract_balance: Uint128) -> Box<dyn Fn(&WasmQuery) ->
                                     ContractResult::Ok(
                                         to_binary(
                                             &(BalanceResponse {
-                                                balance: contract_balance.to_owned(),
+                                                balance: contract_balance,
                                             })
                                         ).unwrap()
                                     )
@@ -84,6 +117,30 @@ pub fn cw20_mock_querier(contract_balance: Uint128) -> Box<dyn Fn(&WasmQuery) ->
     })
 }
 
+/// Execute message of a CW20 token after sender called Send
+pub fn mock_cw20_receive(sender: &str, amount: Uint128, msg: ReceiveMsg) -> ExecuteMsg {
+    ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: sender.to_string(),
+        amount,
+        msg: to_binary(&msg).unwrap(),
+    })
+}
+
+/// Opens a position of owner holding locked_balance, without minting ve or checkpointing
+pub fn mock_position(
+    storage: &mut dyn Storage,
+    owner: &Addr,
+    locked_balance: Uint128,
+    locked_until: Uint64
+) -> u64 {
+    let id = internal_funcs::create_position(storage, owner).unwrap();
+    let mut position = POSITIONS.load(storage, id).unwrap();
+    position.locked_balance = locked_balance;
+    position.locked_until = locked_until;
+    POSITIONS.save(storage, id, &position).unwrap();
+    id
+}
+
 pub fn apply_decimals(amount: Uint128) -> Uint128 {
     amount * Uint128::new(10).pow(TOKEN_DECIMALS)
 }
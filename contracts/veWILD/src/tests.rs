@@ -5,18 +5,43 @@ mod contract_tests {
     use cosmwasm_std::{
         Addr,
         attr,
+        BankMsg,
+        Binary,
+        Decimal,
+        Deps,
+        Env,
+        coins,
         CosmosMsg,
         Empty,
+        Reply,
         StdResult,
+        SubMsg,
+        SubMsgResult,
+        from_binary,
         to_binary,
         Uint128,
+        Uint256,
         Uint64,
         WasmMsg,
         testing::{ mock_dependencies, mock_env, mock_info },
     };
-    use cw20::Cw20ExecuteMsg;
-    use cw20_base::{ state::{ TOKEN_INFO, TokenInfo, MinterData } };
-    use crate::{ state::*, consts::*, msg::*, events::*, error::*, test_helpers::*, * };
+    use cw20::{ Cw20ExecuteMsg, Denom };
+    use cw_utils::{ Expiration, PaymentError };
+    use cw20_base::{ contract::query_balance, state::{ TOKEN_INFO, TokenInfo, MinterData } };
+    use crate::{
+        state::*,
+        consts::*,
+        msg::*,
+        events::*,
+        error::*,
+        hooks,
+        gauges,
+        fee_distributor,
+        checkpoints,
+        nft,
+        test_helpers::*,
+        *,
+    };
 
     #[test]
     fn proper_instantiation() {
@@ -28,8 +53,12 @@ mod contract_tests {
             env.to_owned(),
             mock_info("creator", &[]),
             InstantiateMsg {
-                locked_token: Addr::unchecked(MOCK_LOCKED_TOKEN),
+                locked_token: Denom::Cw20(Addr::unchecked(MOCK_LOCKED_TOKEN)),
                 distribution_period: Uint64::from(1000 as u16),
+                min_lock_period: None,
+                max_lock_period: None,
+                withdraw_delay: None,
+                dao: None,
             }
         ).unwrap();
 
@@ -37,7 +66,7 @@ mod contract_tests {
         let token_state = TOKEN_STATE.load(deps_binding.as_ref().storage).unwrap();
 
         let mut expected_token_state = TokenState::default();
-        expected_token_state.locked_token = Addr::unchecked(MOCK_LOCKED_TOKEN);
+        expected_token_state.locked_token = Denom::Cw20(Addr::unchecked(MOCK_LOCKED_TOKEN));
         expected_token_state.distribution_period = Uint64::from(1000 as u16);
         expected_token_state.last_accrue_block = Uint64::from(env.block.height);
         expected_token_state.last_income_block = Uint64::from(env.block.height);
@@ -58,6 +87,23 @@ mod contract_tests {
         let token_info = TOKEN_INFO.load(deps_binding.as_ref().storage).unwrap();
         assert_eq!(expected_token_info, token_info);
 
+        // Test ownership
+        let ownership = OWNERSHIP.load(deps_binding.as_ref().storage).unwrap();
+        assert_eq!(Ownership::new(Addr::unchecked("creator")), ownership);
+
+        // DAO defaults to the instantiator
+        let dao = DAO.load(deps_binding.as_ref().storage).unwrap();
+        assert_eq!(Addr::unchecked("creator"), dao);
+
+        // Test config
+        let config = CONFIG.load(deps_binding.as_ref().storage).unwrap();
+        let expected_config = Config {
+            min_lock_period: Uint64::from(MIN_LOCK_PERIOD),
+            max_lock_period: Uint64::from(MAX_LOCK_PERIOD),
+            withdraw_delay: Uint64::from(WITHDRAW_DELAY),
+        };
+        assert_eq!(expected_config, config);
+
         let expected_response: Response<Empty> = Response::new().add_event(
             ContractEvent::make_new_distribution_period(Uint64::from(1000 as u16)).to_cosmos_event()
         );
@@ -65,6 +111,38 @@ mod contract_tests {
         assert_eq!(expected_response, resp);
     }
 
+    #[test]
+    fn instantiation_with_config() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let mut msg = InstantiateMsg {
+            locked_token: Denom::Cw20(Addr::unchecked(MOCK_LOCKED_TOKEN)),
+            distribution_period: Uint64::from(1000u16),
+            min_lock_period: Some(Uint64::from(60u64)),
+            max_lock_period: Some(Uint64::from(3600u64)),
+            withdraw_delay: Some(Uint64::zero()),
+            dao: Some("dao".to_string()),
+        };
+
+        instantiate(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg.clone()).unwrap();
+
+        let expected_config = Config {
+            min_lock_period: Uint64::from(60u64),
+            max_lock_period: Uint64::from(3600u64),
+            withdraw_delay: Uint64::zero(),
+        };
+        assert_eq!(expected_config, CONFIG.load(deps.as_ref().storage).unwrap());
+        assert_eq!(Addr::unchecked("dao"), DAO.load(deps.as_ref().storage).unwrap());
+
+        // min lock period above max lock period
+        msg.min_lock_period = Some(Uint64::from(3601u64));
+
+        let mut deps = mock_dependencies();
+        let err = instantiate(deps.as_mut(), env, mock_info("creator", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidLockPeriods {});
+    }
+
     #[cfg(test)]
     mod execute_tests {
         use super::*;
@@ -83,9 +161,7 @@ mod contract_tests {
             let initial_user_state = UserState {
                 locked_balance: initial_locked.clone(),
                 reward_snapshot: Uint128::zero(),
-                locked_until: Uint64::from(env.block.time.seconds() + MIN_LOCK_PERIOD),
                 balance: Uint128::zero(),
-                withdraw_at: Uint64::zero(),
             };
 
             USER_STATE.save(
@@ -93,6 +169,12 @@ mod contract_tests {
                 &Addr::unchecked(user_addr.clone()),
                 &initial_user_state
             ).unwrap();
+            let position_id = mock_position(
+                deps.as_mut().storage,
+                &user_addr,
+                initial_locked,
+                Uint64::from(env.block.time.seconds() + MIN_LOCK_PERIOD)
+            );
 
             internal_funcs
                 ::set_balance(deps.as_mut(), &env, &info, &user_addr, initial_locked.clone())
@@ -109,17 +191,16 @@ mod contract_tests {
             ).unwrap();
 
             let amount = apply_decimals(Uint128::from(1u8));
-            let lock_period = Uint64::from(MIN_LOCK_PERIOD * 2);
-            let new_locked_until = Uint64::from(env.block.time.seconds() + lock_period.u64());
 
-            let msg = ExecuteMsg::Lock {
-                amount: amount.clone(),
-                new_locked_until: new_locked_until,
-            };
+            let msg = mock_cw20_receive(
+                user_addr.as_str(),
+                amount.clone(),
+                ReceiveMsg::IncreaseAmount { position: position_id }
+            );
 
             deps.querier.update_wasm(cw20_mock_querier(amount.clone()));
 
-            let info = mock_info(user_addr.as_str(), &[]);
+            let info = mock_info(MOCK_LOCKED_TOKEN, &[]);
             let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
             assert_eq!(err, ContractError::InsufficientReserves {});
         }
@@ -138,9 +219,7 @@ mod contract_tests {
             let initial_user_state = UserState {
                 locked_balance: initial_locked.clone(),
                 reward_snapshot: Uint128::zero(),
-                locked_until: Uint64::from(env.block.time.seconds() + MIN_LOCK_PERIOD),
                 balance: Uint128::zero(),
-                withdraw_at: Uint64::zero(),
             };
 
             USER_STATE.save(
@@ -148,6 +227,12 @@ mod contract_tests {
                 &Addr::unchecked(user_addr.clone()),
                 &initial_user_state
             ).unwrap();
+            let position_id = mock_position(
+                deps.as_mut().storage,
+                &user_addr,
+                initial_locked,
+                Uint64::from(env.block.time.seconds() + MIN_LOCK_PERIOD)
+            );
 
             internal_funcs
                 ::set_balance(deps.as_mut(), &env, &info, &user_addr, initial_locked.clone())
@@ -166,13 +251,12 @@ mod contract_tests {
             let token_state = TOKEN_STATE.load(deps.as_ref().storage).unwrap();
 
             let amount = apply_decimals(Uint128::from(1u8));
-            let lock_period = Uint64::from(MIN_LOCK_PERIOD * 2);
-            let new_locked_until = Uint64::from(env.block.time.seconds() + lock_period.u64());
 
-            let msg = ExecuteMsg::Lock {
-                amount: amount.clone(),
-                new_locked_until: new_locked_until,
-            };
+            let msg = mock_cw20_receive(
+                user_addr.as_str(),
+                amount.clone(),
+                ReceiveMsg::IncreaseAmount { position: position_id }
+            );
 
             let expected_unvested_income =
                 token_state.reward_per_token * Uint128::from(token_state.distribution_period);
@@ -184,11 +268,16 @@ mod contract_tests {
             );
 
             let info = mock_info(user_addr.as_str(), &[]);
-            let resp = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+            let resp = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(MOCK_LOCKED_TOKEN, &[]),
+                msg
+            ).unwrap();
 
             // TODO: test states
 
-            assert_eq!(resp.messages.len(), 2);
+            assert_eq!(resp.messages.len(), 1);
 
             let expected_claim_amount =
                 (token_state.reward_per_token * initial_locked.clone()) /
@@ -207,25 +296,13 @@ mod contract_tests {
 
             assert_eq!(resp.messages[0].msg, expected_transfer_message);
 
-            let expected_transfer_from_message = CosmosMsg::Wasm(cosmwasm_std::WasmMsg::Execute {
-                contract_addr: String::from(MOCK_LOCKED_TOKEN),
-                msg: to_binary(
-                    &(Cw20ExecuteMsg::TransferFrom {
-                        recipient: env.contract.address.to_string(),
-                        amount: amount.clone(),
-                        owner: info.sender.to_string(),
-                    })
-                ).unwrap(),
-                funds: vec![],
-            });
-
             let expected_balance_on_claim =
                 (initial_user_state.locked_balance * Uint128::from(MIN_LOCK_PERIOD)) /
                 Uint128::from(MAX_LOCK_PERIOD);
 
+            // The unlock time is kept
             let expected_balance_at_the_end =
-                ((amount + initial_user_state.locked_balance) *
-                    Uint128::from(lock_period.clone())) /
+                ((amount + initial_user_state.locked_balance) * Uint128::from(MIN_LOCK_PERIOD)) /
                 Uint128::from(MAX_LOCK_PERIOD);
 
             let initial_balance = initial_locked.clone();
@@ -233,19 +310,21 @@ mod contract_tests {
             let expected_mint_amount = expected_balance_at_the_end - expected_balance_on_claim;
 
             let expected_response: Response<Empty> = Response::new()
-                .add_messages(vec![expected_transfer_message, expected_transfer_from_message])
+                .add_messages(vec![expected_transfer_message])
                 .add_events(
                     vec![
                         ContractEvent::make_claim(
+                            info.sender.to_string(),
                             info.sender.to_string(),
                             expected_claim_amount.clone(),
                             expected_balance_on_claim.clone()
                         ).to_cosmos_event(),
-                        ContractEvent::make_lock(
+                        ContractEvent::make_increase_amount(
                             info.sender.to_string(),
-                            amount.clone() + initial_user_state.locked_balance,
-                            expected_balance_at_the_end.clone(),
-                            new_locked_until.clone()
+                            position_id,
+                            amount,
+                            amount + initial_user_state.locked_balance,
+                            expected_balance_at_the_end
                         ).to_cosmos_event()
                     ]
                 )
@@ -275,39 +354,32 @@ mod contract_tests {
             let lock_period = Uint64::from(MIN_LOCK_PERIOD * 2);
             let new_locked_until = Uint64::from(env.block.time.seconds() + lock_period.u64());
 
-            let msg = ExecuteMsg::Lock {
-                amount: amount.clone(),
+            let msg = mock_cw20_receive("user", amount.clone(), ReceiveMsg::Lock {
                 new_locked_until: new_locked_until,
-            };
+            });
 
             deps.querier.update_wasm(cw20_mock_querier(amount.clone()));
 
             let info = mock_info("user", &[]);
-            let resp = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+            let resp = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(MOCK_LOCKED_TOKEN, &[]),
+                msg
+            ).unwrap();
 
             let expected_balance =
                 (amount * Uint128::from(lock_period.clone())) / Uint128::from(MAX_LOCK_PERIOD);
 
-            assert_eq!(resp.messages.len(), 1);
-
-            let expected_message = CosmosMsg::Wasm(cosmwasm_std::WasmMsg::Execute {
-                contract_addr: String::from(MOCK_LOCKED_TOKEN),
-                msg: to_binary(
-                    &(Cw20ExecuteMsg::TransferFrom {
-                        recipient: env.contract.address.to_string(),
-                        amount: amount.clone(),
-                        owner: info.sender.to_string(),
-                    })
-                ).unwrap(),
-                funds: vec![],
-            });
+            // Tokens are already received, nothing to transfer
+            assert_eq!(resp.messages.len(), 0);
 
             let expected_response: Response<Empty> = Response::new()
-                .add_messages(vec![expected_message])
                 .add_events(
                     vec![
                         ContractEvent::make_lock(
                             info.sender.to_string(),
+                            1,
                             amount.clone(),
                             expected_balance.clone(),
                             new_locked_until.clone()
@@ -325,6 +397,223 @@ mod contract_tests {
             assert_eq!(resp, expected_response);
         }
 
+        #[test]
+        fn test_execute_lock_multiple_positions() {
+            let mut deps = mock_dependencies();
+            let mut env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate_native(deps.as_mut(), env.clone(), info.to_owned());
+
+            let user_addr = Addr::unchecked("user");
+            let amount = apply_decimals(Uint128::from(1u8));
+            deps.querier.update_balance(
+                env.contract.address.clone(),
+                coins(amount.u128() * 100, MOCK_LOCKED_DENOM)
+            );
+            let user_funds = mock_info(user_addr.as_str(), &coins(amount.u128(), MOCK_LOCKED_DENOM));
+
+            // 1. Every lock without position opens a new one
+            for lock_period in [MAX_LOCK_PERIOD, MAX_LOCK_PERIOD / 2] {
+                let msg = ExecuteMsg::Lock {
+                    amount,
+                    new_locked_until: Uint64::from(env.block.time.seconds() + lock_period),
+                };
+                execute(deps.as_mut(), env.clone(), user_funds.clone(), msg).unwrap();
+            }
+
+            let user_state = USER_STATE.load(deps.as_ref().storage, &user_addr).unwrap();
+            assert_eq!(user_state.locked_balance, amount * Uint128::from(2u8));
+            assert_eq!(user_state.balance, amount + amount / Uint128::from(2u8));
+
+            // 2. Positions are paginated by id
+            let query_positions = |deps: Deps, start_after: Option<u64>| {
+                let msg = QueryMsg::Positions {
+                    owner: user_addr.to_string(),
+                    start_after,
+                    limit: Some(1),
+                };
+                let resp: PositionsResponse = from_binary(
+                    &query(deps, mock_env(), msg).unwrap()
+                ).unwrap();
+                resp.positions
+            };
+            let positions = query_positions(deps.as_ref(), None);
+            assert_eq!(positions, vec![PositionResponse {
+                id: 1,
+                owner: user_addr.clone(),
+                locked_balance: amount,
+                locked_until: Uint64::from(env.block.time.seconds() + MAX_LOCK_PERIOD),
+                withdraw_at: Uint64::zero(),
+                withdraw_amount: Uint128::zero(),
+                balance: amount,
+            }]);
+            let positions = query_positions(deps.as_ref(), Some(1));
+            assert_eq!(positions.len(), 1);
+            assert_eq!(positions[0].id, 2);
+            assert_eq!(positions[0].balance, amount / Uint128::from(2u8));
+            assert!(query_positions(deps.as_ref(), Some(2)).is_empty());
+
+            // 3. The shorter position is withdrawn alone
+            env.block.time = env.block.time.plus_seconds(MAX_LOCK_PERIOD / 2);
+            env.block.height += 500;
+            let user_info = mock_info(user_addr.as_str(), &[]);
+            let msg = ExecuteMsg::RequestWithdraw { position: 2, amount: None };
+            execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap();
+
+            env.block.time = env.block.time.plus_seconds(WITHDRAW_DELAY);
+            let msg = ExecuteMsg::Withdraw { position: 2 };
+            let resp = execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap();
+            assert_eq!(
+                resp.messages.last().unwrap().msg,
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: user_addr.to_string(),
+                    amount: coins(amount.u128(), MOCK_LOCKED_DENOM),
+                })
+            );
+
+            let user_state = USER_STATE.load(deps.as_ref().storage, &user_addr).unwrap();
+            assert_eq!(user_state.locked_balance, amount);
+            assert_eq!(
+                user_state.balance,
+                POSITIONS.load(deps.as_ref().storage, 1).unwrap().balance
+            );
+            assert_eq!(TOKEN_STATE.load(deps.as_ref().storage).unwrap().total_locked, amount);
+            let positions = query_positions(deps.as_ref(), None);
+            assert_eq!(positions.len(), 1);
+            assert_eq!(positions[0].id, 1);
+
+            // 4. Number of positions per account is bounded
+            let new_locked_until = Uint64::from(env.block.time.seconds() + MIN_LOCK_PERIOD);
+            for _ in 1..MAX_POSITIONS_PER_ACCOUNT {
+                let msg = ExecuteMsg::Lock { amount, new_locked_until };
+                execute(deps.as_mut(), env.clone(), user_funds.clone(), msg).unwrap();
+            }
+            let msg = ExecuteMsg::Lock { amount, new_locked_until };
+            let err = execute(deps.as_mut(), env, user_funds, msg).unwrap_err();
+            assert_eq!(err, ContractError::TooManyPositions {});
+        }
+
+        #[test]
+        fn test_execute_merge_and_split() {
+            let mut deps = mock_dependencies();
+            let mut env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate_native(deps.as_mut(), env.clone(), info.to_owned());
+
+            let user_addr = Addr::unchecked("user");
+            let amount = apply_decimals(Uint128::from(2u8));
+            let reserves = apply_decimals(Uint128::from(1_000_000u32));
+            deps.querier.update_balance(
+                env.contract.address.clone(),
+                coins(reserves.u128(), MOCK_LOCKED_DENOM)
+            );
+            let user_funds = mock_info(user_addr.as_str(), &coins(amount.u128(), MOCK_LOCKED_DENOM));
+            let user_info = mock_info(user_addr.as_str(), &[]);
+
+            let short_locked_until = Uint64::from(env.block.time.seconds() + MAX_LOCK_PERIOD / 2);
+            let long_locked_until = Uint64::from(env.block.time.seconds() + MAX_LOCK_PERIOD);
+            for new_locked_until in [short_locked_until, long_locked_until] {
+                let msg = ExecuteMsg::Lock { amount, new_locked_until };
+                execute(deps.as_mut(), env.clone(), user_funds.clone(), msg).unwrap();
+            }
+
+            let income = apply_decimals(Uint128::from(1000u32));
+            let income_info = mock_info("creator", &coins(income.u128(), MOCK_LOCKED_DENOM));
+            let msg = ExecuteMsg::AddIncome { add_amount: income };
+            execute(deps.as_mut(), env.clone(), income_info, msg).unwrap();
+
+            env.block.time = env.block.time.plus_seconds(MIN_LOCK_PERIOD);
+            env.block.height += 100;
+            let now = Uint64::from(env.block.time.seconds());
+            let assert_balances = |deps: Deps| {
+                let positions = internal_funcs::account_positions(deps.storage, &user_addr).unwrap();
+                let user_state = USER_STATE.load(deps.storage, &user_addr).unwrap();
+                assert_eq!(
+                    user_state.balance,
+                    positions.iter().map(|(_, position)| position.balance).sum::<Uint128>()
+                );
+                let bias = positions
+                    .iter()
+                    .map(|(_, position)| position.point.bias)
+                    .sum::<Uint256>();
+                assert_eq!(
+                    Uint256::from(checkpoints::balance_of(deps.storage, &user_addr, now).unwrap()),
+                    bias / Uint256::from(POINT_PRECISION)
+                );
+                assert_eq!(user_state.locked_balance, amount * Uint128::from(2u8));
+                assert_eq!(
+                    TOKEN_STATE.load(deps.storage).unwrap().total_locked,
+                    amount * Uint128::from(2u8)
+                );
+            };
+
+            // 1. Split amounts must be nonzero and leave some tokens in the position
+            let quarter = amount / Uint128::from(4u8);
+            for amounts in [vec![], vec![quarter, Uint128::zero()], vec![amount]] {
+                let msg = ExecuteMsg::Split { position: 2, amounts };
+                let err = execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap_err();
+                assert_eq!(err, ContractError::InvalidSplit {});
+            }
+
+            let msg = ExecuteMsg::Split { position: 2, amounts: vec![quarter] };
+            let err = execute(deps.as_mut(), env.clone(), mock_info("other", &[]), msg)
+                .unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+
+            // 2. Split pieces keep the unlock time
+            let msg = ExecuteMsg::Split { position: 2, amounts: vec![quarter, quarter] };
+            let resp = execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap();
+            match &resp.messages[0].msg {
+                CosmosMsg::Bank(BankMsg::Send { to_address, .. }) =>
+                    assert_eq!(to_address, user_addr.as_str()),
+                msg => panic!("unexpected message {:?}", msg),
+            }
+            assert!(
+                resp.events.contains(
+                    &ContractEvent::make_split(
+                        user_addr.to_string(),
+                        2,
+                        vec![3, 4],
+                        amount / Uint128::from(2u8)
+                    ).to_cosmos_event()
+                )
+            );
+
+            let half = amount / Uint128::from(2u8);
+            for (id, locked_balance) in [(2, half), (3, quarter), (4, quarter)] {
+                let position = POSITIONS.load(deps.as_ref().storage, id).unwrap();
+                assert_eq!(position.locked_balance, locked_balance);
+                assert_eq!(position.locked_until, long_locked_until);
+            }
+            assert_balances(deps.as_ref());
+
+            // 3. Positions can't be merged into themselves or with a withdraw request
+            let msg = ExecuteMsg::Merge { from: 1, into: 1 };
+            let err = execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap_err();
+            assert_eq!(err, ContractError::MergeSamePosition {});
+
+            let mut position = POSITIONS.load(deps.as_ref().storage, 1).unwrap();
+            position.withdraw_at = now;
+            POSITIONS.save(deps.as_mut().storage, 1, &position).unwrap();
+            let msg = ExecuteMsg::Merge { from: 1, into: 3 };
+            let err = execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap_err();
+            assert_eq!(err, ContractError::WithdrawRequested { id: 1 });
+            position.withdraw_at = Uint64::zero();
+            POSITIONS.save(deps.as_mut().storage, 1, &position).unwrap();
+
+            // 4. Merged position takes the later unlock time
+            let msg = ExecuteMsg::Merge { from: 1, into: 3 };
+            execute(deps.as_mut(), env.clone(), user_info, msg).unwrap();
+
+            assert!(!POSITIONS.has(deps.as_ref().storage, 1));
+            let position = POSITIONS.load(deps.as_ref().storage, 3).unwrap();
+            assert_eq!(position.locked_balance, amount + quarter);
+            assert_eq!(position.locked_until, long_locked_until);
+            assert_balances(deps.as_ref());
+        }
+
         #[test]
         fn test_execute_lock_errors() {
             let mut deps = mock_dependencies();
@@ -333,169 +622,542 @@ mod contract_tests {
 
             mock_instantiate(deps.as_mut().branch(), env.clone(), info.to_owned());
 
+            let token_info = mock_info(MOCK_LOCKED_TOKEN, &[]);
             let amount = apply_decimals(Uint128::from(1u8));
             // Too short period
             let new_locked_until = Uint64::from(env.block.height + 1000);
-            let msg = ExecuteMsg::Lock {
-                amount: amount.clone(),
+            let msg = mock_cw20_receive(info.sender.as_str(), amount, ReceiveMsg::Lock {
                 new_locked_until: new_locked_until,
-            };
+            });
 
-            let error = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+            let error = execute(deps.as_mut(), env.clone(), token_info.clone(), msg).unwrap_err();
             assert_eq!(error, ContractError::LockPeriodTooShort {});
 
             // Too long period
             let new_locked_until = Uint64::from(env.block.time.seconds() + MAX_LOCK_PERIOD + 1);
-            let msg = ExecuteMsg::Lock {
-                amount: amount.clone(),
+            let msg = mock_cw20_receive(info.sender.as_str(), amount, ReceiveMsg::Lock {
                 new_locked_until: new_locked_until,
-            };
+            });
 
-            let error = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+            let error = execute(deps.as_mut(), env.clone(), token_info.clone(), msg).unwrap_err();
             assert_eq!(error, ContractError::LockPeriodTooLong {});
 
             // Can not reduce lock time
-            let mut user_state = UserState::default();
-            user_state.locked_until = Uint64::from(
-                env.block.time.seconds() + MIN_LOCK_PERIOD + 1000
+            USER_STATE.save(deps.as_mut().storage, &info.sender, &UserState::default()).unwrap();
+            let position_id = mock_position(
+                deps.as_mut().storage,
+                &info.sender,
+                amount,
+                Uint64::from(env.block.time.seconds() + MIN_LOCK_PERIOD + 1000)
             );
-            USER_STATE.save(deps.as_mut().storage, &info.sender, &user_state).unwrap();
 
             let new_locked_until = Uint64::from(env.block.time.seconds() + MIN_LOCK_PERIOD + 500);
+            let msg = ExecuteMsg::ExtendLock { position: position_id, new_locked_until };
+
+            let error = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+            assert_eq!(error, ContractError::LockNotExtended {});
+
+            // Positions of other accounts can't be extended
+            let msg = ExecuteMsg::ExtendLock {
+                position: position_id,
+                new_locked_until: Uint64::from(env.block.time.seconds() + MIN_LOCK_PERIOD * 2),
+            };
+
+            let other_info = mock_info("other", &[]);
+            let error = execute(deps.as_mut(), env.clone(), other_info, msg).unwrap_err();
+            assert_eq!(error, ContractError::Unauthorized {});
+
+            // Unknown position
+            let msg = ExecuteMsg::ExtendLock {
+                position: position_id + 1,
+                new_locked_until: Uint64::from(env.block.time.seconds() + MIN_LOCK_PERIOD * 2),
+            };
+
+            let error = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+            assert_eq!(error, ContractError::PositionNotFound { id: position_id + 1 });
+
+            // Only locked token is accepted
+            let new_locked_until = Uint64::from(env.block.time.seconds() + MIN_LOCK_PERIOD * 2);
+            let msg = mock_cw20_receive(info.sender.as_str(), amount, ReceiveMsg::Lock {
+                new_locked_until: new_locked_until,
+            });
+
+            let error = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("other_cw20", &[]),
+                msg
+            ).unwrap_err();
+            assert_eq!(error, ContractError::InvalidToken {});
+
+            // Tokens can't be pulled with allowance
             let msg = ExecuteMsg::Lock {
-                amount: amount.clone(),
+                amount,
                 new_locked_until: new_locked_until,
             };
 
             let error = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
-            assert_eq!(error, ContractError::CannotReduceLockedTime {});
+            assert_eq!(error, ContractError::Cw20SendRequired {});
+
+            // A new position needs tokens
+            let msg = ExecuteMsg::Lock {
+                amount: Uint128::zero(),
+                new_locked_until,
+            };
+
+            let error = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+            assert_eq!(error, ContractError::NothingToLock {});
         }
 
         #[test]
-        fn test_execute_request_withdraw_with_claim() {
+        fn test_execute_increase_amount_and_extend_lock() {
             let mut deps = mock_dependencies();
-            let env = mock_env();
+            let mut env = mock_env();
             let info = mock_info("creator", &[]);
 
-            mock_instantiate(deps.as_mut(), env.clone(), info.to_owned());
+            mock_instantiate_native(deps.as_mut(), env.clone(), info.to_owned());
 
             let user_addr = Addr::unchecked("user");
-            let info = mock_info(user_addr.as_str(), &[]);
-
-            let user_ve_balance = apply_decimals(Uint128::from(1u8));
-            internal_funcs
-                ::set_balance(deps.as_mut(), &env, &info, &user_addr, user_ve_balance.clone())
-                .unwrap();
-
-            let mut initial_user_state = USER_STATE.load(
-                deps.as_mut().storage,
-                &user_addr
-            ).unwrap();
-            initial_user_state.locked_balance = apply_decimals(Uint128::from(1u8));
-            initial_user_state.locked_until = Uint64::from(env.block.time.seconds());
-            USER_STATE.save(deps.as_mut().storage, &info.sender, &initial_user_state).unwrap();
-
-            let reward_per_token = apply_decimals(Uint128::from(1u8)) / Uint128::from(10u8);
-            TOKEN_STATE.update(
-                deps.as_mut().storage,
-                |mut state| -> StdResult<TokenState> {
-                    state.reward_per_token = reward_per_token.clone();
-                    Ok(state)
-                }
-            ).unwrap();
-
-            let msg = ExecuteMsg::RequestWithdraw {};
-
-            let resp = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
-
-            let mut expected_user_state = initial_user_state.clone();
-            expected_user_state.reward_snapshot = reward_per_token.clone();
-            expected_user_state.withdraw_at = Uint64::from(
-                env.block.time.seconds() + WITHDRAW_DELAY
+            let amount = apply_decimals(Uint128::from(1u8));
+            let reserves = apply_decimals(Uint128::from(1_000_000u32));
+            deps.querier.update_balance(
+                env.contract.address.clone(),
+                coins(reserves.u128(), MOCK_LOCKED_DENOM)
             );
-            expected_user_state.balance = Uint128::zero();
 
-            assert_eq!(
-                expected_user_state,
-                USER_STATE.load(deps.as_mut().storage, &info.sender).unwrap()
+            let user_funds = mock_info(user_addr.as_str(), &coins(amount.u128(), MOCK_LOCKED_DENOM));
+            let locked_until = Uint64::from(env.block.time.seconds() + MIN_LOCK_PERIOD);
+            let msg = ExecuteMsg::Lock { amount, new_locked_until: locked_until };
+            execute(deps.as_mut(), env.clone(), user_funds.clone(), msg).unwrap();
+
+            // 1. Increase keeps the unlock time, even with less than the min lock period left
+            let user_info = mock_info(user_addr.as_str(), &[]);
+            let msg = ExecuteMsg::IncreaseAmount { position: 1, amount: Uint128::zero() };
+            let err = execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap_err();
+            assert_eq!(err, ContractError::NothingToIncrease {});
+
+            env.block.time = env.block.time.plus_seconds(MIN_LOCK_PERIOD - 100);
+            let msg = ExecuteMsg::IncreaseAmount { position: 1, amount };
+            let resp = execute(deps.as_mut(), env.clone(), user_funds.clone(), msg).unwrap();
+            let position = POSITIONS.load(deps.as_ref().storage, 1).unwrap();
+            assert_eq!(position.locked_until, locked_until);
+            assert_eq!(position.locked_balance, amount * Uint128::from(2u8));
+            assert!(
+                resp.events.contains(
+                    &ContractEvent::make_increase_amount(
+                        user_addr.to_string(),
+                        1,
+                        amount,
+                        position.locked_balance,
+                        position.balance
+                    ).to_cosmos_event()
+                )
             );
 
-            let expected_claim_amount =
-                (initial_user_state.balance * reward_per_token) /
-                apply_decimals(Uint128::from(1u8));
-
-            let expected_response: Response<Empty> = Response::new()
-                .add_events(
-                    vec![
-                        (ContractEvent::Claim {
-                            account: info.sender.to_string(),
-                            claim_amount: expected_claim_amount,
-                            ve_balance: Uint128::zero(), //Because user locked_balance == 0
-                        }).to_cosmos_event(),
-                        (ContractEvent::WithdrawRequest {
-                            account: info.sender.to_string(),
-                            withdraw_at: expected_user_state.withdraw_at,
-                            amount: expected_user_state.locked_balance,
-                        }).to_cosmos_event()
-                    ]
-                )
-                .add_messages(
-                    vec![
-                        CosmosMsg::Wasm(WasmMsg::Execute {
-                            contract_addr: MOCK_LOCKED_TOKEN.to_string(),
-                            msg: to_binary(
-                                &(Cw20ExecuteMsg::Transfer {
-                                    recipient: info.sender.to_string(),
-                                    amount: expected_claim_amount,
-                                })
-                            ).unwrap(),
-                            funds: vec![],
-                        })
-                    ]
+            // 2. Extend only moves the unlock time later, within the lock period limits
+            let msg = ExecuteMsg::ExtendLock { position: 1, new_locked_until: locked_until };
+            let err = execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap_err();
+            assert_eq!(err, ContractError::LockNotExtended {});
+            let msg = ExecuteMsg::ExtendLock {
+                position: 1,
+                new_locked_until: Uint64::from(env.block.time.seconds() + MAX_LOCK_PERIOD + 1),
+            };
+            let err = execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap_err();
+            assert_eq!(err, ContractError::LockPeriodTooLong {});
+
+            let new_locked_until = Uint64::from(env.block.time.seconds() + MAX_LOCK_PERIOD);
+            let msg = ExecuteMsg::ExtendLock { position: 1, new_locked_until };
+            let resp = execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap();
+            let position = POSITIONS.load(deps.as_ref().storage, 1).unwrap();
+            assert_eq!(position.locked_until, new_locked_until);
+            assert_eq!(position.balance, position.locked_balance);
+            assert!(
+                resp.events.contains(
+                    &ContractEvent::make_extend_lock(
+                        user_addr.to_string(),
+                        1,
+                        new_locked_until,
+                        position.balance
+                    ).to_cosmos_event()
                 )
-                .add_attributes(
-                    vec![
-                        attr("action", "burn".to_string()),
-                        attr("from", user_addr.to_string()),
-                        attr("amount", user_ve_balance.to_string())
-                    ]
-                );
+            );
 
-            assert_eq!(expected_response, resp);
+            // 3. Expired positions can be relocked but not increased
+            env.block.time = env.block.time.plus_seconds(MAX_LOCK_PERIOD);
+            let msg = ExecuteMsg::IncreaseAmount { position: 1, amount };
+            let err = execute(deps.as_mut(), env.clone(), user_funds, msg).unwrap_err();
+            assert_eq!(err, ContractError::IncreaseExpiredLock {});
+
+            let msg = ExecuteMsg::ExtendLock {
+                position: 1,
+                new_locked_until: Uint64::from(env.block.time.seconds() + MIN_LOCK_PERIOD - 1),
+            };
+            let err = execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap_err();
+            assert_eq!(err, ContractError::LockPeriodTooShort {});
+            let msg = ExecuteMsg::ExtendLock {
+                position: 1,
+                new_locked_until: Uint64::from(env.block.time.seconds() + MIN_LOCK_PERIOD),
+            };
+            execute(deps.as_mut(), env.clone(), user_info, msg).unwrap();
+            assert!(!POSITIONS.load(deps.as_ref().storage, 1).unwrap().balance.is_zero());
         }
 
         #[test]
-        fn test_execute_request_withdraw_without_claim() {
+        fn test_execute_lock_for() {
             let mut deps = mock_dependencies();
             let env = mock_env();
             let info = mock_info("creator", &[]);
 
             mock_instantiate(deps.as_mut(), env.clone(), info.to_owned());
 
-            let mut user_state = UserState::default();
-            user_state.locked_balance = apply_decimals(Uint128::from(1u8));
-            user_state.locked_until = Uint64::from(env.block.time.seconds());
-            USER_STATE.save(deps.as_mut().storage, &info.sender, &user_state).unwrap();
+            let beneficiary_addr = Addr::unchecked("beneficiary");
+            let amount = apply_decimals(Uint128::from(1u8));
+            deps.querier.update_wasm(cw20_mock_querier(amount * Uint128::from(10u8)));
+
+            let max_locked_until = Uint64::from(env.block.time.seconds() + MIN_LOCK_PERIOD * 2);
+            let lock_for = |locked_until: Uint64| {
+                mock_cw20_receive("vesting", amount, ReceiveMsg::LockFor {
+                    beneficiary: beneficiary_addr.to_string(),
+                    locked_until,
+                })
+            };
+            let token_info = mock_info(MOCK_LOCKED_TOKEN, &[]);
 
-            let msg = ExecuteMsg::RequestWithdraw {};
+            // 1. Beneficiary must allow the locker first
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                token_info.clone(),
+                lock_for(max_locked_until)
+            ).unwrap_err();
+            assert_eq!(err, ContractError::LockForNotAllowed {});
 
-            let resp = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+            let beneficiary_info = mock_info(beneficiary_addr.as_str(), &[]);
+            let msg = ExecuteMsg::AllowLockFor {
+                locker: "vesting".to_string(),
+                max_locked_until,
+                max_positions: 0,
+            };
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                beneficiary_info.clone(),
+                msg
+            ).unwrap_err();
+            assert_eq!(err, ContractError::NoLockForPositions {});
 
-            let mut expected_user_state = user_state;
-            expected_user_state.withdraw_at = Uint64::from(
-                env.block.time.seconds() + WITHDRAW_DELAY
+            let msg = ExecuteMsg::AllowLockFor {
+                locker: "vesting".to_string(),
+                max_locked_until,
+                max_positions: 3,
+            };
+            let resp = execute(deps.as_mut(), env.clone(), beneficiary_info.clone(), msg).unwrap();
+            let expected_response: Response<Empty> = Response::new().add_event(
+                ContractEvent::make_lock_for_allowance(
+                    beneficiary_addr.to_string(),
+                    "vesting".to_string(),
+                    max_locked_until,
+                    3
+                ).to_cosmos_event()
+            );
+            assert_eq!(expected_response, resp);
+
+            // 2. Not beyond the allowed unlock time
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                token_info.clone(),
+                lock_for(max_locked_until + Uint64::from(1u8))
+            ).unwrap_err();
+            assert_eq!(err, ContractError::LockForTooLong {});
+
+            // 3. Every lock opens a new position of the beneficiary
+            for _ in 0..2 {
+                let resp = execute(
+                    deps.as_mut(),
+                    env.clone(),
+                    token_info.clone(),
+                    lock_for(max_locked_until)
+                ).unwrap();
+                let position = POSITION_COUNT.load(deps.as_ref().storage).unwrap();
+                assert!(
+                    resp.events.contains(
+                        &ContractEvent::make_lock_for(
+                            "vesting".to_string(),
+                            beneficiary_addr.to_string(),
+                            position,
+                            amount
+                        ).to_cosmos_event()
+                    )
+                );
+            }
+            let positions = internal_funcs
+                ::account_positions(deps.as_ref().storage, &beneficiary_addr)
+                .unwrap();
+            assert_eq!(positions.len(), 2);
+            for (_, position) in positions {
+                assert_eq!(position.locked_balance, amount);
+                assert_eq!(position.locked_until, max_locked_until);
+            }
+            let user_state = USER_STATE.load(deps.as_ref().storage, &beneficiary_addr).unwrap();
+            assert_eq!(user_state.locked_balance, amount * Uint128::from(2u8));
+            assert!(!USER_STATE.has(deps.as_ref().storage, &Addr::unchecked("vesting")));
+
+            // 4. Allowance can be withdrawn
+            let msg = QueryMsg::LockForAllowance {
+                beneficiary: beneficiary_addr.to_string(),
+                locker: "vesting".to_string(),
+            };
+            let resp: LockForAllowanceResponse = from_binary(
+                &query(deps.as_ref(), env.clone(), msg.clone()).unwrap()
+            ).unwrap();
+            assert_eq!(resp.max_locked_until, Some(max_locked_until));
+            assert_eq!(resp.positions, 1);
+
+            let disallow_msg = ExecuteMsg::DisallowLockFor { locker: "vesting".to_string() };
+            execute(deps.as_mut(), env.clone(), beneficiary_info, disallow_msg).unwrap();
+            let resp: LockForAllowanceResponse = from_binary(
+                &query(deps.as_ref(), env.clone(), msg).unwrap()
+            ).unwrap();
+            assert_eq!(resp.max_locked_until, None);
+            assert_eq!(resp.positions, 0);
+
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                token_info,
+                lock_for(max_locked_until)
+            ).unwrap_err();
+            assert_eq!(err, ContractError::LockForNotAllowed {});
+
+            // 5. Locking for oneself needs no allowance
+            let msg = mock_cw20_receive("vesting", amount, ReceiveMsg::LockFor {
+                beneficiary: "vesting".to_string(),
+                locked_until: max_locked_until + Uint64::from(1u8),
+            });
+            execute(deps.as_mut(), env, mock_info(MOCK_LOCKED_TOKEN, &[]), msg).unwrap();
+            assert_eq!(
+                USER_STATE.load(deps.as_ref().storage, &Addr::unchecked("vesting"))
+                    .unwrap()
+                    .locked_balance,
+                amount
+            );
+        }
+
+        #[test]
+        fn test_execute_lock_for_allowance_used_up() {
+            let mut deps = mock_dependencies();
+            let env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate(deps.as_mut(), env.clone(), info.to_owned());
+
+            let beneficiary_addr = Addr::unchecked("beneficiary");
+            let amount = apply_decimals(Uint128::from(1u8));
+            deps.querier.update_wasm(cw20_mock_querier(amount * Uint128::from(100u8)));
+            let locked_until = Uint64::from(env.block.time.seconds() + MIN_LOCK_PERIOD);
+            let token_info = mock_info(MOCK_LOCKED_TOKEN, &[]);
+
+            let msg = ExecuteMsg::AllowLockFor {
+                locker: "vesting".to_string(),
+                max_locked_until: locked_until,
+                max_positions: 2,
+            };
+            execute(deps.as_mut(), env.clone(), mock_info(beneficiary_addr.as_str(), &[]), msg)
+                .unwrap();
+
+            // 1. The locker opens as many positions as allowed, the last one ends the allowance
+            let lock_for = || {
+                mock_cw20_receive("vesting", amount, ReceiveMsg::LockFor {
+                    beneficiary: beneficiary_addr.to_string(),
+                    locked_until,
+                })
+            };
+            execute(deps.as_mut(), env.clone(), token_info.clone(), lock_for()).unwrap();
+            let resp = execute(deps.as_mut(), env.clone(), token_info.clone(), lock_for()).unwrap();
+            assert!(
+                resp.events.contains(
+                    &ContractEvent::make_lock_for_allowance(
+                        beneficiary_addr.to_string(),
+                        "vesting".to_string(),
+                        Uint64::zero(),
+                        0
+                    ).to_cosmos_event()
+                )
+            );
+            assert!(
+                !LOCK_FOR_ALLOWANCES.has(
+                    deps.as_ref().storage,
+                    (&beneficiary_addr, &Addr::unchecked("vesting"))
+                )
+            );
+
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                token_info.clone(),
+                lock_for()
+            ).unwrap_err();
+            assert_eq!(err, ContractError::LockForNotAllowed {});
+
+            // 2. The beneficiary keeps the rest of the position slots
+            for _ in 2..MAX_POSITIONS_PER_ACCOUNT {
+                let msg = mock_cw20_receive(beneficiary_addr.as_str(), amount, ReceiveMsg::Lock {
+                    new_locked_until: locked_until,
+                });
+                execute(deps.as_mut(), env.clone(), token_info.clone(), msg).unwrap();
+            }
+            let positions = internal_funcs
+                ::account_positions(deps.as_ref().storage, &beneficiary_addr)
+                .unwrap();
+            assert_eq!(positions.len(), MAX_POSITIONS_PER_ACCOUNT);
+        }
+
+        #[test]
+        fn test_execute_request_withdraw_with_claim() {
+            let mut deps = mock_dependencies();
+            let env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate(deps.as_mut(), env.clone(), info.to_owned());
+
+            let user_addr = Addr::unchecked("user");
+            let info = mock_info(user_addr.as_str(), &[]);
+
+            let user_ve_balance = apply_decimals(Uint128::from(1u8));
+            internal_funcs
+                ::set_balance(deps.as_mut(), &env, &info, &user_addr, user_ve_balance.clone())
+                .unwrap();
+
+            let mut initial_user_state = USER_STATE.load(
+                deps.as_mut().storage,
+                &user_addr
+            ).unwrap();
+            initial_user_state.locked_balance = apply_decimals(Uint128::from(1u8));
+            USER_STATE.save(deps.as_mut().storage, &info.sender, &initial_user_state).unwrap();
+            let position_id = mock_position(
+                deps.as_mut().storage,
+                &user_addr,
+                initial_user_state.locked_balance,
+                Uint64::from(env.block.time.seconds())
             );
 
+            let reward_per_token = apply_decimals(Uint128::from(1u8)) / Uint128::from(10u8);
+            TOKEN_STATE.update(
+                deps.as_mut().storage,
+                |mut state| -> StdResult<TokenState> {
+                    state.reward_per_token = reward_per_token.clone();
+                    Ok(state)
+                }
+            ).unwrap();
+
+            let msg = ExecuteMsg::RequestWithdraw { position: position_id, amount: None };
+
+            let resp = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+            let mut expected_user_state = initial_user_state.clone();
+            expected_user_state.reward_snapshot = reward_per_token.clone();
+            expected_user_state.balance = Uint128::zero();
+
             assert_eq!(
                 expected_user_state,
                 USER_STATE.load(deps.as_mut().storage, &info.sender).unwrap()
             );
+            let withdraw_at = Uint64::from(env.block.time.seconds() + WITHDRAW_DELAY);
+            assert_eq!(
+                POSITIONS.load(deps.as_ref().storage, position_id).unwrap().withdraw_at,
+                withdraw_at
+            );
+
+            let expected_claim_amount =
+                (initial_user_state.balance * reward_per_token) /
+                apply_decimals(Uint128::from(1u8));
+
+            let expected_response: Response<Empty> = Response::new()
+                .add_events(
+                    vec![
+                        (ContractEvent::Claim {
+                            account: info.sender.to_string(),
+                            recipient: info.sender.to_string(),
+                            claim_amount: expected_claim_amount,
+                            ve_balance: Uint128::zero(), //Because user locked_balance == 0
+                        }).to_cosmos_event(),
+                        (ContractEvent::WithdrawRequest {
+                            account: info.sender.to_string(),
+                            position: position_id,
+                            withdraw_at,
+                            amount: expected_user_state.locked_balance,
+                        }).to_cosmos_event()
+                    ]
+                )
+                .add_messages(
+                    vec![
+                        CosmosMsg::Wasm(WasmMsg::Execute {
+                            contract_addr: MOCK_LOCKED_TOKEN.to_string(),
+                            msg: to_binary(
+                                &(Cw20ExecuteMsg::Transfer {
+                                    recipient: info.sender.to_string(),
+                                    amount: expected_claim_amount,
+                                })
+                            ).unwrap(),
+                            funds: vec![],
+                        })
+                    ]
+                )
+                .add_attributes(
+                    vec![
+                        attr("action", "burn".to_string()),
+                        attr("from", user_addr.to_string()),
+                        attr("amount", user_ve_balance.to_string())
+                    ]
+                );
+
+            assert_eq!(expected_response, resp);
+        }
+
+        #[test]
+        fn test_execute_request_withdraw_without_claim() {
+            let mut deps = mock_dependencies();
+            let env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate(deps.as_mut(), env.clone(), info.to_owned());
+
+            let mut user_state = UserState::default();
+            user_state.locked_balance = apply_decimals(Uint128::from(1u8));
+            USER_STATE.save(deps.as_mut().storage, &info.sender, &user_state).unwrap();
+            let position_id = mock_position(
+                deps.as_mut().storage,
+                &info.sender,
+                user_state.locked_balance,
+                Uint64::from(env.block.time.seconds())
+            );
+            let position = POSITIONS.load(deps.as_ref().storage, position_id).unwrap();
+
+            let msg = ExecuteMsg::RequestWithdraw { position: position_id, amount: None };
+
+            let resp = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+            let mut expected_position = position;
+            expected_position.withdraw_at = Uint64::from(
+                env.block.time.seconds() + WITHDRAW_DELAY
+            );
+            expected_position.withdraw_amount = expected_position.locked_balance;
+            // claim checkpoints the expired lock
+            expected_position.point.ts = Uint64::from(env.block.time.seconds());
+
+            assert_eq!(
+                expected_position,
+                POSITIONS.load(deps.as_ref().storage, position_id).unwrap()
+            );
+            assert_eq!(user_state, USER_STATE.load(deps.as_ref().storage, &info.sender).unwrap());
 
             let expected_response: Response<Empty> = Response::new().add_event(
                 ContractEvent::make_withdraw_request(
                     info.sender.to_string(),
-                    expected_user_state.locked_balance,
-                    expected_user_state.withdraw_at
+                    position_id,
+                    expected_position.locked_balance,
+                    expected_position.withdraw_at
                 ).to_cosmos_event()
             );
 
@@ -511,24 +1173,36 @@ mod contract_tests {
             mock_instantiate(deps.as_mut(), env.clone(), info.to_owned());
 
             // 1. Nothing to withdraw
-            let mut user_state = UserState::default();
+            let user_state = UserState::default();
             USER_STATE.save(deps.as_mut().storage, &info.sender, &user_state).unwrap();
+            let position_id = mock_position(
+                deps.as_mut().storage,
+                &info.sender,
+                Uint128::zero(),
+                Uint64::from(env.block.time.seconds())
+            );
 
-            let msg = ExecuteMsg::RequestWithdraw {};
+            let msg = ExecuteMsg::RequestWithdraw { position: position_id, amount: None };
 
             let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
             assert_eq!(err, ContractError::NothingToWithdraw {});
 
             // 2. Not enough time passed
-            user_state.locked_balance = apply_decimals(Uint128::from(1u8));
-            user_state.locked_until = Uint64::from(
-                env.block.time.seconds() + MIN_LOCK_PERIOD + 1000
+            let position_id = mock_position(
+                deps.as_mut().storage,
+                &info.sender,
+                apply_decimals(Uint128::from(1u8)),
+                Uint64::from(env.block.time.seconds() + MIN_LOCK_PERIOD + 1000)
             );
-            USER_STATE.save(deps.as_mut().storage, &info.sender, &user_state).unwrap();
 
-            let msg = ExecuteMsg::RequestWithdraw {};
+            let msg = ExecuteMsg::RequestWithdraw { position: position_id, amount: None };
             let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
             assert_eq!(err, ContractError::WithdrawBeforeUnlock {});
+
+            // 3. Only the owner can request
+            let msg = ExecuteMsg::RequestWithdraw { position: position_id, amount: None };
+            let err = execute(deps.as_mut(), env.clone(), mock_info("other", &[]), msg).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
         }
 
         #[test]
@@ -542,8 +1216,23 @@ mod contract_tests {
             let lock_balance = apply_decimals(Uint128::from(1u8));
             let mut initial_user_state = UserState::default();
             initial_user_state.locked_balance = lock_balance.clone();
-            initial_user_state.withdraw_at = Uint64::from(env.block.time.seconds());
             USER_STATE.save(deps.as_mut().storage, &info.sender, &initial_user_state).unwrap();
+            let position_id = mock_position(
+                deps.as_mut().storage,
+                &info.sender,
+                lock_balance,
+                Uint64::zero()
+            );
+            POSITIONS.update(
+                deps.as_mut().storage,
+                position_id,
+                |position| -> StdResult<_> {
+                    let mut position = position.unwrap();
+                    position.withdraw_at = Uint64::from(env.block.time.seconds());
+                    position.withdraw_amount = position.locked_balance;
+                    Ok(position)
+                }
+            ).unwrap();
 
             let mut initial_token_state = TOKEN_STATE.load(deps.as_mut().storage).unwrap();
             initial_token_state.total_locked = lock_balance.clone();
@@ -551,7 +1240,7 @@ mod contract_tests {
 
             deps.querier.update_wasm(cw20_mock_querier(lock_balance.clone()));
 
-            let msg = ExecuteMsg::Withdraw {};
+            let msg = ExecuteMsg::Withdraw { position: position_id };
             let resp = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
             let expected_message = CosmosMsg::Wasm(cosmwasm_std::WasmMsg::Execute {
@@ -568,6 +1257,7 @@ mod contract_tests {
                 .add_event(
                     ContractEvent::make_withdraw(
                         info.sender.to_string(),
+                        position_id,
                         lock_balance.clone()
                     ).to_cosmos_event()
                 )
@@ -577,7 +1267,6 @@ mod contract_tests {
 
             let mut expected_user_state = initial_user_state.clone();
             expected_user_state.locked_balance = Uint128::zero();
-            expected_user_state.withdraw_at = Uint64::zero();
 
             let mut expected_token_state = initial_token_state.clone();
             expected_token_state.total_locked = Uint128::zero();
@@ -588,6 +1277,19 @@ mod contract_tests {
             );
 
             assert_eq!(expected_token_state, TOKEN_STATE.load(deps.as_mut().storage).unwrap());
+            assert!(!POSITIONS.has(deps.as_ref().storage, position_id));
+            assert_eq!(
+                query(
+                    deps.as_ref(),
+                    env.clone(),
+                    QueryMsg::Positions {
+                        owner: info.sender.to_string(),
+                        start_after: None,
+                        limit: None,
+                    }
+                ).unwrap(),
+                to_binary(&(PositionsResponse { positions: vec![] })).unwrap()
+            );
         }
 
         #[test]
@@ -598,10 +1300,16 @@ mod contract_tests {
 
             mock_instantiate(deps.as_mut(), env.clone(), info.to_owned());
 
-            let mut user_state = UserState::default();
+            let user_state = UserState::default();
             USER_STATE.save(deps.as_mut().storage, &info.sender, &user_state).unwrap();
+            let position_id = mock_position(
+                deps.as_mut().storage,
+                &info.sender,
+                Uint128::zero(),
+                Uint64::zero()
+            );
 
-            let msg = ExecuteMsg::Withdraw {};
+            let msg = ExecuteMsg::Withdraw { position: position_id };
 
             // 1. Nothing to withdraw
             let err = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
@@ -609,11 +1317,22 @@ mod contract_tests {
             assert_eq!(err, ContractError::WithdrawDelayNotOver {});
 
             // 2. Not enough time passed
-            user_state.withdraw_at = Uint64::from(env.block.time.seconds() + 1);
-            USER_STATE.save(deps.as_mut().storage, &info.sender, &user_state).unwrap();
+            POSITIONS.update(
+                deps.as_mut().storage,
+                position_id,
+                |position| -> StdResult<_> {
+                    let mut position = position.unwrap();
+                    position.withdraw_at = Uint64::from(env.block.time.seconds() + 1);
+                    Ok(position)
+                }
+            ).unwrap();
 
             let err = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
             assert_eq!(err, ContractError::WithdrawDelayNotOver {});
+
+            // 3. Only the owner can withdraw
+            let err = execute(deps.as_mut(), env.clone(), mock_info("other", &[]), msg).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
         }
 
         #[test]
@@ -627,8 +1346,23 @@ mod contract_tests {
             let lock_balance = apply_decimals(Uint128::from(1u8));
             let mut initial_user_state = UserState::default();
             initial_user_state.locked_balance = lock_balance.clone();
-            initial_user_state.withdraw_at = Uint64::from(env.block.time.seconds());
             USER_STATE.save(deps.as_mut().storage, &info.sender, &initial_user_state).unwrap();
+            let position_id = mock_position(
+                deps.as_mut().storage,
+                &info.sender,
+                lock_balance,
+                Uint64::zero()
+            );
+            POSITIONS.update(
+                deps.as_mut().storage,
+                position_id,
+                |position| -> StdResult<_> {
+                    let mut position = position.unwrap();
+                    position.withdraw_at = Uint64::from(env.block.time.seconds());
+                    position.withdraw_amount = position.locked_balance;
+                    Ok(position)
+                }
+            ).unwrap();
 
             let mut initial_token_state = TOKEN_STATE.load(deps.as_mut().storage).unwrap();
             initial_token_state.total_locked = lock_balance.clone() * Uint128::from(2u8); //to sure that there is left locked balance after withdraw
@@ -636,7 +1370,7 @@ mod contract_tests {
 
             deps.querier.update_wasm(cw20_mock_querier(lock_balance.clone() / Uint128::from(2u8)));
 
-            let msg = ExecuteMsg::Withdraw {};
+            let msg = ExecuteMsg::Withdraw { position: position_id };
             let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
 
             assert_eq!(err, ContractError::InsufficientReserves {});
@@ -674,12 +1408,17 @@ mod contract_tests {
                 |state| -> StdResult<_> {
                     let mut user = state.unwrap_or_default();
                     user.locked_balance = user_locked_balance.clone();
-                    user.locked_until = Uint64::from(
-                        env.block.time.plus_seconds(user_locked_until_delta.clone().u64()).seconds()
-                    );
                     Ok(user)
                 }
             ).unwrap();
+            mock_position(
+                deps.as_mut().storage,
+                &user_addr,
+                user_locked_balance.clone(),
+                Uint64::from(
+                    env.block.time.plus_seconds(user_locked_until_delta.clone().u64()).seconds()
+                )
+            );
 
             let initial_token_state = TOKEN_STATE.load(deps.as_mut().storage).unwrap();
             let initial_user_state = USER_STATE.load(deps.as_mut().storage, &user_addr).unwrap();
@@ -690,7 +1429,7 @@ mod contract_tests {
                 deps.as_mut(),
                 env.clone(),
                 mock_info(user_addr.as_str(), &[]),
-                ExecuteMsg::Claim {}
+                ExecuteMsg::Claim { recipient: None }
             ).unwrap();
 
             let expected_pending_reward = initial_user_state.pending_reward(
@@ -701,7 +1440,7 @@ mod contract_tests {
                 Uint128::from(MAX_LOCK_PERIOD);
 
             let expected_message = CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: initial_token_state.locked_token.to_string(),
+                contract_addr: MOCK_LOCKED_TOKEN.to_string(),
                 msg: to_binary(
                     &(Cw20ExecuteMsg::Transfer {
                         recipient: user_addr.clone().into(),
@@ -715,6 +1454,7 @@ mod contract_tests {
                 .add_message(expected_message)
                 .add_event(
                     ContractEvent::make_claim(
+                        user_addr.to_string(),
                         user_addr.to_string(),
                         expected_pending_reward.clone(),
                         expected_balance.clone()
@@ -756,12 +1496,17 @@ mod contract_tests {
                 |state| -> StdResult<_> {
                     let mut user = state.unwrap_or_default();
                     user.locked_balance = user_locked_balance.clone();
-                    user.locked_until = Uint64::from(
-                        env.block.time.plus_seconds(user_locked_until_delta.clone().u64()).seconds()
-                    );
                     Ok(user)
                 }
             ).unwrap();
+            mock_position(
+                deps.as_mut().storage,
+                &user_addr,
+                user_locked_balance.clone(),
+                Uint64::from(
+                    env.block.time.plus_seconds(user_locked_until_delta.clone().u64()).seconds()
+                )
+            );
 
             deps.querier.update_wasm(
                 cw20_mock_querier(user_locked_balance.clone() / Uint128::from(2u128))
@@ -771,7 +1516,7 @@ mod contract_tests {
                 deps.as_mut(),
                 env.clone(),
                 mock_info(user_addr.as_str(), &[]),
-                ExecuteMsg::Claim {}
+                ExecuteMsg::Claim { recipient: None }
             ).unwrap_err();
 
             assert_eq!(err, ContractError::InsufficientReserves {});
@@ -813,10 +1558,8 @@ mod contract_tests {
             let resp = execute(
                 deps.as_mut(),
                 env.clone(),
-                mock_info("creator", &[]),
-                ExecuteMsg::AddIncome {
-                    add_amount,
-                }
+                mock_info(MOCK_LOCKED_TOKEN, &[]),
+                mock_cw20_receive("creator", add_amount, ReceiveMsg::AddIncome {})
             ).unwrap();
 
             let mut expected_token_state = initial_token_state.clone();
@@ -825,18 +1568,6 @@ mod contract_tests {
 
             assert_eq!(expected_token_state, TOKEN_STATE.load(deps.as_mut().storage).unwrap());
 
-            let expected_message = CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: initial_token_state.locked_token.to_string(),
-                msg: to_binary(
-                    &(Cw20ExecuteMsg::TransferFrom {
-                        owner: info.sender.into_string(),
-                        recipient: env.contract.address.to_string(),
-                        amount: add_amount.clone(),
-                    })
-                ).unwrap(),
-                funds: vec![],
-            });
-
             let expected_response: Response<Empty> = Response::new()
                 .add_event(
                     ContractEvent::make_new_income(
@@ -844,8 +1575,7 @@ mod contract_tests {
                         expected_unvested_income.clone(),
                         expected_new_reward_per_token.clone()
                     ).to_cosmos_event()
-                )
-                .add_message(expected_message);
+                );
 
             assert_eq!(expected_response, resp);
         }
@@ -893,10 +1623,8 @@ mod contract_tests {
             let err = execute(
                 deps.as_mut(),
                 env.clone(),
-                mock_info("creator", &[]),
-                ExecuteMsg::AddIncome {
-                    add_amount,
-                }
+                mock_info(MOCK_LOCKED_TOKEN, &[]),
+                mock_cw20_receive("creator", add_amount, ReceiveMsg::AddIncome {})
             ).unwrap_err();
 
             assert_eq!(err, ContractError::InsufficientReserves {});
@@ -906,10 +1634,8 @@ mod contract_tests {
             let _resp = execute(
                 deps.as_mut(),
                 env.clone(),
-                mock_info("creator", &[]),
-                ExecuteMsg::AddIncome {
-                    add_amount,
-                }
+                mock_info(MOCK_LOCKED_TOKEN, &[]),
+                mock_cw20_receive("creator", add_amount, ReceiveMsg::AddIncome {})
             ).unwrap();
         }
 
@@ -950,261 +1676,2749 @@ mod contract_tests {
             );
             assert_eq!(expected_response, resp);
         }
-    }
-
-    #[cfg(test)]
-    mod query_tests {
-        use cosmwasm_std::from_binary;
-        use cw20::{ BalanceResponse, TokenInfoResponse };
-
-        use crate::internal::internal_funcs::set_balance;
-
-        use super::*;
 
         #[test]
-        pub fn test_query_balance() {
+        fn test_execute_set_distribution_period_unauthorized() {
             let mut deps = mock_dependencies();
             let env = mock_env();
             let info = mock_info("creator", &[]);
 
-            mock_instantiate(deps.as_mut().branch(), env.clone(), info.to_owned());
-
-            let account = Addr::unchecked("user");
-            let amount = Uint128::from(100u128);
-
-            set_balance(deps.as_mut(), &env, &info, &account, amount.clone()).expect("no error");
-
-            let query_response = query(deps.as_ref(), env.clone(), QueryMsg::Balance {
-                address: account.to_string(),
-            }).unwrap();
+            mock_instantiate(deps.as_mut(), env.clone(), info);
 
-            let query_response_decoded: BalanceResponse = from_binary(&query_response).unwrap();
+            let msg = ExecuteMsg::SetDistributionPeriod { blocks: Uint64::from(2000u64) };
+            let err = execute(deps.as_mut(), env, mock_info("user", &[]), msg).unwrap_err();
 
-            assert_eq!(query_response_decoded.balance, amount);
+            assert_eq!(err, ContractError::Unauthorized {});
         }
 
         #[test]
-        pub fn test_query_token_info() {
+        fn test_execute_ownership_transfer() {
             let mut deps = mock_dependencies();
             let env = mock_env();
             let info = mock_info("creator", &[]);
 
-            mock_instantiate(deps.as_mut().branch(), env.clone(), info.to_owned());
-
-            let account = Addr::unchecked("user");
-            let amount = Uint128::from(100u128);
+            mock_instantiate(deps.as_mut(), env.clone(), info.to_owned());
 
-            set_balance(deps.as_mut(), &env, &info, &account, amount.clone()).expect("no error");
+            let new_owner = Addr::unchecked("new_owner");
 
-            let expected_token_info = TokenInfoResponse {
-                name: "veWILD".to_string(),
-                symbol: "veWILD".to_string(),
-                decimals: TOKEN_DECIMALS as u8,
-                total_supply: amount,
-            };
+            // 1. Only owner can propose
+            let msg = ExecuteMsg::ProposeOwner { new_owner: new_owner.to_string() };
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(new_owner.as_str(), &[]),
+                msg.clone()
+            ).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
 
-            let query_response = query(deps.as_ref(), env.clone(), QueryMsg::TokenInfo {}).unwrap();
-            let query_response_decoded: TokenInfoResponse = from_binary(&query_response).unwrap();
+            // 2. Nothing to accept yet
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(new_owner.as_str(), &[]),
+                ExecuteMsg::AcceptOwnership {}
+            ).unwrap_err();
+            assert_eq!(err, ContractError::NoPendingOwner {});
 
-            assert_eq!(query_response_decoded, expected_token_info);
+            let resp = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+            let expected_response: Response<Empty> = Response::new().add_event(
+                ContractEvent::make_ownership_proposed(
+                    info.sender.to_string(),
+                    new_owner.to_string()
+                ).to_cosmos_event()
+            );
+            assert_eq!(expected_response, resp);
+
+            // Owner is unchanged until the transfer is accepted
+            let ownership = OWNERSHIP.load(deps.as_ref().storage).unwrap();
+            assert_eq!(ownership.owner, Some(info.sender.clone()));
+            assert_eq!(ownership.pending_owner, Some(new_owner.clone()));
+
+            // 3. Only pending owner can accept
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("user", &[]),
+                ExecuteMsg::AcceptOwnership {}
+            ).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+
+            let resp = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(new_owner.as_str(), &[]),
+                ExecuteMsg::AcceptOwnership {}
+            ).unwrap();
+
+            let expected_response: Response<Empty> = Response::new().add_event(
+                ContractEvent::make_ownership_transferred(
+                    info.sender.to_string(),
+                    new_owner.to_string()
+                ).to_cosmos_event()
+            );
+            assert_eq!(expected_response, resp);
+
+            assert_eq!(
+                OWNERSHIP.load(deps.as_ref().storage).unwrap(),
+                Ownership::new(new_owner.clone())
+            );
+
+            // 4. Previous owner lost its privileges
+            let msg = ExecuteMsg::SetDistributionPeriod { blocks: Uint64::from(2000u64) };
+            let err = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+
+            execute(deps.as_mut(), env, mock_info(new_owner.as_str(), &[]), msg).unwrap();
         }
 
         #[test]
-        pub fn test_query_reward_rate() {
+        fn test_execute_renounce_ownership() {
             let mut deps = mock_dependencies();
             let env = mock_env();
             let info = mock_info("creator", &[]);
 
-            mock_instantiate(deps.as_mut().branch(), env.clone(), info.to_owned());
+            mock_instantiate(deps.as_mut(), env.clone(), info.to_owned());
 
-            let mut token_state = TOKEN_STATE.load(deps.as_mut().storage).unwrap();
-            token_state.reward_rate_stored = Uint128::from(1u128);
-            token_state.last_income_block = Uint64::from(env.block.height);
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("user", &[]),
+                ExecuteMsg::RenounceOwnership {}
+            ).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
 
-            TOKEN_STATE.save(deps.as_mut().storage, &token_state).unwrap();
+            // Pending transfer is dropped together with the owner
+            let msg = ExecuteMsg::ProposeOwner { new_owner: "new_owner".to_string() };
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-            let query_response = query(
-                deps.as_ref(),
+            let resp = execute(
+                deps.as_mut(),
                 env.clone(),
-                QueryMsg::RewardRate {}
+                info.clone(),
+                ExecuteMsg::RenounceOwnership {}
             ).unwrap();
 
-            let query_response_decoded: RewardRateResponse = from_binary(&query_response).unwrap();
+            let expected_response: Response<Empty> = Response::new().add_event(
+                ContractEvent::make_ownership_renounced(info.sender.to_string()).to_cosmos_event()
+            );
+            assert_eq!(expected_response, resp);
 
-            let expected_response = RewardRateResponse {
-                reward_rate: token_state.reward_rate_stored,
-            };
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("new_owner", &[]),
+                ExecuteMsg::AcceptOwnership {}
+            ).unwrap_err();
+            assert_eq!(err, ContractError::NoPendingOwner {});
 
-            assert_eq!(expected_response, query_response_decoded);
+            let msg = ExecuteMsg::SetDistributionPeriod { blocks: Uint64::from(2000u64) };
+            let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
         }
 
         #[test]
-        pub fn test_query_pending_account_reward() {
+        fn test_execute_grant_and_revoke_role() {
             let mut deps = mock_dependencies();
-            let mut env = mock_env();
+            let env = mock_env();
             let info = mock_info("creator", &[]);
 
-            mock_instantiate(deps.as_mut().branch(), env.clone(), info.to_owned());
-
-            let addr = Addr::unchecked("user");
-
-            let reward_per_token = Uint128::from(100u128);
-            let reward_rate_stored = Uint128::from(1000u128);
-            let total_supply = Uint128::from(100u128);
+            mock_instantiate(deps.as_mut(), env.clone(), info.to_owned());
 
-            let user_state = UserState {
-                balance: Uint128::from(100u8),
-                locked_balance: Uint128::from(100u8),
-                locked_until: Uint64::from(1000u16),
-                reward_snapshot: Uint128::zero(),
-                withdraw_at: Uint64::from(1000u16),
+            let governance = Addr::unchecked("governance");
+            let msg = ExecuteMsg::GrantRole {
+                role: Role::ConfigAdmin,
+                address: governance.to_string(),
             };
-            USER_STATE.save(deps.as_mut().storage, &addr, &user_state).unwrap();
 
-            TOKEN_STATE.update(
-                deps.as_mut().storage,
-                |mut state| -> StdResult<TokenState> {
-                    state.reward_per_token = reward_per_token.clone();
-                    state.last_accrue_block = Uint64::from(env.block.height);
-                    state.reward_rate_stored = reward_rate_stored.clone();
-                    Ok(state)
-                }
-            ).unwrap();
+            // 1. Only owner can grant roles
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(governance.as_str(), &[]),
+                msg.clone()
+            ).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
 
-            // Just accrued
-            let query_response = query(deps.as_ref(), env.clone(), QueryMsg::PendingAccountReward {
-                address: addr.clone(),
-            }).unwrap();
+            let resp = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-            let query_response_decoded: PendingAccountRewardResponse = from_binary(
-                &query_response
+            let expected_response: Response<Empty> = Response::new().add_event(
+                ContractEvent::make_role_granted(
+                    "config_admin".to_string(),
+                    governance.to_string()
+                ).to_cosmos_event()
+            );
+            assert_eq!(expected_response, resp);
+
+            // 2. Role holder can use privileged handler, other roles can't
+            let msg = ExecuteMsg::SetDistributionPeriod { blocks: Uint64::from(2000u64) };
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(governance.as_str(), &[]),
+                msg.clone()
             ).unwrap();
 
-            let expected_result =
-                (reward_per_token.clone() * user_state.balance) /
-                apply_decimals(Uint128::from(1u8));
+            let grant_msg = ExecuteMsg::GrantRole {
+                role: Role::IncomeManager,
+                address: "treasury".to_string(),
+            };
+            execute(deps.as_mut(), env.clone(), info.clone(), grant_msg).unwrap();
 
-            assert_eq!(expected_result, query_response_decoded.pending_account_reward);
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("treasury", &[]),
+                msg.clone()
+            ).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
 
-            // There is pending reward after accrue
-            env.block.height += 1;
+            // 3. Revoked role holder loses access
+            let revoke_msg = ExecuteMsg::RevokeRole {
+                role: Role::ConfigAdmin,
+                address: governance.to_string(),
+            };
+            let resp = execute(deps.as_mut(), env.clone(), info, revoke_msg).unwrap();
 
-            let query_response = query(deps.as_ref(), env.clone(), QueryMsg::PendingAccountReward {
-                address: addr.clone(),
-            }).unwrap();
+            let expected_response: Response<Empty> = Response::new().add_event(
+                ContractEvent::make_role_revoked(
+                    "config_admin".to_string(),
+                    governance.to_string()
+                ).to_cosmos_event()
+            );
+            assert_eq!(expected_response, resp);
 
-            let query_response_decoded: PendingAccountRewardResponse = from_binary(
-                &query_response
-            ).unwrap();
+            let err = execute(
+                deps.as_mut(),
+                env,
+                mock_info(governance.as_str(), &[]),
+                msg
+            ).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+        }
 
-            let expected_result =
-                ((reward_per_token.clone() + reward_rate_stored.clone() / total_supply.clone()) *
-                    user_state.balance) /
-                apply_decimals(Uint128::from(1u8));
+        #[test]
+        fn test_execute_add_income_unauthorized() {
+            let mut deps = mock_dependencies();
+            let env = mock_env();
+            let info = mock_info("creator", &[]);
 
-            assert_eq!(expected_result, query_response_decoded.pending_account_reward);
+            mock_instantiate(deps.as_mut(), env.clone(), info.to_owned());
 
-            // user already took its reward
-            USER_STATE.update(
-                deps.as_mut().storage,
-                &addr,
-                |state| -> StdResult<UserState> {
-                    let mut user = state.unwrap();
-                    user.reward_snapshot = expected_result.clone();
-                    Ok(user)
-                }
-            ).unwrap();
+            let msg = mock_cw20_receive(
+                "treasury",
+                apply_decimals(Uint128::from(1u8)),
+                ReceiveMsg::AddIncome {}
+            );
+            let token_info = mock_info(MOCK_LOCKED_TOKEN, &[]);
 
-            let query_response = query(deps.as_ref(), env.clone(), QueryMsg::PendingAccountReward {
-                address: addr.clone(),
-            }).unwrap();
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                token_info.clone(),
+                msg.clone()
+            ).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
 
-            let query_response_decoded: PendingAccountRewardResponse = from_binary(
-                &query_response
-            ).unwrap();
+            let grant_msg = ExecuteMsg::GrantRole {
+                role: Role::IncomeManager,
+                address: "treasury".to_string(),
+            };
+            execute(deps.as_mut(), env.clone(), info, grant_msg).unwrap();
+
+            deps.querier.update_wasm(cw20_mock_querier(apply_decimals(Uint128::from(1u8))));
 
-            assert_eq!(Uint128::zero(), query_response_decoded.pending_account_reward);
+            execute(deps.as_mut(), env, token_info, msg).unwrap();
         }
 
         #[test]
-        pub fn test_query_user_info() {
+        fn test_execute_pause() {
             let mut deps = mock_dependencies();
-            let mut env = mock_env();
+            let env = mock_env();
             let info = mock_info("creator", &[]);
 
-            mock_instantiate(deps.as_mut().branch(), env.clone(), info.to_owned());
-
-            let addr = Addr::unchecked("user");
+            mock_instantiate(deps.as_mut(), env.clone(), info.to_owned());
 
-            let user_state = UserState {
-                balance: Uint128::from(100u8),
-                locked_balance: Uint128::from(100u8),
-                locked_until: Uint64::from(1000u16),
-                reward_snapshot: Uint128::from(100u8),
-                withdraw_at: Uint64::from(1000u16),
+            let pauser = mock_info("pauser", &[]);
+            let msg = ExecuteMsg::Pause {
+                actions: vec![PausableAction::Lock, PausableAction::Claim, PausableAction::AddIncome],
             };
 
-            USER_STATE.save(deps.as_mut().storage, &addr, &user_state).unwrap();
+            // 1. Only pauser can pause
+            let err = execute(deps.as_mut(), env.clone(), pauser.clone(), msg.clone()).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
 
-            let query_response = query(deps.as_ref(), env.clone(), QueryMsg::UserInfo {
-                address: addr,
-            }).unwrap();
+            let grant_msg = ExecuteMsg::GrantRole {
+                role: Role::Pauser,
+                address: pauser.sender.to_string(),
+            };
+            execute(deps.as_mut(), env.clone(), info.clone(), grant_msg).unwrap();
 
-            let query_response_decoded: UserInfoResponse = from_binary(&query_response).unwrap();
+            let resp = execute(deps.as_mut(), env.clone(), pauser.clone(), msg).unwrap();
 
-            let expected_response = UserInfoResponse {
-                locked_balance: user_state.locked_balance,
-                locked_until: user_state.locked_until,
-                reward_snapshot: user_state.reward_snapshot,
-                withdraw_at: user_state.withdraw_at,
-            };
+            let expected_response: Response<Empty> = Response::new().add_event(
+                ContractEvent::make_pause_status_changed(
+                    vec!["lock".to_string(), "claim".to_string(), "add_income".to_string()],
+                    true
+                ).to_cosmos_event()
+            );
+            assert_eq!(expected_response, resp);
 
-            assert_eq!(expected_response, query_response_decoded);
+            // 2. Paused actions fail
+            let token_info = mock_info(MOCK_LOCKED_TOKEN, &[]);
+            let lock_msg = mock_cw20_receive(
+                "user",
+                apply_decimals(Uint128::from(1u8)),
+                ReceiveMsg::Lock {
+                    new_locked_until: Uint64::from(env.block.time.seconds() + MIN_LOCK_PERIOD * 2),
+                }
+            );
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                token_info.clone(),
+                lock_msg.clone()
+            ).unwrap_err();
+            assert_eq!(err, ContractError::Paused { action: "lock".to_string() });
 
-            let query_response = query(deps.as_ref(), env.clone(), QueryMsg::UserInfo {
-                address: Addr::unchecked("not_exist"),
-            }).unwrap();
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("user", &[]),
+                ExecuteMsg::Claim { recipient: None }
+            ).unwrap_err();
+            assert_eq!(err, ContractError::Paused { action: "claim".to_string() });
 
-            let query_response_decoded: UserInfoResponse = from_binary(&query_response).unwrap();
+            let income_msg = mock_cw20_receive(
+                "creator",
+                Uint128::from(1u8),
+                ReceiveMsg::AddIncome {}
+            );
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                token_info.clone(),
+                income_msg
+            ).unwrap_err();
+            assert_eq!(err, ContractError::Paused { action: "add_income".to_string() });
 
-            let expected_response = UserInfoResponse {
-                locked_balance: Uint128::zero(),
-                reward_snapshot: Uint128::zero(),
-                locked_until: Uint64::zero(),
-                withdraw_at: Uint64::zero(),
-            };
+            // 3. Actions are unpaused one at a time
+            let msg = ExecuteMsg::Unpause { actions: vec![PausableAction::Lock] };
+            execute(deps.as_mut(), env.clone(), pauser, msg).unwrap();
 
-            assert_eq!(expected_response, query_response_decoded);
+            deps.querier.update_wasm(cw20_mock_querier(apply_decimals(Uint128::from(1u8))));
+            execute(deps.as_mut(), env.clone(), token_info.clone(), lock_msg).unwrap();
+
+            let err = execute(
+                deps.as_mut(),
+                env,
+                mock_info("user", &[]),
+                ExecuteMsg::Claim { recipient: None }
+            ).unwrap_err();
+            assert_eq!(err, ContractError::Paused { action: "claim".to_string() });
         }
 
         #[test]
-        pub fn test_query_ve_token_info() {
+        fn test_execute_pause_withdraw() {
             let mut deps = mock_dependencies();
-            let env = mock_env();
+            let mut env = mock_env();
             let info = mock_info("creator", &[]);
 
-            mock_instantiate(deps.as_mut().branch(), env.clone(), info.to_owned());
+            mock_instantiate_native(deps.as_mut(), env.clone(), info.to_owned());
 
-            let token_state = TokenState {
-                total_supply: Uint128::from(100u8),
-                total_locked: Uint128::from(100u8),
-                distribution_period: Uint64::from(1000u16),
-                locked_token: Addr::unchecked("cw20"),
-                last_accrue_block: Uint64::from(1000u16),
-                last_income_block: Uint64::from(1000u16),
-                reward_per_token: Uint128::from(100u8),
-                reward_rate_stored: Uint128::from(100u8),
+            let user_addr = Addr::unchecked("user");
+            let amount = apply_decimals(Uint128::from(1u8));
+            let reserves = apply_decimals(Uint128::from(1_000_000u32));
+            deps.querier.update_balance(
+                env.contract.address.clone(),
+                coins(reserves.u128(), MOCK_LOCKED_DENOM)
+            );
+
+            let user_funds = mock_info(user_addr.as_str(), &coins(amount.u128(), MOCK_LOCKED_DENOM));
+            for lock_period in [MIN_LOCK_PERIOD, MIN_LOCK_PERIOD, MAX_LOCK_PERIOD] {
+                let msg = ExecuteMsg::Lock {
+                    amount,
+                    new_locked_until: Uint64::from(env.block.time.seconds() + lock_period),
+                };
+                execute(deps.as_mut(), env.clone(), user_funds.clone(), msg).unwrap();
+            }
+            let msg = ExecuteMsg::SetEarlyWithdrawConfig {
+                config: Some(EarlyWithdrawConfig {
+                    penalty_rate: Decimal::percent(50),
+                    penalty_floor: Decimal::percent(10),
+                    penalty_destination: PenaltyDestination::Lockers,
+                }),
             };
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+            env.block.time = env.block.time.plus_seconds(MIN_LOCK_PERIOD);
+            let user_info = mock_info(user_addr.as_str(), &[]);
+            let msg = ExecuteMsg::RequestWithdraw { position: 1, amount: None };
+            execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap();
+
+            let msg = ExecuteMsg::Pause {
+                actions: vec![
+                    PausableAction::Lock,
+                    PausableAction::Claim,
+                    PausableAction::EarlyWithdraw,
+                    PausableAction::AddIncome
+                ],
+            };
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+            // 1. Exits of locked funds are held
+            let msg = ExecuteMsg::EarlyWithdraw { position: 3 };
+            let err = execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap_err();
+            assert_eq!(err, ContractError::Paused { action: "early_withdraw".to_string() });
+
+            // 2. Expired positions can still be requested and withdrawn, whatever is paused
+            let msg = ExecuteMsg::RequestWithdraw { position: 2, amount: None };
+            execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap();
+
+            env.block.time = env.block.time.plus_seconds(WITHDRAW_DELAY);
+            for position in [1, 2] {
+                let msg = ExecuteMsg::Withdraw { position };
+                let resp = execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap();
+                assert!(
+                    resp.messages.contains(
+                        &SubMsg::new(BankMsg::Send {
+                            to_address: user_addr.to_string(),
+                            amount: coins(amount.u128(), MOCK_LOCKED_DENOM),
+                        })
+                    )
+                );
+            }
+            assert_eq!(
+                internal_funcs::account_positions(deps.as_ref().storage, &user_addr).unwrap().len(),
+                1
+            );
+        }
 
-            TOKEN_STATE.save(deps.as_mut().storage, &token_state).unwrap();
+        #[test]
+        fn test_execute_update_config() {
+            let mut deps = mock_dependencies();
+            let env = mock_env();
+            let info = mock_info("creator", &[]);
 
-            let query_response = query(
-                deps.as_ref(),
-                env.clone(),
-                QueryMsg::VeTokenInfo {}
-            ).unwrap();
+            mock_instantiate(deps.as_mut(), env.clone(), info.to_owned());
 
-            let query_response_decoded: VeTokenInfoResponse = from_binary(&query_response).unwrap();
+            let msg = ExecuteMsg::UpdateConfig {
+                min_lock_period: Some(Uint64::from(60u64)),
+                max_lock_period: Some(Uint64::from(3600u64)),
+                withdraw_delay: None,
+            };
+
+            // 1. Only config admin can update
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("user", &[]),
+                msg.clone()
+            ).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+
+            // 2. Max lock period can't be zero
+            let err = execute(deps.as_mut(), env.clone(), info.clone(), ExecuteMsg::UpdateConfig {
+                min_lock_period: Some(Uint64::zero()),
+                max_lock_period: Some(Uint64::zero()),
+                withdraw_delay: None,
+            }).unwrap_err();
+            assert_eq!(err, ContractError::InvalidLockPeriods {});
+
+            let resp = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+            let expected_response: Response<Empty> = Response::new().add_event(
+                ContractEvent::make_config_updated(
+                    Uint64::from(60u64),
+                    Uint64::from(3600u64),
+                    Uint64::from(WITHDRAW_DELAY)
+                ).to_cosmos_event()
+            );
+            assert_eq!(expected_response, resp);
+
+            // 3. Lock uses the new limits
+            let amount = apply_decimals(Uint128::from(1u8));
+            deps.querier.update_wasm(cw20_mock_querier(amount));
+
+            let token_info = mock_info(MOCK_LOCKED_TOKEN, &[]);
+            let msg = mock_cw20_receive("user", amount, ReceiveMsg::Lock {
+                new_locked_until: Uint64::from(env.block.time.seconds() + 3601),
+            });
+            let err = execute(deps.as_mut(), env.clone(), token_info.clone(), msg).unwrap_err();
+            assert_eq!(err, ContractError::LockPeriodTooLong {});
+
+            let msg = mock_cw20_receive("user", amount, ReceiveMsg::Lock {
+                new_locked_until: Uint64::from(env.block.time.seconds() + 1800),
+            });
+            execute(deps.as_mut(), env, token_info, msg).unwrap();
+
+            let user_addr = Addr::unchecked("user");
+            let user_state = USER_STATE.load(deps.as_ref().storage, &user_addr).unwrap();
+            assert_eq!(user_state.balance, amount / Uint128::from(2u8));
+        }
+
+        #[test]
+        fn test_execute_emergency_shutdown() {
+            let mut deps = mock_dependencies();
+            let env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate(deps.as_mut(), env.clone(), info.to_owned());
+
+            let msg = ExecuteMsg::EmergencyShutdown { reward_policy: ShutdownRewardPolicy::Settle };
+
+            // 1. Only owner can shut down
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("user", &[]),
+                msg.clone()
+            ).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+
+            let resp = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+
+            let expected_response: Response<Empty> = Response::new().add_event(
+                ContractEvent::make_emergency_shutdown(
+                    "settle".to_string(),
+                    Uint64::from(env.block.time.seconds())
+                ).to_cosmos_event()
+            );
+            assert_eq!(expected_response, resp);
+
+            // 2. Irreversible
+            let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+            assert_eq!(err, ContractError::Shutdown {});
+
+            // 3. No new locks and income
+            let token_info = mock_info(MOCK_LOCKED_TOKEN, &[]);
+            let lock_msg = mock_cw20_receive(
+                "user",
+                apply_decimals(Uint128::from(1u8)),
+                ReceiveMsg::Lock {
+                    new_locked_until: Uint64::from(env.block.time.seconds() + MIN_LOCK_PERIOD * 2),
+                }
+            );
+            let err = execute(deps.as_mut(), env.clone(), token_info.clone(), lock_msg).unwrap_err();
+            assert_eq!(err, ContractError::Shutdown {});
+
+            let income_msg = mock_cw20_receive(
+                "creator",
+                Uint128::from(1u8),
+                ReceiveMsg::AddIncome {}
+            );
+            let err = execute(deps.as_mut(), env, token_info, income_msg).unwrap_err();
+            assert_eq!(err, ContractError::Shutdown {});
+        }
+
+        #[test]
+        fn test_execute_withdraw_after_shutdown() {
+            for reward_policy in [ShutdownRewardPolicy::Settle, ShutdownRewardPolicy::Forfeit] {
+                let mut deps = mock_dependencies();
+                let env = mock_env();
+                let info = mock_info("creator", &[]);
+
+                mock_instantiate(deps.as_mut(), env.clone(), info.to_owned());
+
+                let user_addr = Addr::unchecked("user");
+                let user_info = mock_info(user_addr.as_str(), &[]);
+                let locked_balance = apply_decimals(Uint128::from(1u8));
+                let reward_per_token = apply_decimals(Uint128::from(1u8)) / Uint128::from(10u8);
+
+                // Locked for the maximal period, nothing requested
+                internal_funcs
+                    ::set_balance(deps.as_mut(), &env, &user_info, &user_addr, locked_balance)
+                    .unwrap();
+                let mut user_state = USER_STATE.load(deps.as_ref().storage, &user_addr).unwrap();
+                user_state.locked_balance = locked_balance;
+                USER_STATE.save(deps.as_mut().storage, &user_addr, &user_state).unwrap();
+                let position_id = mock_position(
+                    deps.as_mut().storage,
+                    &user_addr,
+                    locked_balance,
+                    Uint64::from(env.block.time.seconds() + MAX_LOCK_PERIOD)
+                );
+
+                TOKEN_STATE.update(
+                    deps.as_mut().storage,
+                    |mut state| -> StdResult<TokenState> {
+                        state.total_locked = locked_balance;
+                        state.reward_per_token = reward_per_token;
+                        Ok(state)
+                    }
+                ).unwrap();
+
+                deps.querier.update_wasm(cw20_mock_querier(apply_decimals(Uint128::from(1000u16))));
+
+                // Exits after shutdown are never paused
+                let msg = ExecuteMsg::Pause {
+                    actions: vec![PausableAction::Lock, PausableAction::EarlyWithdraw],
+                };
+                execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+                let msg = ExecuteMsg::EmergencyShutdown { reward_policy };
+                execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+                let resp = execute(
+                    deps.as_mut(),
+                    env.clone(),
+                    user_info.clone(),
+                    ExecuteMsg::Withdraw { position: position_id }
+                ).unwrap();
+
+                let withdraw_message = CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: MOCK_LOCKED_TOKEN.to_string(),
+                    msg: to_binary(
+                        &(Cw20ExecuteMsg::Transfer {
+                            recipient: user_addr.to_string(),
+                            amount: locked_balance,
+                        })
+                    ).unwrap(),
+                    funds: vec![],
+                });
+                let claim_message = CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: MOCK_LOCKED_TOKEN.to_string(),
+                    msg: to_binary(
+                        &(Cw20ExecuteMsg::Transfer {
+                            recipient: user_addr.to_string(),
+                            amount: reward_per_token,
+                        })
+                    ).unwrap(),
+                    funds: vec![],
+                });
+
+                let messages: Vec<CosmosMsg> = resp.messages
+                    .iter()
+                    .map(|msg| msg.msg.clone())
+                    .collect();
+                match reward_policy {
+                    ShutdownRewardPolicy::Settle =>
+                        assert_eq!(messages, vec![claim_message, withdraw_message]),
+                    ShutdownRewardPolicy::Forfeit => assert_eq!(messages, vec![withdraw_message]),
+                }
+
+                // ve balance is burned and lock is released
+                let user_state = USER_STATE.load(deps.as_ref().storage, &user_addr).unwrap();
+                assert_eq!(user_state.balance, Uint128::zero());
+                assert_eq!(user_state.locked_balance, Uint128::zero());
+                assert_eq!(user_state.reward_snapshot, reward_per_token);
+
+                let token_state = TOKEN_STATE.load(deps.as_ref().storage).unwrap();
+                assert_eq!(token_state.total_locked, Uint128::zero());
+                assert_eq!(token_state.total_supply, Uint128::zero());
+
+                // Nothing left
+                let err = execute(
+                    deps.as_mut(),
+                    env.clone(),
+                    user_info.clone(),
+                    ExecuteMsg::Withdraw { position: position_id }
+                ).unwrap_err();
+                assert_eq!(err, ContractError::PositionNotFound { id: position_id });
+
+                let msg = ExecuteMsg::Claim { recipient: None };
+                let claim_result = execute(deps.as_mut(), env.clone(), user_info.clone(), msg);
+                match reward_policy {
+                    ShutdownRewardPolicy::Settle => {
+                        claim_result.unwrap();
+                    }
+                    ShutdownRewardPolicy::Forfeit => {
+                        assert_eq!(claim_result.unwrap_err(), ContractError::Shutdown {});
+                    }
+                }
+
+                // Forfeited rewards go to whoever the owner sends them to
+                let sweep_msg = ExecuteMsg::SweepForfeitedRewards {
+                    recipient: "treasury".to_string(),
+                };
+                let err = execute(
+                    deps.as_mut(),
+                    env.clone(),
+                    user_info,
+                    sweep_msg.clone()
+                ).unwrap_err();
+                assert_eq!(err, ContractError::Unauthorized {});
+
+                if reward_policy == ShutdownRewardPolicy::Forfeit {
+                    let resp = execute(deps.as_mut(), env.clone(), info.clone(), sweep_msg.clone())
+                        .unwrap();
+                    let sweep_message = CosmosMsg::Wasm(WasmMsg::Execute {
+                        contract_addr: MOCK_LOCKED_TOKEN.to_string(),
+                        msg: to_binary(
+                            &(Cw20ExecuteMsg::Transfer {
+                                recipient: "treasury".to_string(),
+                                amount: reward_per_token,
+                            })
+                        ).unwrap(),
+                        funds: vec![],
+                    });
+                    assert_eq!(resp.messages, vec![SubMsg::new(sweep_message)]);
+                }
+                let err = execute(deps.as_mut(), env, info, sweep_msg).unwrap_err();
+                assert_eq!(err, ContractError::NothingToSweep {});
+            }
+        }
+
+        #[test]
+        fn test_execute_native_locked_token() {
+            let mut deps = mock_dependencies();
+            let mut env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate_native(deps.as_mut(), env.clone(), info.to_owned());
+
+            let user_addr = Addr::unchecked("user");
+            let amount = apply_decimals(Uint128::from(1u8));
+            let new_locked_until = Uint64::from(env.block.time.seconds() + MIN_LOCK_PERIOD * 2);
+            let lock_msg = ExecuteMsg::Lock { amount, new_locked_until };
+
+            // 1. Funds must match amount
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(user_addr.as_str(), &coins(amount.u128() - 1, MOCK_LOCKED_DENOM)),
+                lock_msg.clone()
+            ).unwrap_err();
+            assert_eq!(err, ContractError::FundsMismatch {
+                expected: amount,
+                received: amount - Uint128::from(1u8),
+            });
+
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(user_addr.as_str(), &coins(amount.u128(), "uother")),
+                lock_msg.clone()
+            ).unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::Payment(PaymentError::MissingDenom(MOCK_LOCKED_DENOM.to_string()))
+            );
+
+            // 2. CW20 hook is not accepted
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(MOCK_LOCKED_TOKEN, &[]),
+                mock_cw20_receive(user_addr.as_str(), amount, ReceiveMsg::Lock {
+                    new_locked_until,
+                })
+            ).unwrap_err();
+            assert_eq!(err, ContractError::InvalidToken {});
+
+            // 3. Lock and income are credited from funds, reserves come from the bank balance
+            deps.querier.update_balance(
+                env.contract.address.clone(),
+                coins(amount.u128() * 2, MOCK_LOCKED_DENOM)
+            );
+            let user_info = mock_info(user_addr.as_str(), &coins(amount.u128(), MOCK_LOCKED_DENOM));
+            execute(deps.as_mut(), env.clone(), user_info, lock_msg).unwrap();
+
+            let user_state = USER_STATE.load(deps.as_ref().storage, &user_addr).unwrap();
+            assert_eq!(user_state.locked_balance, amount);
+
+            deps.querier.update_balance(
+                env.contract.address.clone(),
+                coins(amount.u128() - 1, MOCK_LOCKED_DENOM)
+            );
+            let err = internal_funcs::check_reserves(deps.as_ref(), &env).unwrap_err();
+            assert_eq!(err, ContractError::InsufficientReserves {});
+
+            let reserves = apply_decimals(Uint128::from(1_000_000u32));
+            deps.querier.update_balance(
+                env.contract.address.clone(),
+                coins(reserves.u128(), MOCK_LOCKED_DENOM)
+            );
+
+            let income_info = mock_info("creator", &coins(amount.u128(), MOCK_LOCKED_DENOM));
+            let msg = ExecuteMsg::AddIncome { add_amount: amount };
+            execute(deps.as_mut(), env.clone(), income_info, msg).unwrap();
+
+            // 4. Reward and withdraw are paid with bank sends
+            env.block.time = env.block.time.plus_seconds(MIN_LOCK_PERIOD * 2);
+            env.block.height += 500;
+
+            let user_info = mock_info(user_addr.as_str(), &[]);
+            let msg = ExecuteMsg::Claim { recipient: None };
+            let resp = execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap();
+            assert_eq!(resp.messages.len(), 1);
+            match &resp.messages[0].msg {
+                CosmosMsg::Bank(BankMsg::Send { to_address, amount: sent }) => {
+                    assert_eq!(to_address, user_addr.as_str());
+                    assert_eq!(sent[0].denom, MOCK_LOCKED_DENOM);
+                }
+                msg => panic!("unexpected message {:?}", msg),
+            }
+
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                user_info.clone(),
+                ExecuteMsg::RequestWithdraw { position: 1, amount: None }
+            ).unwrap();
+            env.block.time = env.block.time.plus_seconds(WITHDRAW_DELAY);
+            let msg = ExecuteMsg::Withdraw { position: 1 };
+            let resp = execute(deps.as_mut(), env, user_info, msg).unwrap();
+
+            let withdraw_message = CosmosMsg::Bank(BankMsg::Send {
+                to_address: user_addr.to_string(),
+                amount: coins(amount.u128(), MOCK_LOCKED_DENOM),
+            });
+            assert_eq!(resp.messages.last().unwrap().msg, withdraw_message);
+        }
+
+        #[test]
+        fn test_execute_claim_to_recipient() {
+            let mut deps = mock_dependencies();
+            let mut env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate_native(deps.as_mut(), env.clone(), info.to_owned());
+
+            let user_addr = Addr::unchecked("user");
+            let amount = apply_decimals(Uint128::from(1u8));
+            let reserves = apply_decimals(Uint128::from(1_000_000u32));
+            deps.querier.update_balance(
+                env.contract.address.clone(),
+                coins(reserves.u128(), MOCK_LOCKED_DENOM)
+            );
+
+            let new_locked_until = Uint64::from(env.block.time.seconds() + MAX_LOCK_PERIOD);
+            let user_funds = mock_info(user_addr.as_str(), &coins(amount.u128(), MOCK_LOCKED_DENOM));
+            let msg = ExecuteMsg::Lock { amount, new_locked_until };
+            execute(deps.as_mut(), env.clone(), user_funds, msg).unwrap();
+
+            let income = apply_decimals(Uint128::from(1000u32));
+            let income_info = mock_info("creator", &coins(income.u128(), MOCK_LOCKED_DENOM));
+            let msg = ExecuteMsg::AddIncome { add_amount: income };
+            execute(deps.as_mut(), env.clone(), income_info, msg).unwrap();
+
+            let paid_to = |resp: &Response| -> Vec<String> {
+                resp.messages
+                    .iter()
+                    .map(|sub_msg| {
+                        match &sub_msg.msg {
+                            CosmosMsg::Bank(BankMsg::Send { to_address, .. }) =>
+                                to_address.clone(),
+                            msg => panic!("unexpected message {:?}", msg),
+                        }
+                    })
+                    .collect()
+            };
+
+            // 1. The account chooses where its rewards go
+            env.block.height += 10;
+            let user_info = mock_info(user_addr.as_str(), &[]);
+            let msg = ExecuteMsg::Claim { recipient: Some("treasury".to_string()) };
+            let resp = execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap();
+            assert_eq!(paid_to(&resp), vec!["treasury".to_string()]);
+            let claim_event = resp.events
+                .iter()
+                .find(|event| event.ty == "claim")
+                .unwrap();
+            assert!(claim_event.attributes.contains(&attr("account", "user")));
+            assert!(claim_event.attributes.contains(&attr("recipient", "treasury")));
+
+            // 2. Keepers need the account's approval
+            env.block.height += 10;
+            let keeper_info = mock_info("keeper", &[]);
+            let claim_for = ExecuteMsg::ClaimFor { account: user_addr.to_string() };
+            let err = execute(deps.as_mut(), env.clone(), keeper_info.clone(), claim_for.clone())
+                .unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+
+            let msg = ExecuteMsg::ApproveClaimOperator { operator: "keeper".to_string() };
+            let resp = execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap();
+            let expected_response: Response<Empty> = Response::new().add_event(
+                ContractEvent::make_claim_operator(
+                    user_addr.to_string(),
+                    "keeper".to_string(),
+                    true
+                ).to_cosmos_event()
+            );
+            assert_eq!(expected_response, resp);
+
+            let msg = QueryMsg::ClaimOperators {
+                account: user_addr.to_string(),
+                start_after: None,
+                limit: None,
+            };
+            let resp: ClaimOperatorsResponse = from_binary(
+                &query(deps.as_ref(), env.clone(), msg).unwrap()
+            ).unwrap();
+            assert_eq!(resp.operators, vec![Addr::unchecked("keeper")]);
+
+            // 3. Keeper claims are always paid to the account
+            let resp = execute(deps.as_mut(), env.clone(), keeper_info.clone(), claim_for.clone())
+                .unwrap();
+            assert_eq!(paid_to(&resp), vec![user_addr.to_string()]);
+            let claim_event = resp.events
+                .iter()
+                .find(|event| event.ty == "claim")
+                .unwrap();
+            assert!(claim_event.attributes.contains(&attr("recipient", "user")));
+
+            // 4. Revoked keepers can't claim anymore
+            env.block.height += 10;
+            let msg = ExecuteMsg::RevokeClaimOperator { operator: "keeper".to_string() };
+            execute(deps.as_mut(), env.clone(), user_info, msg).unwrap();
+            let err = execute(deps.as_mut(), env, keeper_info, claim_for).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+        }
+
+        #[test]
+        fn test_execute_kick() {
+            let mut deps = mock_dependencies();
+            let mut env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate_native(deps.as_mut(), env.clone(), info.to_owned());
+
+            let reserves = apply_decimals(Uint128::from(1_000_000u32));
+            deps.querier.update_balance(
+                env.contract.address.clone(),
+                coins(reserves.u128(), MOCK_LOCKED_DENOM)
+            );
+
+            let amount = apply_decimals(Uint128::from(1u8));
+            let locks = [
+                ("alice", env.block.time.seconds() + MAX_LOCK_PERIOD),
+                ("bob", env.block.time.seconds() + MIN_LOCK_PERIOD),
+            ];
+            for (user, locked_until) in locks {
+                let user_funds = mock_info(user, &coins(amount.u128(), MOCK_LOCKED_DENOM));
+                let msg = ExecuteMsg::Lock {
+                    amount,
+                    new_locked_until: Uint64::from(locked_until),
+                };
+                execute(deps.as_mut(), env.clone(), user_funds, msg).unwrap();
+            }
+
+            let income = apply_decimals(Uint128::from(1000u32));
+            let income_info = mock_info("creator", &coins(income.u128(), MOCK_LOCKED_DENOM));
+            let msg = ExecuteMsg::AddIncome { add_amount: income };
+            execute(deps.as_mut(), env.clone(), income_info, msg).unwrap();
+
+            let pending = |deps: Deps, env: &Env, user: &str| -> Uint128 {
+                let msg = QueryMsg::PendingAccountReward { address: Addr::unchecked(user) };
+                let resp: PendingAccountRewardResponse = from_binary(
+                    &query(deps, env.clone(), msg).unwrap()
+                ).unwrap();
+                resp.pending_account_rewards[0].amount
+            };
+            let kick = |user: &str| ExecuteMsg::Kick { account: user.to_string() };
+            let keeper_info = mock_info("keeper", &[]);
+
+            // 1. A balance just stored can't be kicked
+            let err = execute(deps.as_mut(), env.clone(), keeper_info.clone(), kick("bob"))
+                .unwrap_err();
+            assert_eq!(err, ContractError::NothingToKick {});
+
+            // 2. Untouched, the expired lock keeps its stored balance and earns
+            env.block.time = env.block.time.plus_seconds(MIN_LOCK_PERIOD + 1);
+            env.block.height += 10;
+            let bob_before = pending(deps.as_ref(), &env, "bob");
+            assert!(!bob_before.is_zero());
+
+            // 3. Anyone kicks it, the rewards so far are paid to bob
+            let resp = execute(deps.as_mut(), env.clone(), keeper_info.clone(), kick("bob"))
+                .unwrap();
+            assert_eq!(
+                resp.messages[0].msg,
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: "bob".to_string(),
+                    amount: coins(bob_before.u128(), MOCK_LOCKED_DENOM),
+                })
+            );
+            let kick_event = resp.events
+                .iter()
+                .find(|event| event.ty == "kick")
+                .unwrap();
+            assert!(kick_event.attributes.contains(&attr("account", "bob")));
+            assert!(kick_event.attributes.contains(&attr("sender", "keeper")));
+            assert!(kick_event.attributes.contains(&attr("new_balance", "0")));
+
+            let resp: cw20::BalanceResponse = from_binary(
+                &query(deps.as_ref(), env.clone(), QueryMsg::Balance {
+                    address: "bob".to_string(),
+                }).unwrap()
+            ).unwrap();
+            assert_eq!(resp.balance, Uint128::zero());
+
+            // 4. Once kicked, the expired lock stops earning while alice keeps earning
+            let alice_before = pending(deps.as_ref(), &env, "alice");
+            env.block.height += 10;
+            assert_eq!(pending(deps.as_ref(), &env, "bob"), Uint128::zero());
+            assert!(pending(deps.as_ref(), &env, "alice") > alice_before);
+
+            let err = execute(deps.as_mut(), env, keeper_info, kick("bob")).unwrap_err();
+            assert_eq!(err, ContractError::NothingToKick {});
+        }
+
+        #[test]
+        fn test_execute_compound() {
+            let mut deps = mock_dependencies();
+            let mut env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate_native(deps.as_mut(), env.clone(), info.to_owned());
+
+            let user_addr = Addr::unchecked("user");
+            let amount = apply_decimals(Uint128::from(1u8));
+            let reserves = apply_decimals(Uint128::from(1_000_000u32));
+            deps.querier.update_balance(
+                env.contract.address.clone(),
+                coins(reserves.u128(), MOCK_LOCKED_DENOM)
+            );
+
+            let user_funds = mock_info(user_addr.as_str(), &coins(amount.u128(), MOCK_LOCKED_DENOM));
+            for lock_period in [MAX_LOCK_PERIOD, MAX_LOCK_PERIOD / 2] {
+                let msg = ExecuteMsg::Lock {
+                    amount,
+                    new_locked_until: Uint64::from(env.block.time.seconds() + lock_period),
+                };
+                execute(deps.as_mut(), env.clone(), user_funds.clone(), msg).unwrap();
+            }
+
+            let income = apply_decimals(Uint128::from(1000u32));
+            let income_info = mock_info("creator", &coins(income.u128(), MOCK_LOCKED_DENOM));
+            let msg = ExecuteMsg::AddIncome { add_amount: income };
+            execute(deps.as_mut(), env.clone(), income_info, msg).unwrap();
+
+            env.block.height += 10;
+
+            // 1. Keepers can't compound for accounts that didn't opt in
+            let keeper_info = mock_info("keeper", &[]);
+            let compound_for = ExecuteMsg::CompoundFor { account: user_addr.to_string() };
+            let err = execute(deps.as_mut(), env.clone(), keeper_info.clone(), compound_for.clone())
+                .unwrap_err();
+            assert_eq!(err, ContractError::AutoCompoundDisabled {});
+
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("other", &[]),
+                ExecuteMsg::Compound {}
+            ).unwrap_err();
+            assert_eq!(err, ContractError::NoActiveLock {});
+
+            // 2. Rewards go to the longest position without any transfer
+            let user_info = mock_info(user_addr.as_str(), &[]);
+            let msg = ExecuteMsg::Compound {};
+            let resp = execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap();
+            assert!(resp.messages.is_empty());
+
+            let position = POSITIONS.load(deps.as_ref().storage, 1).unwrap();
+            let compounded = position.locked_balance - amount;
+            assert!(!compounded.is_zero());
+            assert_eq!(POSITIONS.load(deps.as_ref().storage, 2).unwrap().locked_balance, amount);
+
+            let user_state = USER_STATE.load(deps.as_ref().storage, &user_addr).unwrap();
+            assert_eq!(user_state.locked_balance, amount * Uint128::from(2u8) + compounded);
+            assert_eq!(
+                user_state.balance,
+                position.balance + POSITIONS.load(deps.as_ref().storage, 2).unwrap().balance
+            );
+            assert_eq!(
+                TOKEN_STATE.load(deps.as_ref().storage).unwrap().total_locked,
+                user_state.locked_balance
+            );
+            assert!(
+                resp.events.contains(
+                    &ContractEvent::make_compound(
+                        user_addr.to_string(),
+                        1,
+                        compounded,
+                        position.locked_balance,
+                        user_state.balance
+                    ).to_cosmos_event()
+                )
+            );
+
+            let err = execute(deps.as_mut(), env.clone(), user_info.clone(), ExecuteMsg::Compound {})
+                .unwrap_err();
+            assert_eq!(err, ContractError::NothingToCompound {});
+
+            // 3. Opted-in accounts are compounded by keepers
+            let msg = ExecuteMsg::SetAutoCompound { enabled: true };
+            execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap();
+            let msg = QueryMsg::AutoCompoundAccounts { start_after: None, limit: None };
+            let resp: AutoCompoundAccountsResponse = from_binary(
+                &query(deps.as_ref(), env.clone(), msg).unwrap()
+            ).unwrap();
+            assert_eq!(resp.accounts, vec![user_addr.clone()]);
+
+            env.block.height += 100;
+            let resp = execute(deps.as_mut(), env.clone(), keeper_info.clone(), compound_for.clone())
+                .unwrap();
+            assert!(resp.messages.is_empty());
+            let new_position = POSITIONS.load(deps.as_ref().storage, 1).unwrap();
+            assert!(new_position.locked_balance > position.locked_balance);
+
+            let msg = ExecuteMsg::SetAutoCompound { enabled: false };
+            execute(deps.as_mut(), env.clone(), user_info, msg).unwrap();
+            env.block.height += 10;
+            let err = execute(deps.as_mut(), env, keeper_info, compound_for).unwrap_err();
+            assert_eq!(err, ContractError::AutoCompoundDisabled {});
+        }
+
+        #[test]
+        fn test_execute_partial_withdraw_and_cancel() {
+            let mut deps = mock_dependencies();
+            let mut env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate_native(deps.as_mut(), env.clone(), info.to_owned());
+
+            let user_addr = Addr::unchecked("user");
+            let unit = apply_decimals(Uint128::from(1u8));
+            let amount = unit * Uint128::from(3u8);
+            let reserves = apply_decimals(Uint128::from(1_000_000u32));
+            deps.querier.update_balance(
+                env.contract.address.clone(),
+                coins(reserves.u128(), MOCK_LOCKED_DENOM)
+            );
+
+            let user_funds = mock_info(user_addr.as_str(), &coins(amount.u128(), MOCK_LOCKED_DENOM));
+            let msg = ExecuteMsg::Lock {
+                amount,
+                new_locked_until: Uint64::from(env.block.time.seconds() + MIN_LOCK_PERIOD),
+            };
+            execute(deps.as_mut(), env.clone(), user_funds, msg).unwrap();
+            env.block.time = env.block.time.plus_seconds(MIN_LOCK_PERIOD);
+
+            // 1. Requested amount must fit the position
+            let user_info = mock_info(user_addr.as_str(), &[]);
+            for invalid in [Uint128::zero(), amount + Uint128::one()] {
+                let msg = ExecuteMsg::RequestWithdraw { position: 1, amount: Some(invalid) };
+                let err = execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap_err();
+                assert_eq!(err, ContractError::InvalidWithdrawAmount {});
+            }
+            let msg = ExecuteMsg::CancelWithdrawRequest { position: 1, relock_until: None };
+            let err = execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap_err();
+            assert_eq!(err, ContractError::NoWithdrawRequest { id: 1 });
+
+            // 2. Partial withdraw keeps the rest of the position without a request
+            let msg = ExecuteMsg::RequestWithdraw { position: 1, amount: Some(unit) };
+            execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap();
+            let msg = ExecuteMsg::ExtendLock {
+                position: 1,
+                new_locked_until: Uint64::from(env.block.time.seconds() + MAX_LOCK_PERIOD),
+            };
+            let err = execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap_err();
+            assert_eq!(err, ContractError::WithdrawRequested { id: 1 });
+
+            env.block.time = env.block.time.plus_seconds(WITHDRAW_DELAY);
+            let msg = ExecuteMsg::Withdraw { position: 1 };
+            let resp = execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap();
+            assert_eq!(
+                resp.messages.last().unwrap().msg,
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: user_addr.to_string(),
+                    amount: coins(unit.u128(), MOCK_LOCKED_DENOM),
+                })
+            );
+            assert!(
+                resp.events.contains(
+                    &ContractEvent::make_withdraw(user_addr.to_string(), 1, unit).to_cosmos_event()
+                )
+            );
+            let position = POSITIONS.load(deps.as_ref().storage, 1).unwrap();
+            assert_eq!(position.locked_balance, amount - unit);
+            assert_eq!(position.withdraw_at, Uint64::zero());
+            assert_eq!(position.withdraw_amount, Uint128::zero());
+            let user_state = USER_STATE.load(deps.as_ref().storage, &user_addr).unwrap();
+            assert_eq!(user_state.locked_balance, amount - unit);
+            assert_eq!(
+                TOKEN_STATE.load(deps.as_ref().storage).unwrap().total_locked,
+                amount - unit
+            );
+
+            // 3. Cancelling with relock restores the ve balance
+            let msg = ExecuteMsg::RequestWithdraw { position: 1, amount: Some(unit) };
+            execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap();
+            let relock_until = Uint64::from(env.block.time.seconds() + MAX_LOCK_PERIOD / 2);
+            let msg = ExecuteMsg::CancelWithdrawRequest {
+                position: 1,
+                relock_until: Some(relock_until),
+            };
+            let resp = execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap();
+            assert!(
+                resp.events.contains(
+                    &ContractEvent::make_withdraw_request_cancelled(
+                        user_addr.to_string(),
+                        1,
+                        unit
+                    ).to_cosmos_event()
+                )
+            );
+            let position = POSITIONS.load(deps.as_ref().storage, 1).unwrap();
+            assert_eq!(position.locked_until, relock_until);
+            assert_eq!(position.withdraw_at, Uint64::zero());
+            assert_eq!(position.locked_balance, amount - unit);
+            assert!(!position.balance.is_zero());
+            assert_eq!(
+                query_balance(deps.as_ref(), user_addr.to_string()).unwrap().balance,
+                position.balance
+            );
+
+            env.block.time = env.block.time.plus_seconds(WITHDRAW_DELAY);
+            let msg = ExecuteMsg::Withdraw { position: 1 };
+            let err = execute(deps.as_mut(), env, user_info, msg).unwrap_err();
+            assert_eq!(err, ContractError::WithdrawDelayNotOver {});
+        }
+
+        #[test]
+        fn test_execute_early_withdraw() {
+            let mut deps = mock_dependencies();
+            let mut env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate_native(deps.as_mut(), env.clone(), info.to_owned());
+
+            let user_addr = Addr::unchecked("user");
+            let treasury_addr = Addr::unchecked("treasury");
+            let amount = apply_decimals(Uint128::from(1u8));
+            let reserves = apply_decimals(Uint128::from(1_000_000u32));
+            deps.querier.update_balance(
+                env.contract.address.clone(),
+                coins(reserves.u128(), MOCK_LOCKED_DENOM)
+            );
+
+            let user_funds = mock_info(user_addr.as_str(), &coins(amount.u128(), MOCK_LOCKED_DENOM));
+            for lock_period in [MAX_LOCK_PERIOD / 2, MAX_LOCK_PERIOD, MIN_LOCK_PERIOD] {
+                let msg = ExecuteMsg::Lock {
+                    amount,
+                    new_locked_until: Uint64::from(env.block.time.seconds() + lock_period),
+                };
+                execute(deps.as_mut(), env.clone(), user_funds.clone(), msg).unwrap();
+            }
+            let other_funds = mock_info("other", &coins(amount.u128(), MOCK_LOCKED_DENOM));
+            let msg = ExecuteMsg::Lock {
+                amount,
+                new_locked_until: Uint64::from(env.block.time.seconds() + MAX_LOCK_PERIOD),
+            };
+            execute(deps.as_mut(), env.clone(), other_funds, msg).unwrap();
+
+            // 1. Disabled until the config admin sets a penalty
+            let user_info = mock_info(user_addr.as_str(), &[]);
+            let msg = ExecuteMsg::EarlyWithdraw { position: 1 };
+            let err = execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap_err();
+            assert_eq!(err, ContractError::EarlyWithdrawDisabled {});
+
+            let mut config = EarlyWithdrawConfig {
+                penalty_rate: Decimal::percent(150),
+                penalty_floor: Decimal::percent(10),
+                penalty_destination: PenaltyDestination::Lockers,
+            };
+            let msg = ExecuteMsg::SetEarlyWithdrawConfig { config: Some(config.clone()) };
+            let err = execute(deps.as_mut(), env.clone(), user_info.clone(), msg.clone())
+                .unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+            let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+            assert_eq!(err, ContractError::InvalidPenalty {});
+
+            config.penalty_rate = Decimal::percent(50);
+            let msg = ExecuteMsg::SetEarlyWithdrawConfig { config: Some(config.clone()) };
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+            // 2. Preview lists the locked positions, the penalty shrinks with the time left
+            let msg = QueryMsg::PreviewEarlyWithdraw { address: user_addr.to_string() };
+            let preview: PreviewEarlyWithdrawResponse = from_binary(
+                &query(deps.as_ref(), env.clone(), msg).unwrap()
+            ).unwrap();
+            assert_eq!(preview.positions.len(), 3);
+            for position in &preview.positions {
+                assert_eq!(position.locked_balance, amount);
+                assert_eq!(position.amount, amount - position.penalty);
+            }
+            assert!(preview.positions[0].penalty < preview.positions[1].penalty);
+            assert!(preview.positions[1].penalty <= amount / Uint128::from(2u8));
+            assert_eq!(preview.positions[2].penalty, amount * Decimal::percent(10));
+
+            // 3. Lockers penalty stays in the contract and raises the reward rate
+            let msg = ExecuteMsg::EarlyWithdraw { position: 1 };
+            let resp = execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap();
+            let expected = &preview.positions[0];
+            assert_eq!(
+                resp.messages.last().unwrap().msg,
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: user_addr.to_string(),
+                    amount: coins(expected.amount.u128(), MOCK_LOCKED_DENOM),
+                })
+            );
+            assert!(
+                resp.events.contains(
+                    &ContractEvent::make_early_withdraw(
+                        user_addr.to_string(),
+                        1,
+                        expected.amount,
+                        expected.penalty,
+                        "lockers".to_string()
+                    ).to_cosmos_event()
+                )
+            );
+            let token_state = TOKEN_STATE.load(deps.as_ref().storage).unwrap();
+            assert_eq!(token_state.total_locked, amount * Uint128::from(3u8));
+            assert_eq!(
+                token_state.reward_rate_stored,
+                expected.penalty / Uint128::from(token_state.distribution_period)
+            );
+            assert!(POSITIONS.may_load(deps.as_ref().storage, 1).unwrap().is_none());
+
+            // 4. Treasury penalty is sent away
+            config.penalty_destination = PenaltyDestination::Treasury {
+                address: treasury_addr.clone(),
+            };
+            let msg = ExecuteMsg::SetEarlyWithdrawConfig { config: Some(config) };
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+            let msg = ExecuteMsg::EarlyWithdraw { position: 2 };
+            let resp = execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap();
+            let expected = &preview.positions[1];
+            let sends: Vec<CosmosMsg> = resp.messages
+                .iter()
+                .map(|msg| msg.msg.clone())
+                .collect();
+            assert!(
+                sends.contains(
+                    &CosmosMsg::Bank(BankMsg::Send {
+                        to_address: user_addr.to_string(),
+                        amount: coins(expected.amount.u128(), MOCK_LOCKED_DENOM),
+                    })
+                )
+            );
+            assert!(
+                sends.contains(
+                    &CosmosMsg::Bank(BankMsg::Send {
+                        to_address: treasury_addr.to_string(),
+                        amount: coins(expected.penalty.u128(), MOCK_LOCKED_DENOM),
+                    })
+                )
+            );
+            let user_state = USER_STATE.load(deps.as_ref().storage, &user_addr).unwrap();
+            assert_eq!(user_state.locked_balance, amount);
+
+            // 5. Expired positions go through RequestWithdraw
+            env.block.time = env.block.time.plus_seconds(MIN_LOCK_PERIOD * 2);
+            let msg = ExecuteMsg::EarlyWithdraw { position: 3 };
+            let err = execute(deps.as_mut(), env.clone(), user_info, msg).unwrap_err();
+            assert_eq!(err, ContractError::LockExpired {});
+
+            let msg = QueryMsg::PreviewEarlyWithdraw { address: user_addr.to_string() };
+            let preview: PreviewEarlyWithdrawResponse = from_binary(
+                &query(deps.as_ref(), env.clone(), msg).unwrap()
+            ).unwrap();
+            assert!(preview.positions.is_empty());
+
+            let msg = ExecuteMsg::SetEarlyWithdrawConfig { config: None };
+            execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+            let msg = QueryMsg::EarlyWithdrawConfig {};
+            let resp: EarlyWithdrawConfigResponse = from_binary(
+                &query(deps.as_ref(), env, msg).unwrap()
+            ).unwrap();
+            assert_eq!(resp.config, None);
+        }
+
+        #[test]
+        fn test_execute_transfer_nft() {
+            let mut deps = mock_dependencies();
+            let mut env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate_native(deps.as_mut(), env.clone(), info.to_owned());
+
+            let seller_addr = Addr::unchecked("seller");
+            let buyer_addr = Addr::unchecked("buyer");
+            let amount = apply_decimals(Uint128::from(1u8));
+            let reserves = apply_decimals(Uint128::from(1_000_000u32));
+            deps.querier.update_balance(
+                env.contract.address.clone(),
+                coins(reserves.u128(), MOCK_LOCKED_DENOM)
+            );
+
+            let new_locked_until = Uint64::from(env.block.time.seconds() + MIN_LOCK_PERIOD * 2);
+            let seller_funds = mock_info(
+                seller_addr.as_str(),
+                &coins(amount.u128(), MOCK_LOCKED_DENOM)
+            );
+            let msg = ExecuteMsg::Lock { amount, new_locked_until };
+            execute(deps.as_mut(), env.clone(), seller_funds, msg).unwrap();
+
+            let income_info = mock_info("creator", &coins(amount.u128(), MOCK_LOCKED_DENOM));
+            let msg = ExecuteMsg::AddIncome { add_amount: amount };
+            execute(deps.as_mut(), env.clone(), income_info, msg).unwrap();
+
+            env.block.time = env.block.time.plus_seconds(MIN_LOCK_PERIOD);
+            env.block.height += 100;
+
+            // 1. Only the owner, approved spenders and operators can transfer
+            let msg = ExecuteMsg::TransferNft {
+                recipient: buyer_addr.to_string(),
+                token_id: "1".to_string(),
+            };
+            let err = execute(deps.as_mut(), env.clone(), mock_info("spender", &[]), msg.clone())
+                .unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+
+            let seller_info = mock_info(seller_addr.as_str(), &[]);
+            let approve_msg = ExecuteMsg::Approve {
+                spender: "spender".to_string(),
+                token_id: "1".to_string(),
+                expires: Some(Expiration::AtHeight(env.block.height)),
+            };
+            let err = execute(deps.as_mut(), env.clone(), seller_info.clone(), approve_msg)
+                .unwrap_err();
+            assert_eq!(err, ContractError::ApprovalExpired {});
+
+            let approve_msg = ExecuteMsg::Approve {
+                spender: "spender".to_string(),
+                token_id: "1".to_string(),
+                expires: None,
+            };
+            execute(deps.as_mut(), env.clone(), seller_info.clone(), approve_msg).unwrap();
+
+            let resp: OwnerOfResponse = from_binary(
+                &query(deps.as_ref(), env.clone(), QueryMsg::OwnerOf {
+                    token_id: "1".to_string(),
+                    include_expired: None,
+                }).unwrap()
+            ).unwrap();
+            assert_eq!(resp, OwnerOfResponse {
+                owner: seller_addr.to_string(),
+                approvals: vec![Approval {
+                    spender: "spender".to_string(),
+                    expires: Expiration::Never {},
+                }],
+            });
+
+            // 2. Nobody receives positions they didn't ask for
+            let stranger_msg = ExecuteMsg::TransferNft {
+                recipient: "stranger".to_string(),
+                token_id: "1".to_string(),
+            };
+            let err = execute(deps.as_mut(), env.clone(), seller_info.clone(), stranger_msg)
+                .unwrap_err();
+            assert_eq!(err, ContractError::LockForNotAllowed {});
+            assert_eq!(POSITIONS.load(deps.as_ref().storage, 1).unwrap().owner, seller_addr);
+
+            let err = execute(deps.as_mut(), env.clone(), mock_info("spender", &[]), msg.clone())
+                .unwrap_err();
+            assert_eq!(err, ContractError::LockForNotAllowed {});
+
+            let buyer_info = mock_info(buyer_addr.as_str(), &[]);
+            let allow_msg = |max_locked_until: Uint64| ExecuteMsg::AllowLockFor {
+                locker: "spender".to_string(),
+                max_locked_until,
+                max_positions: 1,
+            };
+            let too_early = new_locked_until - Uint64::from(1u8);
+            execute(deps.as_mut(), env.clone(), buyer_info.clone(), allow_msg(too_early)).unwrap();
+            let err = execute(deps.as_mut(), env.clone(), mock_info("spender", &[]), msg.clone())
+                .unwrap_err();
+            assert_eq!(err, ContractError::LockForTooLong {});
+            let allow = allow_msg(new_locked_until);
+            execute(deps.as_mut(), env.clone(), buyer_info.clone(), allow).unwrap();
+
+            // 3. Rewards accrued so far are paid to the seller on transfer
+            let resp = execute(deps.as_mut(), env.clone(), mock_info("spender", &[]), msg).unwrap();
+            match &resp.messages[0].msg {
+                CosmosMsg::Bank(BankMsg::Send { to_address, amount: sent }) => {
+                    assert_eq!(to_address, seller_addr.as_str());
+                    assert!(!sent[0].amount.is_zero());
+                }
+                msg => panic!("unexpected message {:?}", msg),
+            }
+
+            let position = POSITIONS.load(deps.as_ref().storage, 1).unwrap();
+            assert_eq!(position.owner, buyer_addr);
+            assert!(position.approvals.is_empty());
+            assert!(
+                resp.events.contains(
+                    &ContractEvent::make_lock_for_allowance(
+                        buyer_addr.to_string(),
+                        "spender".to_string(),
+                        Uint64::zero(),
+                        0
+                    ).to_cosmos_event()
+                )
+            );
+
+            let seller_state = USER_STATE.load(deps.as_ref().storage, &seller_addr).unwrap();
+            assert_eq!(seller_state.locked_balance, Uint128::zero());
+            assert_eq!(seller_state.balance, Uint128::zero());
+            let buyer_state = USER_STATE.load(deps.as_ref().storage, &buyer_addr).unwrap();
+            assert_eq!(buyer_state.locked_balance, amount);
+            assert_eq!(buyer_state.balance, position.balance);
+            assert_eq!(
+                buyer_state.reward_snapshot,
+                TOKEN_STATE.load(deps.as_ref().storage).unwrap().reward_per_token
+            );
+
+            let resp: TokensResponse = from_binary(
+                &query(deps.as_ref(), env.clone(), QueryMsg::Tokens {
+                    owner: buyer_addr.to_string(),
+                    start_after: None,
+                    limit: None,
+                }).unwrap()
+            ).unwrap();
+            assert_eq!(resp.tokens, vec!["1".to_string()]);
+            let resp: NftInfoResponse = from_binary(
+                &query(deps.as_ref(), env.clone(), QueryMsg::NftInfo {
+                    token_id: "1".to_string(),
+                }).unwrap()
+            ).unwrap();
+            assert_eq!(resp.extension, nft::PositionMetadata {
+                locked_balance: amount,
+                locked_until: new_locked_until,
+            });
+            let resp: NumTokensResponse = from_binary(
+                &query(deps.as_ref(), env.clone(), QueryMsg::NumTokens {}).unwrap()
+            ).unwrap();
+            assert_eq!(resp.count, 1);
+
+            // 4. The seller lost the withdraw rights
+            let msg = ExecuteMsg::RequestWithdraw { position: 1, amount: None };
+            let err = execute(deps.as_mut(), env.clone(), seller_info, msg).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+
+            // 5. Operators can send the position to a contract that allowed them
+            let msg = ExecuteMsg::ApproveAll { operator: "operator".to_string(), expires: None };
+            execute(deps.as_mut(), env.clone(), buyer_info, msg).unwrap();
+            let msg = ExecuteMsg::AllowLockFor {
+                locker: "operator".to_string(),
+                max_locked_until: new_locked_until,
+                max_positions: 1,
+            };
+            execute(deps.as_mut(), env.clone(), mock_info("vault", &[]), msg).unwrap();
+
+            let msg = ExecuteMsg::SendNft {
+                contract: "vault".to_string(),
+                token_id: "1".to_string(),
+                msg: Binary::from(b"{}".to_vec()),
+            };
+            let resp = execute(deps.as_mut(), env.clone(), mock_info("operator", &[]), msg)
+                .unwrap();
+            let receive_msg = nft::Cw721ReceiveMsg {
+                sender: "operator".to_string(),
+                token_id: "1".to_string(),
+                msg: Binary::from(b"{}".to_vec()),
+            };
+            assert_eq!(
+                resp.messages.last().unwrap().msg,
+                receive_msg.into_cosmos_msg("vault").unwrap()
+            );
+
+            // 6. The new owner withdraws the locked tokens
+            env.block.time = env.block.time.plus_seconds(MIN_LOCK_PERIOD);
+            env.block.height += 500;
+            let vault_info = mock_info("vault", &[]);
+            let msg = ExecuteMsg::RequestWithdraw { position: 1, amount: None };
+            execute(deps.as_mut(), env.clone(), vault_info.clone(), msg).unwrap();
+
+            env.block.time = env.block.time.plus_seconds(WITHDRAW_DELAY);
+            let msg = ExecuteMsg::Withdraw { position: 1 };
+            let resp = execute(deps.as_mut(), env.clone(), vault_info, msg).unwrap();
+            assert_eq!(
+                resp.messages.last().unwrap().msg,
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: "vault".to_string(),
+                    amount: coins(amount.u128(), MOCK_LOCKED_DENOM),
+                })
+            );
+
+            let resp: NumTokensResponse = from_binary(
+                &query(deps.as_ref(), env, QueryMsg::NumTokens {}).unwrap()
+            ).unwrap();
+            assert_eq!(resp.count, 0);
+        }
+
+        #[test]
+        fn test_execute_add_reward_asset() {
+            let mut deps = mock_dependencies();
+            let env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate(deps.as_mut(), env.clone(), info.to_owned());
+
+            let asset = Denom::Native("upartner".to_string());
+            let msg = ExecuteMsg::AddRewardAsset {
+                asset: asset.clone(),
+                distribution_period: Uint64::from(1000u16),
+            };
+
+            // 1. Only config admin
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("partner", &[]),
+                msg.clone()
+            ).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+
+            // 2. Locked token has its own stream
+            let err = execute(deps.as_mut(), env.clone(), info.clone(), ExecuteMsg::AddRewardAsset {
+                asset: Denom::Cw20(Addr::unchecked(MOCK_LOCKED_TOKEN)),
+                distribution_period: Uint64::from(1000u16),
+            }).unwrap_err();
+            assert_eq!(err, ContractError::InvalidToken {});
+
+            let err = execute(deps.as_mut(), env.clone(), info.clone(), ExecuteMsg::AddRewardAsset {
+                asset: asset.clone(),
+                distribution_period: Uint64::zero(),
+            }).unwrap_err();
+            assert_eq!(err, ContractError::ZeroDistributionPeriod {});
+
+            let resp = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+
+            let expected_response: Response<Empty> = Response::new().add_event(
+                ContractEvent::make_reward_asset_added(
+                    "native:upartner".to_string(),
+                    Uint64::from(1000u16)
+                ).to_cosmos_event()
+            );
+            assert_eq!(expected_response, resp);
+
+            let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+            assert_eq!(err, ContractError::RewardAssetExists {});
+
+            // 3. Registry is bounded
+            for i in 1..MAX_REWARD_ASSETS {
+                execute(deps.as_mut(), env.clone(), info.clone(), ExecuteMsg::AddRewardAsset {
+                    asset: Denom::Native(format!("upartner{}", i)),
+                    distribution_period: Uint64::from(1000u16),
+                }).unwrap();
+            }
+            let err = execute(deps.as_mut(), env.clone(), info.clone(), ExecuteMsg::AddRewardAsset {
+                asset: Denom::Native("upartner_last".to_string()),
+                distribution_period: Uint64::from(1000u16),
+            }).unwrap_err();
+            assert_eq!(err, ContractError::TooManyRewardAssets {});
+
+            let query_response = query(deps.as_ref(), env.clone(), QueryMsg::RewardAssets {});
+            let reward_assets: RewardAssetsResponse = from_binary(&query_response.unwrap()).unwrap();
+            assert_eq!(reward_assets.reward_assets.len(), MAX_REWARD_ASSETS);
+            assert_eq!(
+                reward_assets.reward_assets[0],
+                RewardAssetState::new(asset, Uint64::from(1000u16), Uint64::from(env.block.height))
+            );
+        }
+
+        #[test]
+        fn test_execute_balance_hooks() {
+            let mut deps = mock_dependencies();
+            let env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate(deps.as_mut(), env.clone(), info.to_owned());
+
+            let msg = ExecuteMsg::AddBalanceHook {
+                address: "gauge".to_string(),
+                on_error: HookErrorPolicy::Catch,
+            };
+
+            // 1. Only config admin
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("gauge", &[]),
+                msg.clone()
+            ).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+
+            let resp = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+
+            let expected_response: Response<Empty> = Response::new().add_event(
+                ContractEvent::make_balance_hook_added(
+                    "gauge".to_string(),
+                    "catch".to_string()
+                ).to_cosmos_event()
+            );
+            assert_eq!(expected_response, resp);
+
+            let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+            assert_eq!(err, ContractError::HookExists {});
+
+            // 2. Registry is bounded
+            for i in 1..MAX_BALANCE_HOOKS {
+                execute(deps.as_mut(), env.clone(), info.clone(), ExecuteMsg::AddBalanceHook {
+                    address: format!("farm{}", i),
+                    on_error: HookErrorPolicy::Revert,
+                }).unwrap();
+            }
+            let err = execute(deps.as_mut(), env.clone(), info.clone(), ExecuteMsg::AddBalanceHook {
+                address: "farm_last".to_string(),
+                on_error: HookErrorPolicy::Revert,
+            }).unwrap_err();
+            assert_eq!(err, ContractError::TooManyHooks {});
+
+            // 3. Remove
+            let msg = ExecuteMsg::RemoveBalanceHook { address: "gauge".to_string() };
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("gauge", &[]),
+                msg.clone()
+            ).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+
+            let resp = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+            let expected_response: Response<Empty> = Response::new().add_event(
+                ContractEvent::make_balance_hook_removed("gauge".to_string()).to_cosmos_event()
+            );
+            assert_eq!(expected_response, resp);
+
+            let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+            assert_eq!(err, ContractError::HookNotFound {});
+
+            let query_response = query(deps.as_ref(), env.clone(), QueryMsg::BalanceHooks {});
+            let hooks: BalanceHooksResponse = from_binary(&query_response.unwrap()).unwrap();
+            assert_eq!(hooks.hooks.len(), MAX_BALANCE_HOOKS - 1);
+            assert_eq!(hooks.hooks[0], BalanceHook {
+                address: Addr::unchecked("farm1"),
+                on_error: HookErrorPolicy::Revert,
+            });
+        }
+
+        #[test]
+        fn test_balance_hooks_called_on_balance_change() {
+            let mut deps = mock_dependencies();
+            let env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate(deps.as_mut(), env.clone(), info.to_owned());
+
+            for (address, on_error) in [
+                ("farm", HookErrorPolicy::Revert),
+                ("gauge", HookErrorPolicy::Catch),
+            ] {
+                execute(deps.as_mut(), env.clone(), info.clone(), ExecuteMsg::AddBalanceHook {
+                    address: address.to_string(),
+                    on_error,
+                }).unwrap();
+            }
+
+            let user_addr = Addr::unchecked("user");
+            let locked_until = Uint64::from(env.block.time.seconds() + MIN_LOCK_PERIOD);
+            USER_STATE.save(deps.as_mut().storage, &user_addr, &UserState::default()).unwrap();
+            mock_position(deps.as_mut().storage, &user_addr, Uint128::zero(), locked_until);
+
+            let amount = Uint128::from(100u8);
+            let resp = internal_funcs
+                ::set_balance(deps.as_mut(), &env, &info, &user_addr, amount)
+                .unwrap();
+
+            let hook_msg = hooks::VeBalanceChangedHookMsg {
+                account: user_addr.to_string(),
+                old_balance: Uint128::zero(),
+                new_balance: amount,
+                locked_until,
+            };
+            assert_eq!(resp.messages, vec![
+                SubMsg::new(hook_msg.clone().into_cosmos_msg("farm").unwrap()),
+                SubMsg::reply_on_error(
+                    hook_msg.into_cosmos_msg("gauge").unwrap(),
+                    BALANCE_HOOK_REPLY_ID
+                )
+            ]);
+            assert_eq!(
+                resp.messages[0].msg,
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: "farm".to_string(),
+                    msg: Binary::from(
+                        format!(
+                            concat!(
+                                r#"{{"ve_balance_changed_hook":{{"account":"user","#,
+                                r#""old_balance":"0","new_balance":"100","locked_until":"{}"}}}}"#
+                            ),
+                            locked_until
+                        ).as_bytes()
+                    ),
+                    funds: vec![],
+                })
+            );
+
+            // Unchanged balance doesn't call hooks
+            let resp = internal_funcs
+                ::set_balance(deps.as_mut(), &env, &info, &user_addr, amount)
+                .unwrap();
+            assert!(resp.messages.is_empty());
+
+            // Caught hook failures are reported
+            let resp = reply(deps.as_mut(), env.clone(), Reply {
+                id: BALANCE_HOOK_REPLY_ID,
+                result: SubMsgResult::Err("gauge failed".to_string()),
+            }).unwrap();
+            let expected_response: Response<Empty> = Response::new().add_event(
+                ContractEvent::make_balance_hook_failed("gauge failed".to_string()).to_cosmos_event()
+            );
+            assert_eq!(expected_response, resp);
+
+            let err = reply(deps.as_mut(), env, Reply {
+                id: 99,
+                result: SubMsgResult::Err("other".to_string()),
+            }).unwrap_err();
+            assert_eq!(err, ContractError::UnknownReplyId { id: 99 });
+        }
+
+        #[test]
+        fn test_execute_epoch_fees() {
+            let mut deps = mock_dependencies();
+            let mut env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate_native(deps.as_mut(), env.clone(), info.to_owned());
+
+            // alice locks for max period, bob for half of it
+            let amount = apply_decimals(Uint128::from(1u8));
+            let fees = Uint128::from(3000u16);
+            deps.querier.update_balance(
+                env.contract.address.clone(),
+                coins((amount * Uint128::from(2u8) + fees).u128(), MOCK_LOCKED_DENOM)
+            );
+            for (user, lock_period) in [("alice", MAX_LOCK_PERIOD), ("bob", MAX_LOCK_PERIOD / 2)] {
+                let new_locked_until = Uint64::from(env.block.time.seconds() + lock_period);
+                execute(
+                    deps.as_mut(),
+                    env.clone(),
+                    mock_info(user, &coins(amount.u128(), MOCK_LOCKED_DENOM)),
+                    ExecuteMsg::Lock { amount, new_locked_until }
+                ).unwrap();
+            }
+
+            // 1. Income manager only
+            let msg = ExecuteMsg::AddEpochIncome { add_amount: fees };
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("alice", &coins(fees.u128(), MOCK_LOCKED_DENOM)),
+                msg.clone()
+            ).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+
+            // 2. Income goes to the current epoch once it has ve supply at its start
+            let epoch_start = fee_distributor::epoch_start(Uint64::from(env.block.time.seconds()));
+            let next_epoch = epoch_start + Uint64::from(FEE_EPOCH);
+            env.block.time = env.block.time.plus_seconds(next_epoch.u64() - epoch_start.u64());
+
+            let income_info = mock_info("creator", &coins(fees.u128(), MOCK_LOCKED_DENOM));
+            let resp = execute(deps.as_mut(), env.clone(), income_info, msg).unwrap();
+            let expected_response: Response<Empty> = Response::new().add_event(
+                ContractEvent::make_epoch_income(next_epoch, fees).to_cosmos_event()
+            );
+            assert_eq!(expected_response, resp);
+
+            // Unclaimed fees are reserved
+            deps.querier.update_balance(
+                env.contract.address.clone(),
+                coins((amount * Uint128::from(2u8) + fees).u128() - 1, MOCK_LOCKED_DENOM)
+            );
+            let err = internal_funcs::check_reserves(deps.as_ref(), &env).unwrap_err();
+            assert_eq!(err, ContractError::InsufficientReserves {});
+
+            let query_response = query(deps.as_ref(), env.clone(), QueryMsg::EpochFees {
+                time: next_epoch + Uint64::from(10u8),
+            });
+            let epoch_fees: EpochFeesResponse = from_binary(&query_response.unwrap()).unwrap();
+            assert_eq!(epoch_fees.epoch_start, next_epoch);
+            assert_eq!(epoch_fees.fees, fees);
+
+            // 3. Claimable once the epoch is over, split by voting power at its start
+            let pending_fees = |deps: Deps, env: &Env, user: &str| -> PendingEpochFeesResponse {
+                let msg = QueryMsg::PendingEpochFees { address: user.to_string() };
+                from_binary(&query(deps, env.clone(), msg).unwrap()).unwrap()
+            };
+            assert_eq!(pending_fees(deps.as_ref(), &env, "alice").amount, Uint128::zero());
+
+            env.block.time = env.block.time.plus_seconds(FEE_EPOCH);
+            let alice_fees = pending_fees(deps.as_ref(), &env, "alice");
+            let bob_fees = pending_fees(deps.as_ref(), &env, "bob");
+            assert_eq!(alice_fees.next_epoch, next_epoch + Uint64::from(FEE_EPOCH));
+            assert!(alice_fees.amount + bob_fees.amount <= fees);
+            assert!(alice_fees.amount + bob_fees.amount >= fees - Uint128::from(2u8));
+            assert!(alice_fees.amount > bob_fees.amount * Uint128::from(2u8));
+
+            let alice_info = mock_info("alice", &[]);
+            let msg = ExecuteMsg::ClaimEpochFees { limit: None };
+            let resp = execute(deps.as_mut(), env.clone(), alice_info.clone(), msg.clone()).unwrap();
+            let expected_response: Response<Empty> = Response::new()
+                .add_message(BankMsg::Send {
+                    to_address: "alice".to_string(),
+                    amount: coins(alice_fees.amount.u128(), MOCK_LOCKED_DENOM),
+                })
+                .add_event(
+                    ContractEvent::make_epoch_fee_claim(
+                        "alice".to_string(),
+                        alice_fees.amount,
+                        alice_fees.next_epoch
+                    ).to_cosmos_event()
+                );
+            assert_eq!(expected_response, resp);
+
+            // 4. Claimed epochs are not paid again
+            let resp = execute(deps.as_mut(), env.clone(), alice_info, msg.clone()).unwrap();
+            assert!(resp.messages.is_empty());
+            assert_eq!(
+                UNCLAIMED_EPOCH_FEES.load(deps.as_ref().storage).unwrap(),
+                fees - alice_fees.amount
+            );
+
+            // 5. Claims can't dip into the locked tokens
+            let reserved = amount * Uint128::from(2u8) + fees - alice_fees.amount - bob_fees.amount;
+            deps.querier.update_balance(
+                env.contract.address.clone(),
+                coins(reserved.u128() - 1, MOCK_LOCKED_DENOM)
+            );
+            let err = execute(deps.as_mut(), env, mock_info("bob", &[]), msg).unwrap_err();
+            assert_eq!(err, ContractError::InsufficientReserves {});
+        }
+
+        #[test]
+        fn test_execute_epoch_fees_after_shutdown() {
+            for reward_policy in [ShutdownRewardPolicy::Settle, ShutdownRewardPolicy::Forfeit] {
+                let mut deps = mock_dependencies();
+                let mut env = mock_env();
+                let info = mock_info("creator", &[]);
+
+                mock_instantiate_native(deps.as_mut(), env.clone(), info.to_owned());
+
+                let amount = apply_decimals(Uint128::from(1u8));
+                let fees = Uint128::from(3000u16);
+                deps.querier.update_balance(
+                    env.contract.address.clone(),
+                    coins((amount + fees).u128(), MOCK_LOCKED_DENOM)
+                );
+                let new_locked_until = Uint64::from(env.block.time.seconds() + MAX_LOCK_PERIOD);
+                execute(
+                    deps.as_mut(),
+                    env.clone(),
+                    mock_info("alice", &coins(amount.u128(), MOCK_LOCKED_DENOM)),
+                    ExecuteMsg::Lock { amount, new_locked_until }
+                ).unwrap();
+
+                // alice's fees of a finished epoch are unclaimed at shutdown
+                env.block.time = env.block.time.plus_seconds(FEE_EPOCH);
+                let income_info = mock_info("creator", &coins(fees.u128(), MOCK_LOCKED_DENOM));
+                let msg = ExecuteMsg::AddEpochIncome { add_amount: fees };
+                execute(deps.as_mut(), env.clone(), income_info, msg).unwrap();
+                env.block.time = env.block.time.plus_seconds(FEE_EPOCH);
+
+                let msg = ExecuteMsg::EmergencyShutdown { reward_policy };
+                execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+                let alice_info = mock_info("alice", &[]);
+                let msg = ExecuteMsg::ClaimEpochFees { limit: None };
+                let claim_result = execute(deps.as_mut(), env.clone(), alice_info, msg);
+                let sweep_msg = ExecuteMsg::SweepForfeitedRewards {
+                    recipient: "treasury".to_string(),
+                };
+                match reward_policy {
+                    ShutdownRewardPolicy::Settle => {
+                        let resp = claim_result.unwrap();
+                        assert_eq!(resp.messages.len(), 1);
+
+                        let err = execute(deps.as_mut(), env, info, sweep_msg).unwrap_err();
+                        assert_eq!(err, ContractError::NothingToSweep {});
+                    }
+                    ShutdownRewardPolicy::Forfeit => {
+                        assert_eq!(claim_result.unwrap_err(), ContractError::Shutdown {});
+
+                        // No longer reserved for alice, the owner sweeps them
+                        let unclaimed_fees = UNCLAIMED_EPOCH_FEES.load(deps.as_ref().storage);
+                        assert!(unclaimed_fees.unwrap().is_zero());
+                        let resp = execute(deps.as_mut(), env, info, sweep_msg).unwrap();
+                        assert_eq!(
+                            resp.messages,
+                            vec![
+                                SubMsg::new(BankMsg::Send {
+                                    to_address: "treasury".to_string(),
+                                    amount: coins(fees.u128(), MOCK_LOCKED_DENOM),
+                                })
+                            ]
+                        );
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn test_execute_vote_gauge() {
+            let mut deps = mock_dependencies();
+            let mut env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate(deps.as_mut(), env.clone(), info.to_owned());
+
+            // 1. Only config admin registers gauges
+            let msg = ExecuteMsg::AddGauge { address: "gauge_a".to_string() };
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("user", &[]),
+                msg.clone()
+            ).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+
+            let resp = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+            let expected_response: Response<Empty> = Response::new().add_event(
+                ContractEvent::make_gauge_added("gauge_a".to_string()).to_cosmos_event()
+            );
+            assert_eq!(expected_response, resp);
+
+            let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+            assert_eq!(err, ContractError::GaugeExists {});
+
+            execute(deps.as_mut(), env.clone(), info.clone(), ExecuteMsg::AddGauge {
+                address: "gauge_b".to_string(),
+            }).unwrap();
+
+            // user: 1000 tokens locked for max period
+            let user_addr = Addr::unchecked("user");
+            let user_info = mock_info("user", &[]);
+            let locked_until = Uint64::from(env.block.time.seconds() + MAX_LOCK_PERIOD);
+            USER_STATE.save(deps.as_mut().storage, &user_addr, &UserState::default()).unwrap();
+            let position_id = mock_position(
+                deps.as_mut().storage,
+                &user_addr,
+                Uint128::from(1000u16),
+                locked_until
+            );
+            internal_funcs
+                ::update_lock(deps.as_mut(), &env, &user_info, position_id, locked_until)
+                .unwrap();
+            // Votes are a share of the decaying balance from the epoch they take effect in,
+            // up to the rounding of the share of each position slope
+            let ve_power = |deps: Deps, ts: Uint64| {
+                checkpoints::balance_of(deps.storage, &user_addr, ts).unwrap()
+            };
+            let vote_power = |deps: Deps, gauge: &str, ts: Uint64| {
+                let gauge = Addr::unchecked(gauge);
+                gauges::vote_power_at(deps.storage, &user_addr, &gauge, ts).unwrap()
+            };
+            let assert_share = |power: Uint128, balance: Uint128, weight: u16| {
+                let share = balance.multiply_ratio(weight, MAX_GAUGE_VOTE_WEIGHT);
+                assert!(power <= share && share - power <= Uint128::one());
+            };
+            let next_epoch = gauges::next_epoch_start(Uint64::from(env.block.time.seconds()));
+            let balance = ve_power(deps.as_ref(), next_epoch);
+
+            let vote = |gauge: &str, weight: u16| ExecuteMsg::VoteGauge {
+                gauge: gauge.to_string(),
+                weight,
+            };
+
+            // 2. Vote validation
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                user_info.clone(),
+                vote("gauge_c", 1000)
+            ).unwrap_err();
+            assert_eq!(err, ContractError::GaugeNotFound {});
+
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                user_info.clone(),
+                vote("gauge_a", MAX_GAUGE_VOTE_WEIGHT + 1)
+            ).unwrap_err();
+            assert_eq!(err, ContractError::InvalidGaugeWeight {});
+
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("unlocked", &[]),
+                vote("gauge_a", 1000)
+            ).unwrap_err();
+            assert_eq!(err, ContractError::LockExpiresTooSoon {});
+
+            // 3. 60% to gauge_a, 40% to gauge_b
+            let resp = execute(
+                deps.as_mut(),
+                env.clone(),
+                user_info.clone(),
+                vote("gauge_a", 6000)
+            ).unwrap();
+            let expected_response: Response<Empty> = Response::new().add_event(
+                ContractEvent::make_gauge_vote(
+                    user_addr.to_string(),
+                    "gauge_a".to_string(),
+                    6000,
+                    vote_power(deps.as_ref(), "gauge_a", next_epoch)
+                ).to_cosmos_event()
+            );
+            assert_eq!(expected_response, resp);
+            assert_share(vote_power(deps.as_ref(), "gauge_a", next_epoch), balance, 6000);
+
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                user_info.clone(),
+                vote("gauge_b", 4001)
+            ).unwrap_err();
+            assert_eq!(err, ContractError::GaugeWeightExceeded {});
+            execute(deps.as_mut(), env.clone(), user_info.clone(), vote("gauge_b", 4000)).unwrap();
+
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                user_info.clone(),
+                vote("gauge_a", 5000)
+            ).unwrap_err();
+            assert_eq!(err, ContractError::GaugeVoteTooSoon {});
+
+            // 4. Weights apply from the next epoch
+            let query_weight = |deps: Deps, env: &Env, gauge: &str| -> GaugeWeightResponse {
+                let msg = QueryMsg::GaugeWeight { gauge: gauge.to_string(), time: None };
+                from_binary(&query(deps, env.clone(), msg).unwrap()).unwrap()
+            };
+            assert_eq!(query_weight(deps.as_ref(), &env, "gauge_a").weight, Uint128::zero());
+
+            let voted_at = Uint64::from(env.block.time.seconds());
+            env.block.time = env.block.time.plus_seconds(next_epoch.u64() - voted_at.u64());
+            let gauge_a_power = vote_power(deps.as_ref(), "gauge_a", next_epoch);
+            let gauge_b_power = vote_power(deps.as_ref(), "gauge_b", next_epoch);
+            assert_share(gauge_b_power, balance, 4000);
+            // The total is rounded once, not once per vote
+            let total_weight = query_weight(deps.as_ref(), &env, "gauge_a").total_weight;
+            assert!(total_weight - (gauge_a_power + gauge_b_power) <= Uint128::one());
+            assert_eq!(query_weight(deps.as_ref(), &env, "gauge_a"), GaugeWeightResponse {
+                gauge: Addr::unchecked("gauge_a"),
+                epoch_start: next_epoch,
+                weight: gauge_a_power,
+                total_weight,
+                relative_weight: Decimal::from_ratio(gauge_a_power, total_weight),
+            });
+
+            let query_response = query(deps.as_ref(), env.clone(), QueryMsg::UserVotes {
+                address: user_addr.to_string(),
+            });
+            let user_votes: UserVotesResponse = from_binary(&query_response.unwrap()).unwrap();
+            assert_eq!(user_votes, UserVotesResponse {
+                votes: vec![
+                    UserVote {
+                        gauge: Addr::unchecked("gauge_a"),
+                        weight: 6000,
+                        power: gauge_a_power,
+                        last_vote: voted_at,
+                    },
+                    UserVote {
+                        gauge: Addr::unchecked("gauge_b"),
+                        weight: 4000,
+                        power: gauge_b_power,
+                        last_vote: voted_at,
+                    }
+                ],
+                used_weight: 10000,
+            });
+
+            // 5. Balance changes reweight the voted gauges from the following epoch
+            POSITIONS.update(deps.as_mut().storage, position_id, |position| -> StdResult<_> {
+                let mut position = position.unwrap();
+                position.locked_balance = Uint128::from(500u16);
+                Ok(position)
+            }).unwrap();
+            internal_funcs
+                ::update_lock(deps.as_mut(), &env, &user_info, position_id, locked_until)
+                .unwrap();
+            assert_eq!(query_weight(deps.as_ref(), &env, "gauge_b").weight, gauge_b_power);
+
+            env.block.time = env.block.time.plus_seconds(GAUGE_EPOCH);
+            let now = Uint64::from(env.block.time.seconds());
+            let gauge_b_power = vote_power(deps.as_ref(), "gauge_b", now);
+            assert_share(gauge_b_power, ve_power(deps.as_ref(), now), 4000);
+            assert_eq!(query_weight(deps.as_ref(), &env, "gauge_b").weight, gauge_b_power);
+
+            // 6. Removing a vote after the cooldown
+            env.block.time = env.block.time.plus_seconds(GAUGE_VOTE_COOLDOWN);
+            execute(deps.as_mut(), env.clone(), user_info.clone(), vote("gauge_a", 0)).unwrap();
+
+            env.block.time = env.block.time.plus_seconds(GAUGE_EPOCH);
+            let gauge_b_weight = query_weight(deps.as_ref(), &env, "gauge_b");
+            assert!(gauge_b_weight.weight < gauge_b_power);
+            assert_eq!(gauge_b_weight.total_weight, gauge_b_weight.weight);
+            assert_eq!(gauge_b_weight.relative_weight, Decimal::one());
+
+            // 7. A later vote counts the power left by then, not the stored ve balance
+            env.block.time = env.block.time.plus_seconds(GAUGE_VOTE_COOLDOWN);
+            let resp = execute(
+                deps.as_mut(),
+                env.clone(),
+                user_info,
+                vote("gauge_a", 5000)
+            ).unwrap();
+            let stored_balance = USER_STATE.load(deps.as_ref().storage, &user_addr).unwrap().balance;
+            let next_epoch = gauges::next_epoch_start(Uint64::from(env.block.time.seconds()));
+            let power = vote_power(deps.as_ref(), "gauge_a", next_epoch);
+            assert_share(power, ve_power(deps.as_ref(), next_epoch), 5000);
+            assert!(power < stored_balance.multiply_ratio(5000u16, MAX_GAUGE_VOTE_WEIGHT));
+            assert_eq!(
+                resp.events,
+                vec![
+                    ContractEvent::make_gauge_vote(
+                        user_addr.to_string(),
+                        "gauge_a".to_string(),
+                        5000,
+                        power
+                    ).to_cosmos_event()
+                ]
+            );
+
+            let query_response = query(deps.as_ref(), env.clone(), QueryMsg::Gauges {
+                start_after: Some("gauge_a".to_string()),
+                limit: None,
+            });
+            let gauges: GaugesResponse = from_binary(&query_response.unwrap()).unwrap();
+            assert_eq!(gauges.gauges, vec![Addr::unchecked("gauge_b")]);
+
+            // 8. Untouched, the votes end with the lock
+            let expired_at = locked_until.u64() + GAUGE_EPOCH;
+            env.block.time = cosmwasm_std::Timestamp::from_seconds(expired_at);
+            for gauge in ["gauge_a", "gauge_b"] {
+                let weight = query_weight(deps.as_ref(), &env, gauge);
+                assert_eq!(weight.weight, Uint128::zero());
+                assert_eq!(weight.total_weight, Uint128::zero());
+            }
+        }
+
+        #[test]
+        fn test_execute_delegate() {
+            let mut deps = mock_dependencies();
+            let mut env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate(deps.as_mut(), env.clone(), info.to_owned());
+
+            let user_addr = Addr::unchecked("user");
+            let user_info = mock_info("user", &[]);
+            let balance = Uint128::from(1000u16);
+            USER_STATE.save(deps.as_mut().storage, &user_addr, &UserState::default()).unwrap();
+            internal_funcs
+                ::set_balance(deps.as_mut(), &env, &user_info, &user_addr, balance)
+                .unwrap();
+
+            let voting_power = |deps: Deps, address: &str| -> VotingPowerResponse {
+                let msg = QueryMsg::VotingPower { address: address.to_string() };
+                from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap()
+            };
+            let delegations = |deps: Deps, delegate: &str| -> Vec<Delegation> {
+                let msg = QueryMsg::Delegations {
+                    delegate: delegate.to_string(),
+                    start_after: None,
+                    limit: None,
+                };
+                let resp: DelegationsResponse = from_binary(
+                    &query(deps, mock_env(), msg).unwrap()
+                ).unwrap();
+                resp.delegations
+            };
+            assert_eq!(voting_power(deps.as_ref(), "user"), VotingPowerResponse {
+                power: balance,
+                delegated_to: None,
+            });
+
+            // 1. Delegation validation
+            let msg = ExecuteMsg::Delegate { to: "user".to_string() };
+            let err = execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap_err();
+            assert_eq!(err, ContractError::DelegateToSelf {});
+
+            let msg = ExecuteMsg::Undelegate {};
+            let err = execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap_err();
+            assert_eq!(err, ContractError::NotDelegated {});
+
+            // 2. Voting power moves to the delegate, ve balance stays
+            env.block.height += 1;
+            let msg = ExecuteMsg::Delegate { to: "rep".to_string() };
+            let resp = execute(deps.as_mut(), env.clone(), user_info.clone(), msg.clone()).unwrap();
+            let expected_response: Response<Empty> = Response::new().add_event(
+                ContractEvent::make_delegate(user_addr.to_string(), "rep".to_string(), balance)
+                    .to_cosmos_event()
+            );
+            assert_eq!(expected_response, resp);
+
+            let err = execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap_err();
+            assert_eq!(err, ContractError::AlreadyDelegated {});
+
+            assert_eq!(voting_power(deps.as_ref(), "user"), VotingPowerResponse {
+                power: Uint128::zero(),
+                delegated_to: Some(Addr::unchecked("rep")),
+            });
+            assert_eq!(voting_power(deps.as_ref(), "rep").power, balance);
+            assert_eq!(delegations(deps.as_ref(), "rep"), vec![Delegation {
+                delegator: user_addr.clone(),
+                power: balance,
+            }]);
+            assert_eq!(
+                query_balance(deps.as_ref(), "user".to_string()).unwrap().balance,
+                balance
+            );
+            assert_eq!(
+                query_balance(deps.as_ref(), "rep".to_string()).unwrap().balance,
+                Uint128::zero()
+            );
+
+            // 3. Balance changes of the delegator follow, history is kept by height
+            env.block.height += 1;
+            let new_balance = Uint128::from(1500u16);
+            internal_funcs
+                ::set_balance(deps.as_mut(), &env, &user_info, &user_addr, new_balance)
+                .unwrap();
+            assert_eq!(voting_power(deps.as_ref(), "rep").power, new_balance);
+
+            let power_at_height = |deps: Deps, address: &str, height: u64| -> Uint128 {
+                let msg = QueryMsg::VotingPowerAtHeight {
+                    address: address.to_string(),
+                    height: Some(height),
+                };
+                let resp: VotingPowerAtHeightResponse = from_binary(
+                    &query(deps, mock_env(), msg).unwrap()
+                ).unwrap();
+                resp.power
+            };
+            assert_eq!(power_at_height(deps.as_ref(), "user", env.block.height - 1), balance);
+            assert_eq!(power_at_height(deps.as_ref(), "rep", env.block.height - 1), Uint128::zero());
+            assert_eq!(power_at_height(deps.as_ref(), "rep", env.block.height), balance);
+            assert_eq!(power_at_height(deps.as_ref(), "rep", env.block.height + 1), new_balance);
+
+            // 4. Redelegation replaces the delegate
+            let msg = ExecuteMsg::Delegate { to: "rep2".to_string() };
+            execute(deps.as_mut(), env.clone(), user_info.clone(), msg).unwrap();
+            assert_eq!(voting_power(deps.as_ref(), "rep").power, Uint128::zero());
+            assert_eq!(voting_power(deps.as_ref(), "rep2").power, new_balance);
+            assert!(delegations(deps.as_ref(), "rep").is_empty());
+
+            // 5. Undelegate gives the voting power back
+            let resp = execute(deps.as_mut(), env.clone(), user_info, ExecuteMsg::Undelegate {})
+                .unwrap();
+            let expected_response: Response<Empty> = Response::new().add_event(
+                ContractEvent::make_undelegate(
+                    user_addr.to_string(),
+                    "rep2".to_string(),
+                    new_balance
+                ).to_cosmos_event()
+            );
+            assert_eq!(expected_response, resp);
+            assert_eq!(voting_power(deps.as_ref(), "user"), VotingPowerResponse {
+                power: new_balance,
+                delegated_to: None,
+            });
+            assert_eq!(voting_power(deps.as_ref(), "rep2").power, Uint128::zero());
+        }
+
+        #[test]
+        fn test_execute_claim_reward_assets() {
+            let mut deps = mock_dependencies();
+            let mut env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate(deps.as_mut(), env.clone(), info.to_owned());
+
+            let native_asset = Denom::Native("upartner".to_string());
+            let cw20_asset = Denom::Cw20(Addr::unchecked("partner_cw20"));
+            for asset in [native_asset.clone(), cw20_asset.clone()] {
+                let msg = ExecuteMsg::AddRewardAsset {
+                    asset,
+                    distribution_period: Uint64::from(1000u16),
+                };
+                execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+            }
+
+            let reward_amount = apply_decimals(Uint128::from(100u16));
+            deps.querier.update_wasm(
+                cw20_balances_mock_querier(
+                    vec![
+                        (MOCK_LOCKED_TOKEN, apply_decimals(Uint128::from(1u8))),
+                        ("partner_cw20", reward_amount)
+                    ]
+                )
+            );
+            deps.querier.update_balance(
+                env.contract.address.clone(),
+                coins(reward_amount.u128(), "upartner")
+            );
+
+            // ve balance == locked amount for the maximal lock
+            let user_addr = Addr::unchecked("user");
+            let msg = mock_cw20_receive(
+                user_addr.as_str(),
+                apply_decimals(Uint128::from(1u8)),
+                ReceiveMsg::Lock {
+                    new_locked_until: Uint64::from(env.block.time.seconds() + MAX_LOCK_PERIOD),
+                }
+            );
+            execute(deps.as_mut(), env.clone(), mock_info(MOCK_LOCKED_TOKEN, &[]), msg).unwrap();
+
+            // 1. Income: native with funds, CW20 with Send
+            let msg = ExecuteMsg::AddRewardIncome {
+                asset: native_asset.clone(),
+                add_amount: reward_amount,
+            };
+            let err = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
+            assert_eq!(err, ContractError::Payment(PaymentError::NoFunds {}));
+
+            let resp = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("creator", &coins(reward_amount.u128(), "upartner")),
+                msg
+            ).unwrap();
+
+            let expected_response: Response<Empty> = Response::new().add_event(
+                ContractEvent::make_reward_asset_income(
+                    "native:upartner".to_string(),
+                    reward_amount,
+                    Uint128::zero(),
+                    reward_amount / Uint128::from(1000u16)
+                ).to_cosmos_event()
+            );
+            assert_eq!(expected_response, resp);
+
+            let msg = ExecuteMsg::AddRewardIncome {
+                asset: cw20_asset.clone(),
+                add_amount: reward_amount,
+            };
+            let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+            assert_eq!(err, ContractError::Cw20SendRequired {});
+
+            let msg = mock_cw20_receive("creator", reward_amount, ReceiveMsg::AddRewardIncome {});
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("unknown_cw20", &[]),
+                msg.clone()
+            ).unwrap_err();
+            assert_eq!(err, ContractError::RewardAssetNotFound {});
+
+            execute(deps.as_mut(), env.clone(), mock_info("partner_cw20", &[]), msg).unwrap();
+
+            // 2. Each asset is backed by its own balance
+            deps.querier.update_balance(
+                env.contract.address.clone(),
+                coins(reward_amount.u128() - 1, "upartner")
+            );
+            let err = internal_funcs::check_reserves(deps.as_ref(), &env).unwrap_err();
+            assert_eq!(err, ContractError::InsufficientReserves {});
+            deps.querier.update_balance(
+                env.contract.address.clone(),
+                coins(reward_amount.u128(), "upartner")
+            );
+
+            // 3. Half of the period vested
+            env.block.height += 500;
+            let expected_reward = apply_decimals(Uint128::from(50u16));
+
+            let query_response = query(deps.as_ref(), env.clone(), QueryMsg::PendingAccountReward {
+                address: user_addr.clone(),
+            }).unwrap();
+            let pending: PendingAccountRewardResponse = from_binary(&query_response).unwrap();
+            assert_eq!(
+                pending.pending_account_rewards,
+                vec![
+                    PendingReward {
+                        asset: Denom::Cw20(Addr::unchecked(MOCK_LOCKED_TOKEN)),
+                        amount: Uint128::zero(),
+                    },
+                    PendingReward { asset: cw20_asset.clone(), amount: expected_reward },
+                    PendingReward { asset: native_asset.clone(), amount: expected_reward }
+                ]
+            );
+
+            // 4. Claim pays every asset
+            let user_info = mock_info(user_addr.as_str(), &[]);
+            let resp = execute(
+                deps.as_mut(),
+                env.clone(),
+                user_info.clone(),
+                ExecuteMsg::Claim { recipient: None }
+            ).unwrap();
+
+            let messages: Vec<CosmosMsg> = resp.messages
+                .iter()
+                .map(|msg| msg.msg.clone())
+                .collect();
+            let cw20_message = CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "partner_cw20".to_string(),
+                msg: to_binary(
+                    &(Cw20ExecuteMsg::Transfer {
+                        recipient: user_addr.to_string(),
+                        amount: expected_reward,
+                    })
+                ).unwrap(),
+                funds: vec![],
+            });
+            let native_message = CosmosMsg::Bank(BankMsg::Send {
+                to_address: user_addr.to_string(),
+                amount: coins(expected_reward.u128(), "upartner"),
+            });
+            assert_eq!(messages, vec![cw20_message, native_message]);
+
+            let claim_event = ContractEvent::make_reward_asset_claim(
+                user_addr.to_string(),
+                user_addr.to_string(),
+                "native:upartner".to_string(),
+                expected_reward
+            ).to_cosmos_event();
+            assert!(resp.events.contains(&claim_event));
+
+            let snapshot = USER_REWARD_SNAPSHOTS.load(
+                deps.as_ref().storage,
+                (&user_addr, "native:upartner")
+            ).unwrap();
+            let asset_state = REWARD_ASSETS.load(deps.as_ref().storage, "native:upartner").unwrap();
+            assert_eq!(snapshot, asset_state.reward_per_token);
+
+            // Nothing left in the same block
+            let msg = ExecuteMsg::Claim { recipient: None };
+            let resp = execute(deps.as_mut(), env, user_info, msg).unwrap();
+            assert_eq!(resp.messages.len(), 0);
+        }
+    }
+
+    #[cfg(test)]
+    mod query_tests {
+        use cosmwasm_std::from_binary;
+        use cw20::{ BalanceResponse, TokenInfoResponse };
+
+        use crate::internal::internal_funcs::set_balance;
+
+        use super::*;
+
+        #[test]
+        pub fn test_query_balance() {
+            let mut deps = mock_dependencies();
+            let env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate(deps.as_mut().branch(), env.clone(), info.to_owned());
+
+            let account = Addr::unchecked("user");
+            let amount = Uint128::from(100u128);
+
+            set_balance(deps.as_mut(), &env, &info, &account, amount.clone()).expect("no error");
+
+            let query_response = query(deps.as_ref(), env.clone(), QueryMsg::Balance {
+                address: account.to_string(),
+            }).unwrap();
+
+            let query_response_decoded: BalanceResponse = from_binary(&query_response).unwrap();
+
+            assert_eq!(query_response_decoded.balance, amount);
+        }
+
+        #[test]
+        pub fn test_query_token_info() {
+            let mut deps = mock_dependencies();
+            let env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate(deps.as_mut().branch(), env.clone(), info.to_owned());
+
+            let account = Addr::unchecked("user");
+            let amount = Uint128::from(100u128);
+
+            set_balance(deps.as_mut(), &env, &info, &account, amount.clone()).expect("no error");
+
+            let expected_token_info = TokenInfoResponse {
+                name: "veWILD".to_string(),
+                symbol: "veWILD".to_string(),
+                decimals: TOKEN_DECIMALS as u8,
+                total_supply: amount,
+            };
+
+            let query_response = query(deps.as_ref(), env.clone(), QueryMsg::TokenInfo {}).unwrap();
+            let query_response_decoded: TokenInfoResponse = from_binary(&query_response).unwrap();
+
+            assert_eq!(query_response_decoded, expected_token_info);
+        }
+
+        #[test]
+        pub fn test_query_reward_rate() {
+            let mut deps = mock_dependencies();
+            let env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate(deps.as_mut().branch(), env.clone(), info.to_owned());
+
+            let mut token_state = TOKEN_STATE.load(deps.as_mut().storage).unwrap();
+            token_state.reward_rate_stored = Uint128::from(1u128);
+            token_state.last_income_block = Uint64::from(env.block.height);
+
+            TOKEN_STATE.save(deps.as_mut().storage, &token_state).unwrap();
+
+            let query_response = query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::RewardRate {}
+            ).unwrap();
+
+            let query_response_decoded: RewardRateResponse = from_binary(&query_response).unwrap();
+
+            let expected_response = RewardRateResponse {
+                reward_rate: token_state.reward_rate_stored,
+            };
+
+            assert_eq!(expected_response, query_response_decoded);
+        }
+
+        #[test]
+        pub fn test_query_pending_account_reward() {
+            let mut deps = mock_dependencies();
+            let mut env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate(deps.as_mut().branch(), env.clone(), info.to_owned());
+
+            let addr = Addr::unchecked("user");
+
+            let reward_per_token = Uint128::from(100u128);
+            let reward_rate_stored = Uint128::from(1000u128);
+            let total_supply = Uint128::from(100u128);
+
+            let user_state = UserState {
+                balance: Uint128::from(100u8),
+                locked_balance: Uint128::from(100u8),
+                reward_snapshot: Uint128::zero(),
+            };
+            USER_STATE.save(deps.as_mut().storage, &addr, &user_state).unwrap();
+
+            TOKEN_STATE.update(
+                deps.as_mut().storage,
+                |mut state| -> StdResult<TokenState> {
+                    state.reward_per_token = reward_per_token.clone();
+                    state.last_accrue_block = Uint64::from(env.block.height);
+                    state.reward_rate_stored = reward_rate_stored.clone();
+                    Ok(state)
+                }
+            ).unwrap();
+
+            // Just accrued
+            let query_response = query(deps.as_ref(), env.clone(), QueryMsg::PendingAccountReward {
+                address: addr.clone(),
+            }).unwrap();
+
+            let query_response_decoded: PendingAccountRewardResponse = from_binary(
+                &query_response
+            ).unwrap();
+
+            let expected_result =
+                (reward_per_token.clone() * user_state.balance) /
+                apply_decimals(Uint128::from(1u8));
+
+            assert_eq!(expected_result, query_response_decoded.pending_account_rewards[0].amount);
+
+            // There is pending reward after accrue
+            env.block.height += 1;
+
+            let query_response = query(deps.as_ref(), env.clone(), QueryMsg::PendingAccountReward {
+                address: addr.clone(),
+            }).unwrap();
+
+            let query_response_decoded: PendingAccountRewardResponse = from_binary(
+                &query_response
+            ).unwrap();
+
+            let expected_result =
+                ((reward_per_token.clone() + reward_rate_stored.clone() / total_supply.clone()) *
+                    user_state.balance) /
+                apply_decimals(Uint128::from(1u8));
+
+            assert_eq!(expected_result, query_response_decoded.pending_account_rewards[0].amount);
+
+            // user already took its reward
+            USER_STATE.update(
+                deps.as_mut().storage,
+                &addr,
+                |state| -> StdResult<UserState> {
+                    let mut user = state.unwrap();
+                    user.reward_snapshot = expected_result.clone();
+                    Ok(user)
+                }
+            ).unwrap();
+
+            let query_response = query(deps.as_ref(), env.clone(), QueryMsg::PendingAccountReward {
+                address: addr.clone(),
+            }).unwrap();
+
+            let query_response_decoded: PendingAccountRewardResponse = from_binary(
+                &query_response
+            ).unwrap();
+
+            assert_eq!(Uint128::zero(), query_response_decoded.pending_account_rewards[0].amount);
+        }
+
+        #[test]
+        pub fn test_query_user_info() {
+            let mut deps = mock_dependencies();
+            let mut env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate(deps.as_mut().branch(), env.clone(), info.to_owned());
+
+            let addr = Addr::unchecked("user");
+
+            let user_state = UserState {
+                balance: Uint128::from(100u8),
+                locked_balance: Uint128::from(100u8),
+                reward_snapshot: Uint128::from(100u8),
+            };
+
+            USER_STATE.save(deps.as_mut().storage, &addr, &user_state).unwrap();
+
+            let query_response = query(deps.as_ref(), env.clone(), QueryMsg::UserInfo {
+                address: addr,
+            }).unwrap();
+
+            let query_response_decoded: UserInfoResponse = from_binary(&query_response).unwrap();
+
+            let expected_response = UserInfoResponse {
+                locked_balance: user_state.locked_balance,
+                reward_snapshot: user_state.reward_snapshot,
+            };
+
+            assert_eq!(expected_response, query_response_decoded);
+
+            let query_response = query(deps.as_ref(), env.clone(), QueryMsg::UserInfo {
+                address: Addr::unchecked("not_exist"),
+            }).unwrap();
+
+            let query_response_decoded: UserInfoResponse = from_binary(&query_response).unwrap();
+
+            let expected_response = UserInfoResponse {
+                locked_balance: Uint128::zero(),
+                reward_snapshot: Uint128::zero(),
+            };
+
+            assert_eq!(expected_response, query_response_decoded);
+        }
+
+        #[test]
+        pub fn test_query_ve_token_info() {
+            let mut deps = mock_dependencies();
+            let env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate(deps.as_mut().branch(), env.clone(), info.to_owned());
+
+            let token_state = TokenState {
+                total_supply: Uint128::from(100u8),
+                total_locked: Uint128::from(100u8),
+                distribution_period: Uint64::from(1000u16),
+                locked_token: Denom::Cw20(Addr::unchecked(MOCK_LOCKED_TOKEN)),
+                last_accrue_block: Uint64::from(1000u16),
+                last_income_block: Uint64::from(1000u16),
+                reward_per_token: Uint128::from(100u8),
+                reward_rate_stored: Uint128::from(100u8),
+            };
+
+            TOKEN_STATE.save(deps.as_mut().storage, &token_state).unwrap();
+
+            let query_response = query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::VeTokenInfo {}
+            ).unwrap();
+
+            let query_response_decoded: VeTokenInfoResponse = from_binary(&query_response).unwrap();
 
             let expected_response = VeTokenInfoResponse {
                 total_locked: token_state.total_locked,
@@ -1217,5 +4431,1036 @@ mod contract_tests {
 
             assert_eq!(expected_response, query_response_decoded);
         }
+
+        #[test]
+        pub fn test_query_owner() {
+            let mut deps = mock_dependencies();
+            let env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate(deps.as_mut().branch(), env.clone(), info.to_owned());
+
+            let msg = ExecuteMsg::ProposeOwner { new_owner: "new_owner".to_string() };
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+            let query_response = query(deps.as_ref(), env, QueryMsg::Owner {}).unwrap();
+            let query_response_decoded: OwnerResponse = from_binary(&query_response).unwrap();
+
+            let expected_response = OwnerResponse {
+                owner: Some(info.sender),
+                pending_owner: Some(Addr::unchecked("new_owner")),
+            };
+
+            assert_eq!(expected_response, query_response_decoded);
+        }
+
+        #[test]
+        pub fn test_query_role_members() {
+            let mut deps = mock_dependencies();
+            let env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate(deps.as_mut().branch(), env.clone(), info.to_owned());
+
+            for address in ["pauser_c", "pauser_a", "pauser_b"] {
+                let msg = ExecuteMsg::GrantRole {
+                    role: Role::Pauser,
+                    address: address.to_string(),
+                };
+                execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+            }
+            let msg = ExecuteMsg::GrantRole {
+                role: Role::IncomeManager,
+                address: "treasury".to_string(),
+            };
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+            let query_response = query(deps.as_ref(), env.clone(), QueryMsg::RoleMembers {
+                role: Role::Pauser,
+                start_after: None,
+                limit: Some(2),
+            }).unwrap();
+            let query_response_decoded: RoleMembersResponse = from_binary(
+                &query_response
+            ).unwrap();
+
+            assert_eq!(
+                query_response_decoded.members,
+                vec![Addr::unchecked("pauser_a"), Addr::unchecked("pauser_b")]
+            );
+
+            let query_response = query(deps.as_ref(), env.clone(), QueryMsg::RoleMembers {
+                role: Role::Pauser,
+                start_after: Some("pauser_b".to_string()),
+                limit: None,
+            }).unwrap();
+            let query_response_decoded: RoleMembersResponse = from_binary(
+                &query_response
+            ).unwrap();
+
+            assert_eq!(query_response_decoded.members, vec![Addr::unchecked("pauser_c")]);
+        }
+
+        #[test]
+        pub fn test_query_pause_status() {
+            let mut deps = mock_dependencies();
+            let env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate(deps.as_mut().branch(), env.clone(), info.to_owned());
+
+            let msg = ExecuteMsg::Pause {
+                actions: vec![PausableAction::EarlyWithdraw, PausableAction::AddIncome],
+            };
+            execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+            let query_response = query(deps.as_ref(), env, QueryMsg::PauseStatus {}).unwrap();
+            let query_response_decoded: PauseStatusResponse = from_binary(
+                &query_response
+            ).unwrap();
+
+            let expected_response = PauseStatusResponse {
+                lock: false,
+                claim: false,
+                early_withdraw: true,
+                add_income: true,
+            };
+
+            assert_eq!(expected_response, query_response_decoded);
+        }
+
+        #[test]
+        pub fn test_query_balance_of() {
+            let mut deps = mock_dependencies();
+            let mut env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate(deps.as_mut(), env.clone(), info.to_owned());
+            deps.querier.update_wasm(cw20_mock_querier(apply_decimals(Uint128::from(10u8))));
+
+            let lock_start = env.block.time.seconds();
+            let lock_period = MAX_LOCK_PERIOD / 2;
+            for (user, amount) in [("alice", 1u8), ("bob", 3u8)] {
+                let msg = mock_cw20_receive(
+                    user,
+                    apply_decimals(Uint128::from(amount)),
+                    ReceiveMsg::Lock {
+                        new_locked_until: Uint64::from(lock_start + lock_period),
+                    }
+                );
+                execute(deps.as_mut(), env.clone(), mock_info(MOCK_LOCKED_TOKEN, &[]), msg).unwrap();
+            }
+
+            let slope = |amount: u8| {
+                Uint256::from(apply_decimals(Uint128::from(amount))) *
+                    Uint256::from(POINT_PRECISION) /
+                    Uint256::from(MAX_LOCK_PERIOD)
+            };
+            let (alice_slope, bob_slope) = (slope(1), slope(3));
+            let power = |slope: Uint256, seconds: u64| {
+                let bias = slope * Uint256::from(seconds);
+                Uint128::try_from(bias / Uint256::from(POINT_PRECISION)).unwrap()
+            };
+
+            let query_balance_of = |deps: Deps, env: Env, address: &str, timestamp| {
+                let msg = QueryMsg::BalanceOf {
+                    address: Addr::unchecked(address),
+                    timestamp,
+                };
+                let resp: BalanceOfResponse = from_binary(&query(deps, env, msg).unwrap()).unwrap();
+                resp.balance
+            };
+            let query_total_supply_at = |deps: Deps, env: Env, timestamp| {
+                let msg = QueryMsg::TotalSupplyAt { timestamp };
+                let resp: TotalSupplyAtResponse = from_binary(
+                    &query(deps, env, msg).unwrap()
+                ).unwrap();
+                resp.total_supply
+            };
+
+            // 1. Full lock time left
+            assert_eq!(
+                query_balance_of(deps.as_ref(), env.clone(), "alice", None),
+                power(alice_slope, lock_period)
+            );
+            assert_eq!(
+                query_total_supply_at(deps.as_ref(), env.clone(), None),
+                power(alice_slope + bob_slope, lock_period)
+            );
+
+            // 2. Decays every second without touching the contract
+            env.block.time = env.block.time.plus_seconds(lock_period / 4);
+            assert_eq!(
+                query_balance_of(deps.as_ref(), env.clone(), "bob", None),
+                power(bob_slope, lock_period - lock_period / 4)
+            );
+            assert_eq!(
+                query_total_supply_at(deps.as_ref(), env.clone(), None),
+                power(alice_slope + bob_slope, lock_period - lock_period / 4)
+            );
+
+            // 3. Nothing left after unlock, while the reward share stays until the next update
+            env.block.time = env.block.time.plus_seconds(lock_period);
+            assert_eq!(query_balance_of(deps.as_ref(), env.clone(), "alice", None), Uint128::zero());
+            assert_eq!(query_total_supply_at(deps.as_ref(), env.clone(), None), Uint128::zero());
+
+            let balance: BalanceResponse = from_binary(
+                &query(deps.as_ref(), env.clone(), QueryMsg::Balance {
+                    address: "alice".to_string(),
+                }).unwrap()
+            ).unwrap();
+            assert!(!balance.balance.is_zero());
+
+            // 4. Past timestamps
+            let timestamp = Some(Uint64::from(lock_start));
+            assert_eq!(
+                query_balance_of(deps.as_ref(), env.clone(), "alice", timestamp),
+                power(alice_slope, lock_period)
+            );
+            assert_eq!(
+                query_total_supply_at(deps.as_ref(), env, Some(Uint64::from(lock_start - 1))),
+                Uint128::zero()
+            );
+        }
+
+        #[test]
+        pub fn test_query_balance_at_height() {
+            let mut deps = mock_dependencies();
+            let mut env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate(deps.as_mut(), env.clone(), info.to_owned());
+            deps.querier.update_wasm(cw20_mock_querier(apply_decimals(Uint128::from(10u8))));
+
+            let user_addr = Addr::unchecked("user");
+            let amount = apply_decimals(Uint128::from(1u8));
+            let lock_height = env.block.height;
+            let locked_until = Uint64::from(env.block.time.seconds() + MAX_LOCK_PERIOD);
+            let msg = mock_cw20_receive(user_addr.as_str(), amount, ReceiveMsg::Lock {
+                new_locked_until: locked_until,
+            });
+            execute(deps.as_mut(), env.clone(), mock_info(MOCK_LOCKED_TOKEN, &[]), msg).unwrap();
+
+            // Lock for the maximal period again 10 blocks later, adding the same amount
+            env.block.height += 10;
+            env.block.time = env.block.time.plus_seconds(60);
+            let msg = ExecuteMsg::ExtendLock {
+                position: 1,
+                new_locked_until: Uint64::from(env.block.time.seconds() + MAX_LOCK_PERIOD),
+            };
+            execute(deps.as_mut(), env.clone(), mock_info(user_addr.as_str(), &[]), msg).unwrap();
+            let msg = mock_cw20_receive(user_addr.as_str(), amount, ReceiveMsg::IncreaseAmount {
+                position: 1,
+            });
+            execute(deps.as_mut(), env.clone(), mock_info(MOCK_LOCKED_TOKEN, &[]), msg).unwrap();
+
+            let query_balance_at_height = |height: u64| {
+                let msg = QueryMsg::BalanceAtHeight {
+                    address: user_addr.clone(),
+                    height: Uint64::from(height),
+                };
+                let resp: BalanceAtHeightResponse = from_binary(
+                    &query(deps.as_ref(), env.clone(), msg).unwrap()
+                ).unwrap();
+                resp.balance
+            };
+            let query_total_supply_at_height = |height: u64| {
+                let msg = QueryMsg::TotalSupplyAtHeight { height: Uint64::from(height) };
+                let resp: TotalSupplyAtHeightResponse = from_binary(
+                    &query(deps.as_ref(), env.clone(), msg).unwrap()
+                ).unwrap();
+                resp.total_supply
+            };
+
+            // Changes are visible from the next block
+            assert_eq!(query_balance_at_height(lock_height), Uint128::zero());
+            assert_eq!(query_balance_at_height(lock_height + 1), amount);
+            assert_eq!(query_balance_at_height(lock_height + 10), amount);
+            assert_eq!(query_balance_at_height(lock_height + 11), amount * Uint128::from(2u8));
+
+            assert_eq!(query_total_supply_at_height(lock_height), Uint128::zero());
+            assert_eq!(query_total_supply_at_height(lock_height + 5), amount);
+            assert_eq!(query_total_supply_at_height(lock_height + 11), amount * Uint128::from(2u8));
+        }
+
+        #[test]
+        pub fn test_query_config() {
+            let mut deps = mock_dependencies();
+            let env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate(deps.as_mut().branch(), env.clone(), info.to_owned());
+
+            let query_response = query(deps.as_ref(), env, QueryMsg::Config {}).unwrap();
+            let query_response_decoded: ConfigResponse = from_binary(&query_response).unwrap();
+
+            let expected_response = ConfigResponse {
+                min_lock_period: Uint64::from(MIN_LOCK_PERIOD),
+                max_lock_period: Uint64::from(MAX_LOCK_PERIOD),
+                withdraw_delay: Uint64::from(WITHDRAW_DELAY),
+            };
+
+            assert_eq!(expected_response, query_response_decoded);
+        }
+
+        #[test]
+        pub fn test_query_shutdown_status() {
+            let mut deps = mock_dependencies();
+            let env = mock_env();
+            let info = mock_info("creator", &[]);
+
+            mock_instantiate(deps.as_mut().branch(), env.clone(), info.to_owned());
+
+            let query_response = query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::ShutdownStatus {}
+            ).unwrap();
+            let query_response_decoded: ShutdownStatusResponse = from_binary(
+                &query_response
+            ).unwrap();
+            assert_eq!(query_response_decoded, ShutdownStatusResponse { shutdown: None });
+
+            let msg = ExecuteMsg::EmergencyShutdown {
+                reward_policy: ShutdownRewardPolicy::Forfeit,
+            };
+            execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+            let query_response = query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::ShutdownStatus {}
+            ).unwrap();
+            let query_response_decoded: ShutdownStatusResponse = from_binary(
+                &query_response
+            ).unwrap();
+
+            let expected_response = ShutdownStatusResponse {
+                shutdown: Some(Shutdown {
+                    activated_at: Uint64::from(env.block.time.seconds()),
+                    reward_policy: ShutdownRewardPolicy::Forfeit,
+                }),
+            };
+            assert_eq!(expected_response, query_response_decoded);
+        }
+    }
+    #[cfg(test)]
+    mod migrate_tests {
+        use cosmwasm_std::Storage;
+        use cw2::{ get_contract_version, set_contract_version };
+
+        use crate::contract::{ CONTRACT_NAME, CONTRACT_VERSION };
+        use crate::migrations::v0_1;
+
+        use super::*;
+
+        fn mock_v0_1_storage(storage: &mut dyn Storage) -> v0_1::TokenState {
+            set_contract_version(storage, CONTRACT_NAME, "0.1.0").unwrap();
+
+            let legacy_state = v0_1::TokenState {
+                total_supply: Uint128::from(100u8),
+                total_locked: Uint128::from(200u8),
+                distribution_period: Uint64::from(1000u16),
+                locked_token: Addr::unchecked(MOCK_LOCKED_TOKEN),
+                last_accrue_block: Uint64::from(1000u16),
+                last_income_block: Uint64::from(900u16),
+                reward_per_token: Uint128::from(10u8),
+                reward_rate_stored: Uint128::from(1u8),
+            };
+            v0_1::TOKEN_STATE.save(storage, &legacy_state).unwrap();
+
+            legacy_state
+        }
+
+        #[test]
+        fn test_migrate_from_v0_1() {
+            let mut deps = mock_dependencies();
+            let env = mock_env();
+
+            let legacy_state = mock_v0_1_storage(deps.as_mut().storage);
+
+            // Single lock of the account becomes its first position
+            let user_addr = Addr::unchecked("user");
+            let legacy_user = v0_1::UserState {
+                balance: Uint128::from(100u8),
+                locked_balance: Uint128::from(200u8) * Uint128::from(MAX_LOCK_PERIOD),
+                locked_until: Uint64::from(env.block.time.seconds() + MAX_LOCK_PERIOD / 2),
+                reward_snapshot: Uint128::zero(),
+                withdraw_at: Uint64::zero(),
+            };
+            v0_1::USER_STATE.save(deps.as_mut().storage, &user_addr, &legacy_user).unwrap();
+            // Withdrawn accounts keep their reward snapshot only
+            let withdrawn_addr = Addr::unchecked("withdrawn");
+            v0_1::USER_STATE.save(deps.as_mut().storage, &withdrawn_addr, &(v0_1::UserState {
+                balance: Uint128::zero(),
+                locked_balance: Uint128::zero(),
+                locked_until: Uint64::from(env.block.time.seconds()),
+                reward_snapshot: Uint128::from(5u8),
+                withdraw_at: Uint64::zero(),
+            })).unwrap();
+
+            let resp = migrate(deps.as_mut(), env.clone(), MigrateMsg {
+                owner: Some("owner".to_string()),
+            }).unwrap();
+
+            let expected_response: Response<Empty> = Response::new().add_event(
+                ContractEvent::make_migrate(
+                    "0.1.0".to_string(),
+                    CONTRACT_VERSION.to_string()
+                ).to_cosmos_event()
+            );
+            assert_eq!(expected_response, resp);
+
+            let expected_token_state = TokenState {
+                total_supply: legacy_state.total_supply,
+                total_locked: legacy_state.total_locked,
+                distribution_period: legacy_state.distribution_period,
+                locked_token: Denom::Cw20(legacy_state.locked_token),
+                last_accrue_block: legacy_state.last_accrue_block,
+                last_income_block: legacy_state.last_income_block,
+                reward_per_token: legacy_state.reward_per_token,
+                reward_rate_stored: legacy_state.reward_rate_stored,
+            };
+            assert_eq!(expected_token_state, TOKEN_STATE.load(deps.as_ref().storage).unwrap());
+
+            assert_eq!(
+                Ownership::new(Addr::unchecked("owner")),
+                OWNERSHIP.load(deps.as_ref().storage).unwrap()
+            );
+            assert_eq!(Addr::unchecked("owner"), DAO.load(deps.as_ref().storage).unwrap());
+            assert_eq!(PauseState::default(), PAUSE_STATE.load(deps.as_ref().storage).unwrap());
+            assert_eq!(Config::default(), CONFIG.load(deps.as_ref().storage).unwrap());
+
+            assert_eq!(USER_STATE.load(deps.as_ref().storage, &user_addr).unwrap(), UserState {
+                balance: legacy_user.balance,
+                locked_balance: legacy_user.locked_balance,
+                reward_snapshot: legacy_user.reward_snapshot,
+            });
+            let position = POSITIONS.load(deps.as_ref().storage, 1).unwrap();
+            assert_eq!(position.owner, user_addr);
+            assert_eq!(position.locked_balance, legacy_user.locked_balance);
+            assert_eq!(position.locked_until, legacy_user.locked_until);
+            assert_eq!(position.balance, legacy_user.balance);
+            assert!(OWNER_POSITIONS.has(deps.as_ref().storage, (&user_addr, 1)));
+            assert_eq!(
+                USER_STATE.load(deps.as_ref().storage, &withdrawn_addr).unwrap().reward_snapshot,
+                Uint128::from(5u8)
+            );
+            assert_eq!(POSITION_COUNT.load(deps.as_ref().storage).unwrap(), 1);
+
+            let height = env.block.height + 1;
+            let balance = BALANCE_SNAPSHOTS.may_load_at_height(
+                deps.as_ref().storage,
+                &user_addr,
+                height
+            );
+            assert_eq!(balance.unwrap(), Some(legacy_user.balance));
+            let power = VOTING_POWER.may_load_at_height(deps.as_ref().storage, &user_addr, height);
+            assert_eq!(power.unwrap(), Some(legacy_user.balance));
+            let total_supply = TOTAL_SUPPLY_SNAPSHOTS.may_load_at_height(
+                deps.as_ref().storage,
+                height
+            );
+            assert_eq!(total_supply.unwrap(), Some(legacy_state.total_supply));
+
+            // Existing locks get voting power checkpoints
+            let now = Uint64::from(env.block.time.seconds());
+            let expected_power = Uint128::from(200u8) * Uint128::from(MAX_LOCK_PERIOD / 2);
+            assert_eq!(
+                checkpoints::balance_of(deps.as_ref().storage, &user_addr, now).unwrap(),
+                expected_power
+            );
+            assert_eq!(
+                checkpoints::total_supply_at(deps.as_ref().storage, now).unwrap(),
+                expected_power
+            );
+
+            let version = get_contract_version(deps.as_ref().storage).unwrap();
+            assert_eq!(version.contract, CONTRACT_NAME);
+            assert_eq!(version.version, CONTRACT_VERSION);
+
+            // Migrating to the same version again is a no-op
+            migrate(deps.as_mut(), env, MigrateMsg { owner: None }).unwrap();
+            assert_eq!(expected_token_state, TOKEN_STATE.load(deps.as_ref().storage).unwrap());
+        }
+
+        #[test]
+        fn test_migrate_errors() {
+            let mut deps = mock_dependencies();
+            let env = mock_env();
+
+            // 1. Owner must be provided for 0.1
+            mock_v0_1_storage(deps.as_mut().storage);
+
+            let err = migrate(deps.as_mut(), env.clone(), MigrateMsg { owner: None }).unwrap_err();
+            assert_eq!(err, ContractError::MigrationOwnerRequired {});
+
+            // 2. Other contract
+            set_contract_version(deps.as_mut().storage, "crates.io:cw20-base", "0.1.0").unwrap();
+
+            let err = migrate(deps.as_mut(), env.clone(), MigrateMsg {
+                owner: Some("owner".to_string()),
+            }).unwrap_err();
+            assert_eq!(err, ContractError::InvalidMigrationContract {
+                previous_contract: "crates.io:cw20-base".to_string(),
+            });
+
+            // 3. Downgrade
+            set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "99.0.0").unwrap();
+
+            let err = migrate(deps.as_mut(), env.clone(), MigrateMsg {
+                owner: Some("owner".to_string()),
+            }).unwrap_err();
+            assert_eq!(err, ContractError::MigrationDowngrade {
+                previous_version: "99.0.0".to_string(),
+                new_version: CONTRACT_VERSION.to_string(),
+            });
+
+            // 4. Layouts without a migration step
+            for version in ["0.0.9", "0.2.0-alpha.1"] {
+                set_contract_version(deps.as_mut().storage, CONTRACT_NAME, version).unwrap();
+
+                let err = migrate(deps.as_mut(), env.clone(), MigrateMsg {
+                    owner: Some("owner".to_string()),
+                }).unwrap_err();
+                assert_eq!(err, ContractError::UnsupportedMigration {
+                    previous_version: version.to_string(),
+                });
+            }
+        }
+    }
+
+    mod integration_tests {
+        use cosmwasm_schema::cw_serde;
+        use cosmwasm_std::{ Binary, Deps, DepsMut, Env, MessageInfo, StdError };
+        use cw2::ContractVersion;
+        use cw_multi_test::{ App, ContractWrapper, Executor };
+        use cw_storage_plus::Item;
+
+        use crate::contract::{ CONTRACT_NAME, CONTRACT_VERSION };
+
+        use super::*;
+
+        // Minimal proposal module: snapshots the voting power at the proposal height
+        // like the DAO DAO proposal modules do
+        #[cw_serde]
+        struct MockProposalInstantiateMsg {
+            voting_module: String,
+        }
+
+        #[cw_serde]
+        enum MockProposalExecuteMsg {
+            Propose {},
+            Vote {},
+        }
+
+        #[cw_serde]
+        enum MockProposalQueryMsg {
+            Proposal {},
+        }
+
+        #[cw_serde]
+        struct MockProposal {
+            start_height: u64,
+            total_power: Uint128,
+            yes: Uint128,
+            voters: Vec<Addr>,
+        }
+
+        const VOTING_MODULE: Item<Addr> = Item::new("voting_module");
+        const PROPOSAL: Item<MockProposal> = Item::new("proposal");
+
+        fn proposal_instantiate(
+            deps: DepsMut,
+            _env: Env,
+            _info: MessageInfo,
+            msg: MockProposalInstantiateMsg
+        ) -> StdResult<Response> {
+            VOTING_MODULE.save(deps.storage, &deps.api.addr_validate(&msg.voting_module)?)?;
+            Ok(Response::new())
+        }
+
+        fn proposal_execute(
+            deps: DepsMut,
+            env: Env,
+            info: MessageInfo,
+            msg: MockProposalExecuteMsg
+        ) -> StdResult<Response> {
+            let voting_module = VOTING_MODULE.load(deps.storage)?;
+            match msg {
+                MockProposalExecuteMsg::Propose {} => {
+                    let total_power: TotalPowerAtHeightResponse = deps.querier.query_wasm_smart(
+                        &voting_module,
+                        &(QueryMsg::TotalPowerAtHeight { height: Some(env.block.height) })
+                    )?;
+                    PROPOSAL.save(deps.storage, &(MockProposal {
+                        start_height: env.block.height,
+                        total_power: total_power.power,
+                        yes: Uint128::zero(),
+                        voters: vec![],
+                    }))?;
+                }
+                MockProposalExecuteMsg::Vote {} => {
+                    let mut proposal = PROPOSAL.load(deps.storage)?;
+                    if proposal.voters.contains(&info.sender) {
+                        return Err(StdError::generic_err("already voted"));
+                    }
+                    let power: VotingPowerAtHeightResponse = deps.querier.query_wasm_smart(
+                        &voting_module,
+                        &(QueryMsg::VotingPowerAtHeight {
+                            address: info.sender.to_string(),
+                            height: Some(proposal.start_height),
+                        })
+                    )?;
+                    if power.power.is_zero() {
+                        return Err(StdError::generic_err("no voting power"));
+                    }
+                    proposal.yes += power.power;
+                    proposal.voters.push(info.sender);
+                    PROPOSAL.save(deps.storage, &proposal)?;
+                }
+            }
+            Ok(Response::new())
+        }
+
+        fn proposal_query(deps: Deps, _env: Env, msg: MockProposalQueryMsg) -> StdResult<Binary> {
+            match msg {
+                MockProposalQueryMsg::Proposal {} => to_binary(&PROPOSAL.load(deps.storage)?),
+            }
+        }
+
+        /// cw20-base locked token with balances for alice, bob and carol,
+        /// veWILD instantiated by DAO core
+        fn instantiate_ve_token(app: &mut App) -> (Addr, Addr) {
+            let token_code_id = app.store_code(
+                Box::new(
+                    ContractWrapper::new(
+                        cw20_base::contract::execute,
+                        cw20_base::contract::instantiate,
+                        cw20_base::contract::query
+                    )
+                )
+            );
+            let ve_code_id = app.store_code(
+                Box::new(ContractWrapper::new(execute, instantiate, query).with_reply(reply))
+            );
+
+            let initial_balances = ["alice", "bob", "carol"].map(|address| cw20::Cw20Coin {
+                address: address.to_string(),
+                amount: Uint128::from(1000u16),
+            });
+            let token = app.instantiate_contract(
+                token_code_id,
+                Addr::unchecked("creator"),
+                &(cw20_base::msg::InstantiateMsg {
+                    name: "Wild".to_string(),
+                    symbol: "WILD".to_string(),
+                    decimals: 18,
+                    initial_balances: initial_balances.to_vec(),
+                    mint: None,
+                    marketing: None,
+                }),
+                &[],
+                "WILD",
+                None
+            ).unwrap();
+
+            // DAO core instantiates its voting module
+            let ve_token = app.instantiate_contract(
+                ve_code_id,
+                Addr::unchecked("dao"),
+                &(InstantiateMsg {
+                    locked_token: Denom::Cw20(token.clone()),
+                    distribution_period: Uint64::from(1000u16),
+                    min_lock_period: None,
+                    max_lock_period: None,
+                    withdraw_delay: None,
+                    dao: None,
+                }),
+                &[],
+                "veWILD",
+                None
+            ).unwrap();
+
+            (token, ve_token)
+        }
+
+        fn lock(app: &mut App, token: &Addr, ve_token: &Addr, user: &str, amount: u128, secs: u64) {
+            let new_locked_until = Uint64::from(app.block_info().time.seconds() + secs);
+            app.execute_contract(
+                Addr::unchecked(user),
+                token.clone(),
+                &(Cw20ExecuteMsg::Send {
+                    contract: ve_token.to_string(),
+                    amount: Uint128::from(amount),
+                    msg: to_binary(
+                        &(ReceiveMsg::Lock { new_locked_until })
+                    ).unwrap(),
+                }),
+                &[]
+            ).unwrap();
+        }
+
+        #[test]
+        fn test_dao_voting_module() {
+            let mut app = App::default();
+
+            let (token, ve_token) = instantiate_ve_token(&mut app);
+            let proposal_code_id = app.store_code(
+                Box::new(
+                    ContractWrapper::new(proposal_execute, proposal_instantiate, proposal_query)
+                )
+            );
+
+            let proposal_module = app.instantiate_contract(
+                proposal_code_id,
+                Addr::unchecked("dao"),
+                &(MockProposalInstantiateMsg { voting_module: ve_token.to_string() }),
+                &[],
+                "proposal",
+                None
+            ).unwrap();
+
+            let dao: Addr = app.wrap().query_wasm_smart(&ve_token, &(QueryMsg::Dao {})).unwrap();
+            assert_eq!(dao, Addr::unchecked("dao"));
+            let info: InfoResponse = app
+                .wrap()
+                .query_wasm_smart(&ve_token, &(QueryMsg::Info {}))
+                .unwrap();
+            assert_eq!(info.info, ContractVersion {
+                contract: CONTRACT_NAME.to_string(),
+                version: CONTRACT_VERSION.to_string(),
+            });
+
+            // alice locks for max period, bob for half of it
+            lock(&mut app, &token, &ve_token, "alice", 100, MAX_LOCK_PERIOD);
+            lock(&mut app, &token, &ve_token, "bob", 100, MAX_LOCK_PERIOD / 2);
+            app.update_block(|block| {
+                block.height += 1;
+                block.time = block.time.plus_seconds(5);
+            });
+
+            app.execute_contract(
+                Addr::unchecked("proposer"),
+                proposal_module.clone(),
+                &(MockProposalExecuteMsg::Propose {}),
+                &[]
+            ).unwrap();
+
+            // Locking in the proposal block doesn't count for it
+            lock(&mut app, &token, &ve_token, "carol", 100, MAX_LOCK_PERIOD);
+
+            let proposal: MockProposal = app
+                .wrap()
+                .query_wasm_smart(&proposal_module, &(MockProposalQueryMsg::Proposal {}))
+                .unwrap();
+            assert_eq!(proposal.total_power, Uint128::from(150u8));
+
+            for voter in ["alice", "bob"] {
+                app.execute_contract(
+                    Addr::unchecked(voter),
+                    proposal_module.clone(),
+                    &(MockProposalExecuteMsg::Vote {}),
+                    &[]
+                ).unwrap();
+            }
+            let err = app
+                .execute_contract(
+                    Addr::unchecked("carol"),
+                    proposal_module.clone(),
+                    &(MockProposalExecuteMsg::Vote {}),
+                    &[]
+                )
+                .unwrap_err();
+            assert_eq!(err.root_cause().to_string(), "Generic error: no voting power");
+
+            let proposal: MockProposal = app
+                .wrap()
+                .query_wasm_smart(&proposal_module, &(MockProposalQueryMsg::Proposal {}))
+                .unwrap();
+            assert_eq!(proposal.yes, Uint128::from(150u8));
+
+            // Current height sees carol's lock
+            let height = app.block_info().height;
+            let power: VotingPowerAtHeightResponse = app
+                .wrap()
+                .query_wasm_smart(&ve_token, &(QueryMsg::VotingPowerAtHeight {
+                    address: "carol".to_string(),
+                    height: Some(height + 1),
+                }))
+                .unwrap();
+            assert_eq!(power, VotingPowerAtHeightResponse {
+                power: Uint128::from(100u8),
+                height: height + 1,
+            });
+            let total_power: TotalPowerAtHeightResponse = app
+                .wrap()
+                .query_wasm_smart(&ve_token, &(QueryMsg::TotalPowerAtHeight { height: None }))
+                .unwrap();
+            assert_eq!(total_power, TotalPowerAtHeightResponse {
+                power: Uint128::from(150u8),
+                height,
+            });
+        }
+
+        #[test]
+        fn test_dao_voting_power_until_kicked() {
+            let mut app = App::default();
+
+            let (token, ve_token) = instantiate_ve_token(&mut app);
+            let proposal_code_id = app.store_code(
+                Box::new(
+                    ContractWrapper::new(proposal_execute, proposal_instantiate, proposal_query)
+                )
+            );
+            let proposal_module = app.instantiate_contract(
+                proposal_code_id,
+                Addr::unchecked("dao"),
+                &(MockProposalInstantiateMsg { voting_module: ve_token.to_string() }),
+                &[],
+                "proposal",
+                None
+            ).unwrap();
+
+            let propose = |app: &mut App| -> MockProposal {
+                app.update_block(|block| {
+                    block.height += 1;
+                    block.time = block.time.plus_seconds(5);
+                });
+                app.execute_contract(
+                    Addr::unchecked("proposer"),
+                    proposal_module.clone(),
+                    &(MockProposalExecuteMsg::Propose {}),
+                    &[]
+                ).unwrap();
+                app.wrap()
+                    .query_wasm_smart(&proposal_module, &(MockProposalQueryMsg::Proposal {}))
+                    .unwrap()
+            };
+            let vote = |app: &mut App, voter: &str| {
+                app.execute_contract(
+                    Addr::unchecked(voter),
+                    proposal_module.clone(),
+                    &(MockProposalExecuteMsg::Vote {}),
+                    &[]
+                )
+            };
+
+            // alice locks for max period, bob for the minimum one
+            lock(&mut app, &token, &ve_token, "alice", 1000, MAX_LOCK_PERIOD);
+            lock(&mut app, &token, &ve_token, "bob", 1000, MIN_LOCK_PERIOD);
+            let bob_power = Uint128::from((1000 * MIN_LOCK_PERIOD) / MAX_LOCK_PERIOD);
+
+            // 1. Nobody touched bob's expired lock, it still counts for new proposals
+            app.update_block(|block| {
+                block.time = block.time.plus_seconds(MIN_LOCK_PERIOD);
+            });
+            let proposal = propose(&mut app);
+            assert_eq!(proposal.total_power, Uint128::from(1000u16) + bob_power);
+            vote(&mut app, "bob").unwrap();
+            let proposal: MockProposal = app
+                .wrap()
+                .query_wasm_smart(&proposal_module, &(MockProposalQueryMsg::Proposal {}))
+                .unwrap();
+            assert_eq!(proposal.yes, bob_power);
+
+            // 2. Anyone kicks it, the power is gone from the next block on
+            app.execute_contract(
+                Addr::unchecked("keeper"),
+                ve_token.clone(),
+                &(ExecuteMsg::Kick { account: "bob".to_string() }),
+                &[]
+            ).unwrap();
+
+            let proposal = propose(&mut app);
+            assert_eq!(proposal.total_power, Uint128::from(1000u16));
+            let err = vote(&mut app, "bob").unwrap_err();
+            assert_eq!(err.root_cause().to_string(), "Generic error: no voting power");
+            vote(&mut app, "alice").unwrap();
+
+            // The kick doesn't rewrite history
+            let power: VotingPowerAtHeightResponse = app
+                .wrap()
+                .query_wasm_smart(&ve_token, &(QueryMsg::VotingPowerAtHeight {
+                    address: "bob".to_string(),
+                    height: Some(proposal.start_height - 1),
+                }))
+                .unwrap();
+            assert_eq!(power.power, bob_power);
+        }
+
+        // Balance hook subscriber, records the last hook message
+        const LAST_HOOK: Item<hooks::VeBalanceChangedHookMsg> = Item::new("last_hook");
+
+        fn hook_instantiate(
+            _deps: DepsMut,
+            _env: Env,
+            _info: MessageInfo,
+            _msg: Empty
+        ) -> StdResult<Response> {
+            Ok(Response::new())
+        }
+
+        fn hook_execute(
+            deps: DepsMut,
+            _env: Env,
+            _info: MessageInfo,
+            msg: hooks::VeBalanceChangedExecuteMsg
+        ) -> StdResult<Response> {
+            let hooks::VeBalanceChangedExecuteMsg::VeBalanceChangedHook(msg) = msg;
+            LAST_HOOK.save(deps.storage, &msg)?;
+            Ok(Response::new())
+        }
+
+        fn failing_hook_execute(
+            _deps: DepsMut,
+            _env: Env,
+            _info: MessageInfo,
+            _msg: hooks::VeBalanceChangedExecuteMsg
+        ) -> StdResult<Response> {
+            Err(StdError::generic_err("hook failed"))
+        }
+
+        fn hook_query(deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+            to_binary(&LAST_HOOK.load(deps.storage)?)
+        }
+
+        #[test]
+        fn test_balance_hook_error_policy() {
+            let mut app = App::default();
+            let (token, ve_token) = instantiate_ve_token(&mut app);
+
+            let hook_code_id = app.store_code(
+                Box::new(ContractWrapper::new(hook_execute, hook_instantiate, hook_query))
+            );
+            let failing_hook_code_id = app.store_code(
+                Box::new(ContractWrapper::new(failing_hook_execute, hook_instantiate, hook_query))
+            );
+            let hook = app
+                .instantiate_contract(
+                    hook_code_id,
+                    Addr::unchecked("dao"),
+                    &Empty {},
+                    &[],
+                    "hook",
+                    None
+                )
+                .unwrap();
+            let failing_hook = app
+                .instantiate_contract(
+                    failing_hook_code_id,
+                    Addr::unchecked("dao"),
+                    &Empty {},
+                    &[],
+                    "failing_hook",
+                    None
+                )
+                .unwrap();
+
+            for (address, on_error) in [
+                (&hook, HookErrorPolicy::Revert),
+                (&failing_hook, HookErrorPolicy::Catch),
+            ] {
+                app.execute_contract(
+                    Addr::unchecked("dao"),
+                    ve_token.clone(),
+                    &(ExecuteMsg::AddBalanceHook { address: address.to_string(), on_error }),
+                    &[]
+                ).unwrap();
+            }
+
+            // 1. Caught failure doesn't revert the lock
+            let new_locked_until = Uint64::from(app.block_info().time.seconds() + MAX_LOCK_PERIOD);
+            let resp = app.execute_contract(
+                Addr::unchecked("alice"),
+                token.clone(),
+                &(Cw20ExecuteMsg::Send {
+                    contract: ve_token.to_string(),
+                    amount: Uint128::from(100u8),
+                    msg: to_binary(
+                        &(ReceiveMsg::Lock { new_locked_until })
+                    ).unwrap(),
+                }),
+                &[]
+            ).unwrap();
+            assert!(resp.events.iter().any(|event| event.ty == "wasm-balance_hook_failed"));
+
+            let last_hook: hooks::VeBalanceChangedHookMsg = app
+                .wrap()
+                .query_wasm_smart(&hook, &Empty {})
+                .unwrap();
+            assert_eq!(last_hook, hooks::VeBalanceChangedHookMsg {
+                account: "alice".to_string(),
+                old_balance: Uint128::zero(),
+                new_balance: Uint128::from(100u8),
+                locked_until: new_locked_until,
+            });
+
+            // 2. Failure reverts the lock
+            app.execute_contract(
+                Addr::unchecked("dao"),
+                ve_token.clone(),
+                &(ExecuteMsg::RemoveBalanceHook { address: failing_hook.to_string() }),
+                &[]
+            ).unwrap();
+            app.execute_contract(
+                Addr::unchecked("dao"),
+                ve_token.clone(),
+                &(ExecuteMsg::AddBalanceHook {
+                    address: failing_hook.to_string(),
+                    on_error: HookErrorPolicy::Revert,
+                }),
+                &[]
+            ).unwrap();
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked("bob"),
+                    token.clone(),
+                    &(Cw20ExecuteMsg::Send {
+                        contract: ve_token.to_string(),
+                        amount: Uint128::from(100u8),
+                        msg: to_binary(
+                            &(ReceiveMsg::Lock { new_locked_until })
+                        ).unwrap(),
+                    }),
+                    &[]
+                )
+                .unwrap_err();
+            assert_eq!(err.root_cause().to_string(), "Generic error: hook failed");
+
+            let balance: cw20::BalanceResponse = app
+                .wrap()
+                .query_wasm_smart(&ve_token, &(QueryMsg::Balance { address: "bob".to_string() }))
+                .unwrap();
+            assert_eq!(balance.balance, Uint128::zero());
+
+            // 3. After shutdown a failing hook cannot keep alice from withdrawing
+            let token_balance = |app: &App| -> Uint128 {
+                let msg = cw20::Cw20QueryMsg::Balance { address: "alice".to_string() };
+                let resp: cw20::BalanceResponse = app
+                    .wrap()
+                    .query_wasm_smart(&token, &msg)
+                    .unwrap();
+                resp.balance
+            };
+            let balance_before = token_balance(&app);
+            app.execute_contract(
+                Addr::unchecked("dao"),
+                ve_token.clone(),
+                &(ExecuteMsg::EmergencyShutdown { reward_policy: ShutdownRewardPolicy::Settle }),
+                &[]
+            ).unwrap();
+            let resp = app
+                .execute_contract(
+                    Addr::unchecked("alice"),
+                    ve_token.clone(),
+                    &(ExecuteMsg::Withdraw { position: 1 }),
+                    &[]
+                )
+                .unwrap();
+            assert!(resp.events.iter().any(|event| event.ty == "wasm-balance_hook_failed"));
+            assert_eq!(token_balance(&app), balance_before + Uint128::from(100u8));
+        }
     }
 }